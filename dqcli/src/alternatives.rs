@@ -0,0 +1,37 @@
+/// Same-name TLDs offered when the domain asked for is taken.
+pub const ALTERNATIVE_TLDS: usize = 5;
+/// Generated names offered under the TLD asked for.
+pub const ALTERNATIVE_NAMES: usize = 3;
+
+/// Prefixes and suffixes tried on a taken name, most common first.
+const AFFIXES: &[(&str, &str)] = &[
+    ("get", ""),
+    ("", "hq"),
+    ("try", ""),
+    ("", "app"),
+    ("use", ""),
+    ("", "labs"),
+];
+
+/// Names to try when `name` is taken: `getname`, `namehq`, `tryname`, ...
+pub fn name_variants(name: &str) -> impl Iterator<Item = String> + '_ {
+    AFFIXES
+        .iter()
+        .map(move |(prefix, suffix)| format!("{}{}{}", prefix, name, suffix))
+        .filter(|variant| variant.chars().count() <= 63)
+}
+
+/// What to offer instead of `name.tld`: the same name on the first
+/// `ALTERNATIVE_TLDS` of `ranked` other than `tld`, then the first
+/// `ALTERNATIVE_NAMES` variants of the name under `tld`.
+pub fn alternatives(name: &str, tld: &str, ranked: &[String]) -> Vec<String> {
+    let other_tlds = ranked
+        .iter()
+        .filter(|t| !t.eq_ignore_ascii_case(tld))
+        .take(ALTERNATIVE_TLDS)
+        .map(|t| format!("{}.{}", name, t));
+    let other_names = name_variants(name)
+        .take(ALTERNATIVE_NAMES)
+        .map(|variant| format!("{}.{}", variant, tld));
+    other_tlds.chain(other_names).collect()
+}
@@ -0,0 +1,165 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const DEFAULT_AUDIT_LOG_MAX_KB: u64 = 1024;
+
+/// Size past which `record` rotates the log to `audit.log.1`.
+static MAX_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_AUDIT_LOG_MAX_KB * 1024);
+
+/// Sets the rotation size from `[limits] audit_log_max_kb`.
+pub fn set_max_bytes(bytes: u64) {
+    MAX_BYTES.store(bytes, Ordering::Relaxed);
+}
+
+/// User-visible actions worth a trail when dq is part of a business process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    /// Registrar search opened for a domain
+    Purchase,
+    /// WHOIS/RDAP lookup page opened
+    Lookup,
+    /// Live site opened
+    Visit,
+    /// Domain copied to the clipboard
+    Copy,
+    WatchAdd,
+    WatchRemove,
+    WatchExport,
+    WatchImport,
+    /// Results written to `--output` or `--output-dir`
+    Export,
+    /// `dq report` written
+    Report,
+    Screenshot,
+    /// `[hooks] summary` run
+    SummaryHook,
+    /// Results published to `[publish] url`
+    Publish,
+    /// Newly registered lookalike reported by `dq protect`
+    ProtectAlert,
+}
+
+impl AuditAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            AuditAction::Purchase => "purchase",
+            AuditAction::Lookup => "lookup",
+            AuditAction::Visit => "visit",
+            AuditAction::Copy => "copy",
+            AuditAction::WatchAdd => "watch-add",
+            AuditAction::WatchRemove => "watch-remove",
+            AuditAction::WatchExport => "watch-export",
+            AuditAction::WatchImport => "watch-import",
+            AuditAction::Export => "export",
+            AuditAction::Report => "report",
+            AuditAction::Screenshot => "screenshot",
+            AuditAction::SummaryHook => "summary-hook",
+            AuditAction::Publish => "publish",
+            AuditAction::ProtectAlert => "protect-alert",
+        }
+    }
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix seconds
+    pub at: u64,
+    pub action: AuditAction,
+    pub detail: String,
+}
+
+fn audit_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|p| p.join("dq").join("audit.log"))
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    path.with_extension("log.1")
+}
+
+/// Appends an entry. The log is best effort: failing to write it never
+/// stops the action itself.
+pub fn record(action: AuditAction, detail: impl Into<String>) {
+    let Some(path) = audit_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let entry = AuditEntry {
+        at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        action,
+        detail: detail.into(),
+    };
+    let Ok(mut line) = serde_json::to_string(&entry) else {
+        return;
+    };
+    line.push('\n');
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        // One write per line under a lock keeps concurrent dq processes from interleaving
+        if file.lock().is_ok() {
+            let _ = file.write_all(line.as_bytes());
+            // Keep one previous generation; the lock is still held, so no
+            // other process appends to the file being moved
+            if file.metadata().is_ok_and(|m| m.len() >= MAX_BYTES.load(Ordering::Relaxed)) {
+                let _ = std::fs::rename(&path, rotated_path(&path));
+            }
+        }
+    }
+}
+
+/// Entries in the order they were written, the rotated generation first;
+/// unreadable lines are skipped.
+pub fn entries() -> Vec<AuditEntry> {
+    let Some(path) = audit_path() else {
+        return Vec::new();
+    };
+    [rotated_path(&path), path]
+        .into_iter()
+        .filter_map(|path| File::open(path).ok())
+        .flat_map(|file| {
+            BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str(&line).ok())
+        })
+        .collect()
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in UTC.
+pub fn format_utc(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
@@ -0,0 +1,170 @@
+use futures::{stream, StreamExt};
+use librdap_storm::{Availability, Prober};
+use serde::Serialize;
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    time::Duration,
+};
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    interrupt::{Interrupt, INTERRUPTED_EXIT},
+    usage::UsageRecorder,
+};
+
+/// Search spaces larger than this are only swept after a yes at the
+/// prompt, or with `--yes`.
+pub const CONFIRM_ABOVE: u64 = 1_000;
+/// Larger spaces are refused outright: even at `MAX_RATE` they take days.
+pub const MAX_SPACE: u64 = 500_000;
+/// Highest `--rate` accepted, in probes per second.
+pub const MAX_RATE: u32 = 20;
+
+const LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+const DIGITS: &str = "0123456789";
+
+/// Names to try, as the characters allowed at each position.
+pub struct Pattern(Vec<Vec<char>>);
+
+impl Pattern {
+    /// `?` is any letter, `#` any digit, `*` either, anything else stands
+    /// for itself (`c?t`, `###`, `go*`). A bare length or range (`3`,
+    /// `3-4`) is every all-letter name that long.
+    pub fn parse(pattern: &str) -> Result<Vec<Pattern>, String> {
+        let pattern = pattern.trim().to_lowercase();
+        if let Some((from, to)) = length_class(&pattern) {
+            if from == 0 || from > to || to > 63 {
+                return Err(format!("'{}' is not a length range between 1 and 63", pattern));
+            }
+            let letters: Vec<char> = LETTERS.chars().collect();
+            return Ok((from..=to).map(|len| Pattern(vec![letters.clone(); len])).collect());
+        }
+        let mut slots = Vec::new();
+        for c in pattern.chars() {
+            slots.push(match c {
+                '?' => LETTERS.chars().collect(),
+                '#' => DIGITS.chars().collect(),
+                '*' => LETTERS.chars().chain(DIGITS.chars()).collect(),
+                'a'..='z' | '0'..='9' | '-' => vec![c],
+                _ => {
+                    return Err(format!(
+                        "'{}' can't be part of a domain name; use letters, digits, '-' and the wildcards ? # *",
+                        c
+                    ))
+                }
+            });
+        }
+        if slots.is_empty() || slots.len() > 63 {
+            return Err("a pattern is 1 to 63 characters long".to_string());
+        }
+        Ok(vec![Pattern(slots)])
+    }
+
+    /// How many names the pattern matches.
+    pub fn size(&self) -> u64 {
+        self.0.iter().fold(1u64, |n, slot| n.saturating_mul(slot.len() as u64))
+    }
+
+    /// Every name the pattern matches, letters before digits.
+    pub fn names(self) -> impl Iterator<Item = String> {
+        let mut next = Some(vec![0; self.0.len()]);
+        std::iter::from_fn(move || {
+            let indices = next.as_mut()?;
+            let name = indices.iter().zip(&self.0).map(|(&i, slot)| slot[i]).collect();
+            // Count up like an odometer; past the last name there is none
+            let carried = indices.iter_mut().zip(&self.0).rev().all(|(i, slot)| {
+                *i = (*i + 1) % slot.len();
+                *i == 0
+            });
+            if carried {
+                next = None;
+            }
+            Some(name)
+        })
+    }
+}
+
+fn length_class(pattern: &str) -> Option<(usize, usize)> {
+    let (from, to) = pattern.split_once('-').unwrap_or((pattern, pattern));
+    Some((from.parse().ok()?, to.parse().ok()?))
+}
+
+/// Asks on the terminal whether to sweep `size` names. Without a terminal
+/// there is nobody to ask, so the answer is no.
+pub fn confirm(size: u64, tld: &str, rate: u32) -> io::Result<bool> {
+    if !io::stdin().is_terminal() {
+        eprintln!("Error: {} names need confirming; pass --yes to sweep them without a terminal", size);
+        return Ok(false);
+    }
+    let minutes = size / rate as u64 / 60;
+    let eta = if minutes < 90 { format!("{} min", minutes.max(1)) } else { format!("{:.1} h", minutes as f64 / 60.0) };
+    eprint!("Probe {} names under .{} (about {} at {}/s)? [y/N] ", size, tld, eta, rate);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Printed by `dq brute --json` for each available name.
+#[derive(Debug, Serialize)]
+struct BruteHit {
+    domain: String,
+    explanation: String,
+}
+
+/// Probes every name of `patterns` under `tld`, at most `rate` a second,
+/// printing the available ones as they are found.
+pub async fn run(
+    prober: Prober,
+    patterns: Vec<Pattern>,
+    tld: &str,
+    rate: u32,
+    json: bool,
+    usage: &UsageRecorder,
+) -> io::Result<()> {
+    let total: u64 = patterns.iter().map(Pattern::size).sum();
+    let suffix = tld.to_string();
+    let domains = patterns.into_iter().flat_map(Pattern::names).map(move |name| format!("{}.{}", name, suffix));
+
+    // One probe starts per tick, whatever answers it (DNS, RDAP, WHOIS)
+    let mut ticker = tokio::time::interval(Duration::from_secs(1) / rate);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let ticks = Box::pin(stream::unfold(ticker, |mut ticker| async move {
+        ticker.tick().await;
+        Some(((), ticker))
+    }));
+    let interrupt = Interrupt::install();
+    let prober_ref = &prober;
+    let mut results = stream::iter(interrupt.guard(domains))
+        .zip(ticks)
+        .map(|(domain, ())| async move { prober_ref.probe_one(&domain).await })
+        .buffer_unordered(rate as usize * 2);
+
+    let (mut probed, mut available, mut unknown) = (0u64, 0u64, 0u64);
+    while let Some(result) = results.next().await {
+        probed += 1;
+        match result.availability {
+            Availability::Available => {
+                available += 1;
+                if json {
+                    let hit = BruteHit { domain: result.domain, explanation: result.explanation };
+                    println!("{}", serde_json::to_string(&hit)?);
+                } else {
+                    println!("{}", result.domain);
+                }
+                io::stdout().flush()?;
+            }
+            ref availability @ Availability::Unknown { .. } if !availability.is_invalid_name() => unknown += 1,
+            _ => {}
+        }
+        if probed % 100 == 0 {
+            eprintln!("{} of {} probed, {} available", probed, total, available);
+        }
+    }
+    usage.record(1, probed, &prober.metrics());
+    eprintln!("{} of {} names probed: {} available, {} unknown", probed, total, available, unknown);
+    if interrupt.is_set() {
+        std::process::exit(INTERRUPTED_EXIT);
+    }
+    Ok(())
+}
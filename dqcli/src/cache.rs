@@ -0,0 +1,257 @@
+use librdap_storm::{
+    fetch_iana_tlds, tlds::TldError, Availability, Confidence, ProbeResult, ProbeSource,
+};
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// How long the IANA TLD list stays fresh.
+const LIST_TTL_SECS: u64 = 24 * 3600;
+pub const DEFAULT_RESULT_TTL_MINUTES: u64 = 15;
+pub const DEFAULT_MAX_CACHED_RESULTS: usize = 10_000;
+
+const TLDS_FILE: &str = "tlds.json";
+/// Raw IANA `dns.json`, maintained by the prober itself
+const BOOTSTRAP_FILE: &str = "rdap-dns.json";
+const RESULTS_FILE: &str = "results.json";
+/// Every result `--cached` runs have seen
+#[cfg(feature = "sqlite-cache")]
+const RESULT_DB_FILE: &str = "results.sqlite";
+
+fn cache_path(name: &str) -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("dq").join(name))
+}
+
+#[cfg(feature = "sqlite-cache")]
+pub fn result_db_path() -> Option<PathBuf> {
+    cache_path(RESULT_DB_FILE)
+}
+
+/// Drops the oldest verdicts until at most `max` remain.
+fn evict_oldest(entries: &mut BTreeMap<String, CachedResult>, max: usize) {
+    if entries.len() <= max {
+        return;
+    }
+    let mut ages: Vec<u64> = entries.values().map(|e| e.checked_at).collect();
+    ages.sort_unstable();
+    let cutoff = ages[entries.len() - max];
+    entries.retain(|_, e| e.checked_at >= cutoff);
+    // Ties at the cutoff can leave a few too many
+    while entries.len() > max {
+        let Some(oldest) = entries.iter().min_by_key(|(_, e)| e.checked_at).map(|(k, _)| k.clone()) else {
+            break;
+        };
+        entries.remove(&oldest);
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Reads a cache file under a shared lock, so a write from another dq
+/// process is never seen half-done.
+fn read_locked<T: DeserializeOwned>(name: &str) -> Option<T> {
+    read_file_locked(&cache_path(name)?)
+}
+
+/// `read_locked` for a file outside the cache dir.
+pub fn read_file_locked<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let mut file = File::open(path).ok()?;
+    file.lock_shared().ok()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Read-modify-write of a cache file under an exclusive lock, so
+/// concurrent dq processes merge their updates instead of clobbering them.
+fn update_locked<T, F>(name: &str, update: F)
+where
+    T: DeserializeOwned + Serialize + Default,
+    F: FnOnce(&mut T),
+{
+    if let Some(path) = cache_path(name) {
+        update_file_locked(&path, update);
+    }
+}
+
+/// `update_locked` for a file outside the cache dir.
+pub fn update_file_locked<T, F>(path: &Path, update: F)
+where
+    T: DeserializeOwned + Serialize + Default,
+    F: FnOnce(&mut T),
+{
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let Ok(mut file) = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+    else {
+        return;
+    };
+    if file.lock().is_err() {
+        return;
+    }
+
+    let mut content = String::new();
+    let _ = file.read_to_string(&mut content);
+    let mut value: T = serde_json::from_str(&content).unwrap_or_default();
+    update(&mut value);
+
+    if let Ok(json) = serde_json::to_string(&value) {
+        let _ = file.set_len(0);
+        let _ = file.rewind();
+        let _ = file.write_all(json.as_bytes());
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stamped<T> {
+    /// Unix seconds
+    fetched_at: u64,
+    value: T,
+}
+
+/// Returns the cached value when fresh, otherwise fetches and caches a new
+/// one, falling back to a stale copy if the fetch fails.
+async fn cached_or_fetch<T, E, Fut>(name: &str, fetch: Fut) -> Result<T, E>
+where
+    T: DeserializeOwned + Serialize + Default,
+    Fut: std::future::Future<Output = Result<T, E>>,
+{
+    let cached: Option<Stamped<T>> = read_locked(name);
+    let cached = match cached {
+        Some(c) if now_secs().saturating_sub(c.fetched_at) < LIST_TTL_SECS => return Ok(c.value),
+        other => other,
+    };
+
+    match fetch.await {
+        Ok(value) => {
+            let json = serde_json::to_value(&value).ok();
+            update_locked(name, |stamped: &mut Stamped<serde_json::Value>| {
+                if let Some(json) = json {
+                    *stamped = Stamped { fetched_at: now_secs(), value: json };
+                }
+            });
+            Ok(value)
+        }
+        Err(e) => cached.map(|c| c.value).ok_or(e),
+    }
+}
+
+/// The IANA TLD list, shared across dq processes for a day.
+pub async fn iana_tlds(client: &Client) -> Result<Vec<String>, TldError> {
+    cached_or_fetch(TLDS_FILE, fetch_iana_tlds(client)).await
+}
+
+/// Where probers keep the IANA RDAP bootstrap between dq processes.
+pub fn bootstrap_path() -> Option<PathBuf> {
+    cache_path(BOOTSTRAP_FILE)
+}
+
+/// A definitive verdict remembered from an earlier probe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResult {
+    /// Unix seconds
+    pub checked_at: u64,
+    pub available: bool,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub explanation: String,
+    /// `Confidence::name` of the original verdict
+    #[serde(default)]
+    pub confidence: Option<String>,
+}
+
+impl CachedResult {
+    /// Replays the verdict as a zero-duration probe result.
+    pub fn to_probe_result(&self, domain: &str) -> ProbeResult {
+        let age_secs = now_secs().saturating_sub(self.checked_at);
+        ProbeResult {
+            domain: domain.to_string(),
+            availability: if self.available { Availability::Available } else { Availability::Taken },
+            duration: Duration::ZERO,
+            headers: Vec::new(),
+            endpoint: self.endpoint.clone(),
+            explanation: format!("cached {}s ago: {}", age_secs, self.explanation),
+            source: ProbeSource::Cache,
+            confidence: self.confidence.as_deref().and_then(Confidence::from_name).unwrap_or(Confidence::Medium),
+            raw: None,
+        }
+    }
+}
+
+/// Available/taken verdicts shared on disk between TUI and NDJSON runs.
+/// Errors are never cached, so a flaky endpoint is retried next time.
+#[derive(Debug, Default)]
+pub struct ResultCache {
+    ttl_secs: u64,
+    max_entries: usize,
+    entries: BTreeMap<String, CachedResult>,
+    /// Verdicts from this process, merged into the file by `save`
+    fresh: BTreeMap<String, CachedResult>,
+}
+
+impl ResultCache {
+    /// Loads verdicts younger than `ttl`, keeping at most `max_entries` of
+    /// them in memory and on disk.
+    pub fn load(ttl: Duration, max_entries: usize) -> Self {
+        let ttl_secs = ttl.as_secs();
+        let now = now_secs();
+        let mut entries: BTreeMap<String, CachedResult> = read_locked(RESULTS_FILE).unwrap_or_default();
+        entries.retain(|_, e| now.saturating_sub(e.checked_at) < ttl_secs);
+        evict_oldest(&mut entries, max_entries);
+        Self { ttl_secs, max_entries, entries, fresh: BTreeMap::new() }
+    }
+
+    pub fn get(&self, domain: &str) -> Option<&CachedResult> {
+        self.entries.get(domain)
+    }
+
+    pub fn record(&mut self, result: &ProbeResult) {
+        let available = match result.availability {
+            Availability::Available => true,
+            Availability::Taken => false,
+            // Registry holds and premium lists change; ask again next time
+            Availability::Reserved | Availability::Premium | Availability::Unknown { .. } => return,
+        };
+        let entry = CachedResult {
+            checked_at: now_secs(),
+            available,
+            endpoint: result.endpoint.clone(),
+            explanation: result.explanation.clone(),
+            confidence: Some(result.confidence.name().to_string()),
+        };
+        self.entries.insert(result.domain.clone(), entry.clone());
+        self.fresh.insert(result.domain.clone(), entry);
+        evict_oldest(&mut self.entries, self.max_entries);
+        evict_oldest(&mut self.fresh, self.max_entries);
+    }
+
+    pub fn save(&self) {
+        if self.fresh.is_empty() {
+            return;
+        }
+        let now = now_secs();
+        update_locked(RESULTS_FILE, |entries: &mut BTreeMap<String, CachedResult>| {
+            entries.retain(|_, e| now.saturating_sub(e.checked_at) < self.ttl_secs);
+            entries.extend(self.fresh.iter().map(|(k, v)| (k.clone(), v.clone())));
+            evict_oldest(entries, self.max_entries);
+        });
+    }
+}
@@ -0,0 +1,86 @@
+use clap::CommandFactory;
+use std::{
+    backtrace::Backtrace,
+    error::Error,
+    fmt::Write,
+    path::PathBuf,
+    sync::OnceLock,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{audit::format_utc, instance::state_path, Args};
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// The subcommand and flag names dq was started with. Flag values and
+/// queries are left out, so reports don't carry the names being searched.
+fn invocation() -> String {
+    let command = Args::command();
+    let mut words = vec!["dq".to_string()];
+    for (i, arg) in std::env::args().skip(1).enumerate() {
+        if arg.starts_with('-') {
+            words.push(arg.split('=').next().unwrap_or_default().to_string());
+        } else if i == 0 && command.get_subcommands().any(|s| s.get_name() == arg) {
+            words.push(arg);
+        }
+    }
+    words.join(" ")
+}
+
+fn write_report(message: &str, location: &str, backtrace: &Backtrace) -> Option<PathBuf> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut report = String::new();
+    let _ = writeln!(report, "dq {} crash report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "time: {} UTC", format_utc(now));
+    let _ = writeln!(report, "os: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "invocation: {}", invocation());
+    if let Some(started) = STARTED.get() {
+        let _ = writeln!(report, "uptime: {:.1}s", started.elapsed().as_secs_f64());
+    }
+    let thread = std::thread::current();
+    let _ = writeln!(report, "thread: {}", thread.name().unwrap_or("unnamed"));
+    let _ = writeln!(report, "panic: {}", message);
+    let _ = writeln!(report, "at: {}", location);
+    let _ = writeln!(report, "\n{}", backtrace);
+
+    let path = state_path(&format!("crashes/{}-{}.txt", now, std::process::id()))?;
+    std::fs::create_dir_all(path.parent()?).ok()?;
+    std::fs::write(&path, report).ok()?;
+    Some(path)
+}
+
+/// On a panic, after the usual message, saves a crash report (version,
+/// platform, invocation, backtrace) in the state dir and says where, so
+/// it can go with a bug report.
+pub fn install() {
+    STARTED.get_or_init(Instant::now);
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+        if let Some(path) = write_report(&message, &location, &Backtrace::force_capture()) {
+            eprintln!(
+                "\ndq crashed. A report was saved to {}\nPlease attach it to an issue at {}/issues",
+                path.display(),
+                env!("CARGO_PKG_REPOSITORY")
+            );
+        }
+    }));
+}
+
+/// `Error: <error>`, then each underlying cause on a line of its own.
+pub fn describe(error: &dyn Error) -> String {
+    let mut text = format!("Error: {}", error);
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let _ = write!(text, "\n  caused by: {}", cause);
+        source = cause.source();
+    }
+    text
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+/// Result state as far as filters are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    Available,
+    Taken,
+    Reserved,
+    Premium,
+    Error,
+    /// Pending or in flight
+    Checking,
+}
+
+impl StatusKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "available" | "free" => Some(StatusKind::Available),
+            "taken" => Some(StatusKind::Taken),
+            "reserved" => Some(StatusKind::Reserved),
+            "premium" => Some(StatusKind::Premium),
+            "error" | "unknown" => Some(StatusKind::Error),
+            "checking" | "pending" => Some(StatusKind::Checking),
+            _ => None,
+        }
+    }
+}
+
+/// What a filter sees of one result row.
+pub struct FilterItem<'a> {
+    pub domain: &'a str,
+    pub tld: &'a str,
+    pub status: StatusKind,
+    /// In the display currency
+    pub price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    fn holds(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            Cmp::Eq => ordering == Equal,
+            Cmp::Ne => ordering != Equal,
+            Cmp::Lt => ordering == Less,
+            Cmp::Le => ordering != Greater,
+            Cmp::Gt => ordering == Greater,
+            Cmp::Ge => ordering != Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Status(bool, StatusKind),
+    /// Length of the full domain, TLD included
+    Len(Cmp, usize),
+    TldLen(Cmp, usize),
+    Price(Cmp, f64),
+    /// TLD membership; `false` for `tld not in ...` / `tld!=...`
+    Tld(bool, Vec<String>),
+}
+
+/// A conjunction of terms typed after `:` in the TUI, e.g.
+/// `status=available len<=8 tld in startup price<20`.
+#[derive(Debug, Clone)]
+pub struct FilterExpr {
+    source: String,
+    terms: Vec<Term>,
+}
+
+/// Splits `key<op>value` on the first operator.
+fn split_comparison(word: &str) -> Option<(&str, Cmp, &str)> {
+    let pos = word.find(['=', '!', '<', '>'])?;
+    let (key, rest) = word.split_at(pos);
+    let (cmp, value) = if let Some(v) = rest.strip_prefix("<=") {
+        (Cmp::Le, v)
+    } else if let Some(v) = rest.strip_prefix(">=") {
+        (Cmp::Ge, v)
+    } else if let Some(v) = rest.strip_prefix("!=") {
+        (Cmp::Ne, v)
+    } else if let Some(v) = rest.strip_prefix("==") {
+        (Cmp::Eq, v)
+    } else if let Some(v) = rest.strip_prefix('=') {
+        (Cmp::Eq, v)
+    } else if let Some(v) = rest.strip_prefix('<') {
+        (Cmp::Lt, v)
+    } else if let Some(v) = rest.strip_prefix('>') {
+        (Cmp::Gt, v)
+    } else {
+        return None;
+    };
+    Some((key, cmp, value))
+}
+
+/// TLDs named by a comma-separated list whose items may be `[groups]` names.
+fn tld_set(list: &str, groups: &BTreeMap<String, Vec<String>>) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().trim_start_matches(['@', '.']).to_lowercase())
+        .filter(|item| !item.is_empty())
+        .flat_map(|item| groups.get(&item).cloned().unwrap_or_else(|| vec![item]))
+        .map(|tld| tld.to_lowercase())
+        .collect()
+}
+
+impl FilterExpr {
+    pub fn parse(input: &str, groups: &BTreeMap<String, Vec<String>>) -> Result<Self, String> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let mut terms = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            let word = words[i];
+            // `tld in a,b` / `tld not in @group`
+            if word.eq_ignore_ascii_case("tld") {
+                let (keep, list) = match (words.get(i + 1), words.get(i + 2), words.get(i + 3)) {
+                    (Some(w), Some(list), _) if w.eq_ignore_ascii_case("in") => {
+                        i += 3;
+                        (true, *list)
+                    }
+                    (Some(not), Some(w), Some(list))
+                        if not.eq_ignore_ascii_case("not") && w.eq_ignore_ascii_case("in") =>
+                    {
+                        i += 4;
+                        (false, *list)
+                    }
+                    _ => return Err("expected `tld in <tlds or group>`".to_string()),
+                };
+                terms.push(Term::Tld(keep, tld_set(list, groups)));
+                continue;
+            }
+
+            let (key, cmp, value) =
+                split_comparison(word).ok_or_else(|| format!("`{}`: expected key<op>value", word))?;
+            let number = |value: &str| value.parse::<f64>().map_err(|_| format!("`{}`: not a number", word));
+            let term = match key.to_lowercase().as_str() {
+                "status" => {
+                    let status =
+                        StatusKind::from_name(&value.to_lowercase()).ok_or_else(|| format!("`{}`: unknown status", value))?;
+                    match cmp {
+                        Cmp::Eq => Term::Status(true, status),
+                        Cmp::Ne => Term::Status(false, status),
+                        _ => return Err(format!("`{}`: status only supports = and !=", word)),
+                    }
+                }
+                "len" => Term::Len(cmp, number(value)? as usize),
+                "tldlen" => Term::TldLen(cmp, number(value)? as usize),
+                "price" => Term::Price(cmp, number(value)?),
+                "tld" => match cmp {
+                    Cmp::Eq => Term::Tld(true, tld_set(value, groups)),
+                    Cmp::Ne => Term::Tld(false, tld_set(value, groups)),
+                    _ => return Err(format!("`{}`: tld only supports =, != and in", word)),
+                },
+                other => return Err(format!("unknown filter key `{}` (status, len, tldlen, price, tld)", other)),
+            };
+            terms.push(term);
+            i += 1;
+        }
+        Ok(Self { source: input.trim().to_string(), terms })
+    }
+
+    /// The filter as typed, for display.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, item: &FilterItem) -> bool {
+        self.terms.iter().all(|term| match term {
+            Term::Status(keep, status) => (item.status == *status) == *keep,
+            Term::Len(cmp, n) => cmp.holds(item.domain.chars().count().cmp(n)),
+            Term::TldLen(cmp, n) => cmp.holds(item.tld.chars().count().cmp(n)),
+            // Rows without a known price never match a price condition
+            Term::Price(cmp, limit) => item.price.is_some_and(|p| cmp.holds(p.total_cmp(limit))),
+            Term::Tld(keep, tlds) => tlds.iter().any(|t| t == item.tld) == *keep,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups() -> BTreeMap<String, Vec<String>> {
+        BTreeMap::from([("startup".to_string(), vec!["io".to_string(), "ai".to_string(), "dev".to_string()])])
+    }
+
+    fn item(domain: &str, status: StatusKind, price: Option<f64>) -> FilterItem<'_> {
+        let tld = domain.split_once('.').map_or("", |(_, tld)| tld);
+        FilterItem { domain, tld, status, price }
+    }
+
+    fn matches(expr: &str, item: &FilterItem) -> bool {
+        FilterExpr::parse(expr, &groups()).unwrap().matches(item)
+    }
+
+    #[test]
+    fn combines_terms() {
+        let expr = "status=available len<=8 tld in startup price<20";
+        assert!(matches(expr, &item("acme.io", StatusKind::Available, Some(19.5))));
+        assert!(!matches(expr, &item("acme.io", StatusKind::Available, Some(20.0))));
+        assert!(!matches(expr, &item("acme.com", StatusKind::Available, Some(9.0))));
+        assert!(!matches(expr, &item("acmecorp.io", StatusKind::Available, Some(9.0))));
+        assert!(!matches(expr, &item("acme.io", StatusKind::Taken, Some(9.0))));
+        // An empty filter keeps everything
+        assert!(matches("", &item("acme.io", StatusKind::Checking, None)));
+    }
+
+    #[test]
+    fn negates_status_and_tlds() {
+        let taken = item("acme.com", StatusKind::Taken, None);
+        assert!(matches("status!=available", &taken));
+        assert!(matches("status=taken tld!=io,ai", &taken));
+        assert!(!matches("tld not in com,net", &taken));
+        assert!(matches("TLD NOT IN @startup", &taken));
+        assert!(matches("tld=.COM", &taken));
+        assert!(matches("status=unknown", &item("acme.com", StatusKind::Error, None)));
+    }
+
+    #[test]
+    fn compares_lengths_and_prices() {
+        let row = item("acme.co.uk", StatusKind::Available, Some(7.25));
+        assert!(matches("len==10 tldlen>2 tldlen>=5", &row));
+        assert!(!matches("tldlen<5", &row));
+        assert!(matches("price>=7.25 price<=7.25", &row));
+        assert!(!matches("price>7.25", &row));
+        // Without a price, no price condition holds
+        assert!(!matches("price>=0", &item("acme.io", StatusKind::Available, None)));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for expr in ["status", "status<available", "status=gone", "len<=eight", "color=red", "tld", "tld in", "tld not io", "tld>io"] {
+            assert!(FilterExpr::parse(expr, &groups()).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn keeps_source_for_display() {
+        let expr = FilterExpr::parse("  status=available   len<=8 ", &groups()).unwrap();
+        assert_eq!(expr.source(), "status=available   len<=8");
+    }
+}
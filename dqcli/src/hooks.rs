@@ -0,0 +1,62 @@
+use serde::Serialize;
+use std::{io, process::Stdio, time::Duration};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use crate::audit::{self, AuditAction};
+
+/// A hook still running after this long is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runs `[hooks] summary` through the shell with `summary` as JSON on its
+/// stdin. The hook's output goes to stderr, keeping stdout for records;
+/// a failing hook is reported but doesn't fail the run.
+pub async fn run_summary_hook(command: Option<&str>, summary: &impl Serialize) {
+    let Some(command) = command else {
+        return;
+    };
+    let result = match serde_json::to_string(summary) {
+        Ok(json) => run(command, json).await,
+        Err(e) => Err(e.into()),
+    };
+    match result {
+        Ok(()) => audit::record(AuditAction::SummaryHook, command),
+        Err(e) => {
+            eprintln!("Warning: summary hook `{}` failed: {}", command, e);
+            audit::record(AuditAction::SummaryHook, format!("{} (failed: {})", command, e));
+        }
+    }
+}
+
+async fn run(command: &str, json: String) -> io::Result<()> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(io::stderr())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // A hook that doesn't read its input isn't an error
+    let _ = stdin.write_all(format!("{}\n", json).as_bytes()).await;
+    drop(stdin);
+
+    let status = tokio::time::timeout(HOOK_TIMEOUT, child.wait())
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, format!("still running after {}s, killed", HOOK_TIMEOUT.as_secs())))??;
+    if !status.success() {
+        return Err(io::Error::other(status.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
@@ -0,0 +1,86 @@
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{self, Read, Seek, Write},
+    path::PathBuf,
+};
+
+#[cfg(unix)]
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+};
+
+pub fn state_path(name: &str) -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|p| p.join("dq").join(name))
+}
+
+/// Held by the one running watcher. Dropping it (or the process dying)
+/// releases the lock, so a crashed watcher never blocks the next one.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+/// Another process holds the watcher lock.
+#[derive(Debug, Clone, Copy)]
+pub struct AlreadyRunning {
+    /// From the lock file; `None` if it couldn't be read
+    pub pid: Option<u32>,
+}
+
+/// Claims the watcher role for this user.
+pub fn acquire() -> io::Result<Result<InstanceLock, AlreadyRunning>> {
+    let Some(path) = state_path("watch.lock") else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no state directory"));
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Not truncated on open: the pid inside belongs to whoever holds the lock
+    let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+    match file.try_lock() {
+        Ok(()) => {
+            file.set_len(0)?;
+            file.rewind()?;
+            writeln!(file, "{}", std::process::id())?;
+            Ok(Ok(InstanceLock { _file: file }))
+        }
+        Err(TryLockError::WouldBlock) => {
+            let mut content = String::new();
+            let pid = file.read_to_string(&mut content).ok().and_then(|_| content.trim().parse().ok());
+            Ok(Err(AlreadyRunning { pid }))
+        }
+        Err(TryLockError::Error(e)) => Err(e),
+    }
+}
+
+/// Listens on the watcher socket. Only call while holding the lock: a
+/// leftover socket file from a crashed watcher is removed first.
+#[cfg(unix)]
+pub fn listen(_lock: &InstanceLock) -> io::Result<UnixListener> {
+    let path = state_path("watch.sock").ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))?;
+    let _ = std::fs::remove_file(&path);
+    UnixListener::bind(path)
+}
+
+/// Sends one request line to the running watcher and hands each reply line
+/// to `on_line` until the watcher closes the connection.
+#[cfg(unix)]
+pub async fn send(request: &str, mut on_line: impl FnMut(&str)) -> io::Result<()> {
+    let path = state_path("watch.sock").ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))?;
+    let mut stream = UnixStream::connect(path).await?;
+    stream.write_all(request.trim().as_bytes()).await?;
+    stream.write_all(b"\n").await?;
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next_line().await? {
+        on_line(&line);
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn send(_request: &str, _on_line: impl FnMut(&str)) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "talking to the watcher needs Unix sockets"))
+}
@@ -0,0 +1,42 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Exit status of a sweep cut short by Ctrl-C (128 + SIGINT).
+pub const INTERRUPTED_EXIT: i32 = 130;
+
+/// Set by the first Ctrl-C of a streaming sweep, which then stops handing
+/// out new probes and finishes the ones in flight. A second Ctrl-C exits
+/// at once.
+#[derive(Clone, Default)]
+pub struct Interrupt(Arc<AtomicBool>);
+
+impl Interrupt {
+    /// Traps SIGINT for the rest of the process. Needs a tokio runtime.
+    pub fn install() -> Self {
+        let interrupt = Self::default();
+        let flag = Arc::clone(&interrupt.0);
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            flag.store(true, Ordering::Relaxed);
+            eprintln!("Interrupted: finishing probes in flight (Ctrl-C again to quit now)");
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(INTERRUPTED_EXIT);
+            }
+        });
+        interrupt
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `items` until the first Ctrl-C.
+    pub fn guard<I: IntoIterator>(&self, items: I) -> impl Iterator<Item = I::Item> {
+        let interrupt = self.clone();
+        items.into_iter().take_while(move |_| !interrupt.is_set())
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(feature = "tui")]
+use crossterm::event::KeyCode;
+#[cfg(feature = "tui")]
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Quit,
+    Down,
+    Up,
+    #[cfg(feature = "tui")]
+    PageDown,
+    #[cfg(feature = "tui")]
+    PageUp,
+    Top,
+    Bottom,
+    Edit,
+    Copy,
+    Open,
+    Filter,
+    ToggleRegistrable,
+    ToggleMode,
+    Screenshot,
+    FilterExpr,
+    Settings,
+    Favorite,
+    Mark,
+}
+
+impl KeyAction {
+    /// Actions that can be rebound from the `[keys]` config section.
+    pub const NAMES: &'static [&'static str] = &[
+        "quit", "down", "up", "top", "bottom", "edit", "copy", "open", "filter", "registrable",
+        "mode", "screenshot", "where", "settings", "favorite", "mark",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "quit" => Some(KeyAction::Quit),
+            "down" => Some(KeyAction::Down),
+            "up" => Some(KeyAction::Up),
+            "top" => Some(KeyAction::Top),
+            "bottom" => Some(KeyAction::Bottom),
+            "edit" => Some(KeyAction::Edit),
+            "copy" => Some(KeyAction::Copy),
+            "open" => Some(KeyAction::Open),
+            "filter" => Some(KeyAction::Filter),
+            "registrable" => Some(KeyAction::ToggleRegistrable),
+            "mode" => Some(KeyAction::ToggleMode),
+            "screenshot" => Some(KeyAction::Screenshot),
+            "where" => Some(KeyAction::FilterExpr),
+            "settings" => Some(KeyAction::Settings),
+            "favorite" => Some(KeyAction::Favorite),
+            "mark" => Some(KeyAction::Mark),
+            _ => None,
+        }
+    }
+}
+
+/// Maps keys to actions in results mode. Special keys (arrows, Enter, Tab,
+/// Esc, ...) are fixed; character bindings can be overridden from config.
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    chars: HashMap<char, KeyAction>,
+}
+
+#[cfg(feature = "tui")]
+impl Keymap {
+    /// Binds `c` to `action`, replacing the action's previous character.
+    pub fn bind(&mut self, c: char, action: KeyAction) {
+        self.chars.retain(|_, a| *a != action);
+        self.chars.insert(c, action);
+    }
+
+    pub fn action(&self, code: KeyCode) -> Option<KeyAction> {
+        match code {
+            KeyCode::Char(c) => self.chars.get(&c).copied(),
+            KeyCode::Esc => Some(KeyAction::Quit),
+            KeyCode::Down => Some(KeyAction::Down),
+            KeyCode::Up => Some(KeyAction::Up),
+            KeyCode::PageDown => Some(KeyAction::PageDown),
+            KeyCode::PageUp => Some(KeyAction::PageUp),
+            KeyCode::Home => Some(KeyAction::Top),
+            KeyCode::End => Some(KeyAction::Bottom),
+            KeyCode::Enter => Some(KeyAction::Copy),
+            KeyCode::Tab => Some(KeyAction::Filter),
+            _ => None,
+        }
+    }
+
+    /// The action bound to character `c`.
+    pub fn action_for(&self, c: char) -> Option<KeyAction> {
+        self.chars.get(&c).copied()
+    }
+
+    /// The character currently bound to `action`, for help text.
+    pub fn key_for(&self, action: KeyAction) -> Option<char> {
+        self.chars
+            .iter()
+            .find(|(_, a)| **a == action)
+            .map(|(c, _)| *c)
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Default for Keymap {
+    fn default() -> Self {
+        let chars = [
+            ('q', KeyAction::Quit),
+            ('j', KeyAction::Down),
+            ('k', KeyAction::Up),
+            ('g', KeyAction::Top),
+            ('G', KeyAction::Bottom),
+            ('i', KeyAction::Edit),
+            ('y', KeyAction::Copy),
+            ('o', KeyAction::Open),
+            ('f', KeyAction::Filter),
+            ('r', KeyAction::ToggleRegistrable),
+            ('m', KeyAction::ToggleMode),
+            ('s', KeyAction::Screenshot),
+            (':', KeyAction::FilterExpr),
+            (',', KeyAction::Settings),
+            ('*', KeyAction::Favorite),
+            (' ', KeyAction::Mark),
+        ]
+        .into_iter()
+        .collect();
+
+        Self { chars }
+    }
+}
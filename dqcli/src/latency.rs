@@ -0,0 +1,82 @@
+use hdrhistogram::serialization::interval_log::{IntervalLogWriterBuilder, Tag};
+use hdrhistogram::serialization::V2DeflateSerializer;
+use hdrhistogram::Histogram;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Probe durations of one sweep, in microseconds.
+pub struct ProbeLatency {
+    histogram: Histogram<u64>,
+    started: SystemTime,
+    clock: Instant,
+}
+
+/// Percentiles of the probe durations of a sweep, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LatencySummary {
+    /// Probes measured; cached and skipped records are left out
+    pub count: u64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub p999_ms: f64,
+    pub max_ms: f64,
+}
+
+impl ProbeLatency {
+    pub fn new() -> Self {
+        Self {
+            histogram: Histogram::new(3).expect("3 significant figures is valid"),
+            started: SystemTime::now(),
+            clock: Instant::now(),
+        }
+    }
+
+    pub fn record(&mut self, duration: Duration) {
+        // Auto-resizing, so any value fits
+        let _ = self.histogram.record(duration.as_micros().max(1) as u64);
+    }
+
+    /// None before anything was probed.
+    pub fn summary(&self) -> Option<LatencySummary> {
+        let h = &self.histogram;
+        if h.is_empty() {
+            return None;
+        }
+        // Rounded to 0.1 ms
+        let ms = |micros: f64| (micros / 100.0).round() / 10.0;
+        let at = |quantile: f64| ms(h.value_at_quantile(quantile) as f64);
+        Some(LatencySummary {
+            count: h.len(),
+            mean_ms: ms(h.mean()),
+            p50_ms: at(0.5),
+            p90_ms: at(0.9),
+            p99_ms: at(0.99),
+            p999_ms: at(0.999),
+            max_ms: ms(h.max() as f64),
+        })
+    }
+
+    /// Writes the histogram as a one-interval HdrHistogram log, for
+    /// HistogramLogAnalyzer and the other HdrHistogram tools.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let mut serializer = V2DeflateSerializer::new();
+        let mut log = IntervalLogWriterBuilder::new()
+            .add_comment("dq probe durations, in microseconds")
+            .with_start_time(self.started)
+            .with_base_time(self.started)
+            .with_max_value_divisor(1000.0)
+            .begin_log_with(&mut file, &mut serializer)?;
+        log.write_histogram(&self.histogram, Duration::ZERO, self.clock.elapsed(), Tag::new("probes"))
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        file.flush()
+    }
+}
@@ -1,30 +1,117 @@
-use clap::Parser;
+#[cfg(feature = "tui")]
+mod alternatives;
+mod audit;
+mod brute;
+mod cache;
+mod config;
+mod crash;
+#[cfg(feature = "tui")]
+mod filter;
+mod hooks;
+mod instance;
+mod interrupt;
+mod keymap;
+mod latency;
+mod mode;
+mod output;
+mod pricing;
+mod protect;
+mod publish;
+mod remote;
+mod report;
+mod schema;
+#[cfg(feature = "tui")]
+mod screenshot;
+#[cfg(feature = "tui")]
+mod settings;
+mod snapshot;
+mod stats;
+#[cfg(feature = "tui")]
+mod status;
+mod transliterate;
+mod tuning;
+mod typosquat;
+#[cfg(feature = "self-update")]
+mod update;
+mod usage;
+mod watcher;
+mod watchlist;
+mod words;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use audit::AuditAction;
+use cache::ResultCache;
+use config::{config_path, get_default_config_toml, load_config, Config};
+#[cfg(feature = "tui")]
+use config::{LinkTarget, LinksConfig, Spinner, Theme, TldConfig};
+#[cfg(feature = "tui")]
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor::Show,
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures::StreamExt;
-use librdap_storm::{fetch_iana_tlds, Availability, ProbeConfig, Prober};
+use librdap_storm::{
+    is_registrable, public_suffix, restriction_for, Availability, DnsResolver, EndpointMetrics, ProbeConfig, ProbeError,
+    Prober, Proxy, PublicSuffixList, TldRanker, DIAGNOSTIC_HEADERS,
+};
+#[cfg(feature = "tui")]
+use librdap_storm::{BootstrapState, CancellationToken, Registrant};
+#[cfg(feature = "tui")]
+use filter::{FilterExpr, FilterItem, StatusKind};
+use hooks::run_summary_hook;
+use interrupt::{Interrupt, INTERRUPTED_EXIT};
+#[cfg(feature = "tui")]
+use keymap::{KeyAction, Keymap};
+use latency::{LatencySummary, ProbeLatency};
+use mode::{SweepMode, SweepPlan};
+use output::{Compression, Format, LineWriter, OutputOptions};
+use pricing::LivePrices;
+#[cfg(feature = "tui")]
+use pricing::{load_prices, PriceTable};
+use publish::Publisher;
+use remote::{RemoteStore, HISTORY_FILE, WATCHLIST_FILE};
+use report::{Locks, ReportRow};
+use schema::{print_schema, SchemaRecord};
+#[cfg(feature = "tui")]
+use settings::{Setting, SettingsScreen};
+use snapshot::Snapshot;
+use schemars::JsonSchema;
+use tuning::ProbeHistory;
+use usage::{UsageRecorder, UsageStats};
+use watchlist::{ListFormat, Watchlist};
+#[cfg(feature = "tui")]
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame, Terminal,
 };
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "tui")]
 use std::{
-    collections::HashMap,
-    io::{self, Write},
-    path::PathBuf,
+    collections::BTreeSet,
     sync::{Arc, Mutex},
-    time::Duration,
+};
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap, HashSet},
+    hash::{BuildHasher, Hasher},
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+/// Kept back from `--deadline` for writing out skipped records and saving
+/// state.
+const DEADLINE_MARGIN: Duration = Duration::from_millis(250);
+#[cfg(feature = "tui")]
+/// The TUI redraws this often to show arriving results.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
 
+#[cfg(feature = "tui")]
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum FilterMode {
     All,
@@ -32,6 +119,7 @@ enum FilterMode {
     Taken,
 }
 
+#[cfg(feature = "tui")]
 impl FilterMode {
     fn next(self) -> Self {
         match self {
@@ -42,31 +130,6 @@ impl FilterMode {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
-struct Config {
-    #[serde(default)]
-    tlds: TldConfig,
-}
-
-#[derive(Debug, Default, Deserialize, Serialize)]
-struct TldConfig {
-    #[serde(default)]
-    always: Vec<String>,
-    #[serde(default)]
-    never: Vec<String>,
-}
-
-fn config_path() -> Option<PathBuf> {
-    dirs::config_dir().map(|p| p.join("dq").join("config.toml"))
-}
-
-fn load_config() -> Config {
-    config_path()
-        .and_then(|path| std::fs::read_to_string(&path).ok())
-        .and_then(|content| toml::from_str(&content).ok())
-        .unwrap_or_default()
-}
-
 fn apply_config_to_tlds(mut tlds: Vec<String>, config: &Config) -> Vec<String> {
     let never_set: std::collections::HashSet<_> = config.tlds.never.iter()
         .map(|s| s.to_lowercase())
@@ -84,32 +147,37 @@ fn apply_config_to_tlds(mut tlds: Vec<String>, config: &Config) -> Vec<String> {
     tlds
 }
 
-fn get_default_config_toml() -> String {
-    r#"# Domain Query (dq) Configuration
-
-[tlds]
-# TLDs to always include in results, regardless of IANA list
-# always = ["com", "net", "org", "io", "dev", "rs", "no", "pm"]
-always = []
-
-# TLDs to never include/hide from results
-# never = ["adult", "xxx", "reklame"]
-never = []
-"#.to_string()
+/// Normalizes TLD inputs (case, leading dot) and merges duplicates, keeping
+/// the first occurrence's position. Returns the merged inputs per TLD for
+/// every TLD that was given more than once.
+fn dedupe_tlds(tlds: Vec<String>) -> (Vec<String>, BTreeMap<String, Vec<String>>) {
+    let mut inputs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut unique = Vec::new();
+    for input in tlds {
+        let tld = input.trim().trim_start_matches('.').to_lowercase();
+        if tld.is_empty() {
+            continue;
+        }
+        let seen = inputs.entry(tld.clone()).or_default();
+        if seen.is_empty() {
+            unique.push(tld);
+        }
+        seen.push(input);
+    }
+    inputs.retain(|_, sources| sources.len() > 1);
+    (unique, inputs)
 }
 
+/// Splits `foo.co.uk` into `foo` and its public suffix `co.uk`.
 fn parse_domain_query(query: &str) -> (String, Option<String>) {
-    if let Some(dot_pos) = query.rfind('.') {
-        let base = &query[..dot_pos];
-        let potential_tld = &query[dot_pos + 1..];
-
-        let is_valid_tld = !base.is_empty()
-            && !potential_tld.is_empty()
-            && potential_tld.len() <= 20
-            && potential_tld.chars().all(|c| c.is_ascii_alphabetic());
+    if let Some((base, suffix)) = PublicSuffixList::builtin().split(query) {
+        let is_valid_tld = suffix.len() <= 20
+            && suffix
+                .split('.')
+                .all(|label| label.starts_with("xn--") || label.chars().all(char::is_alphabetic));
 
         if is_valid_tld {
-            return (base.to_string(), Some(potential_tld.to_lowercase()));
+            return (base.to_string(), Some(suffix.to_lowercase()));
         }
     }
 
@@ -120,25 +188,146 @@ fn prioritize_tld(mut tlds: Vec<String>, priority_tld: &str) -> Vec<String> {
     if let Some(pos) = tlds.iter().position(|t| t.eq_ignore_ascii_case(priority_tld)) {
         let tld = tlds.remove(pos);
         tlds.insert(0, tld);
+    } else if priority_tld.contains('.') {
+        // Second-level namespaces like co.uk aren't in the IANA list
+        tlds.insert(0, priority_tld.to_string());
     }
     tlds
 }
 
+/// `500ms`, `30s`, `2m`, `1h`, `7d`, or plain seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().map_err(|_| format!("expected e.g. 30s or 2m, got \"{}\"", value))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 3600)),
+        "d" => Ok(Duration::from_secs(number * 86400)),
+        _ => Err(format!("unknown unit \"{}\", expected ms, s, m, h or d", unit)),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "dq")]
 #[command(about = "Domain Query - instant availability search across all TLDs", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Domain query to search (without TLD)
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Domain query to search (without TLD); after `--` when it's also a
+    /// subcommand name (`dq -- schema`)
     query: Option<String>,
 
     /// Output results as NDJSON stream (one JSON object per line)
     #[arg(long, short = 'j')]
     ndjson: bool,
 
-    /// Comma-separated list of specific TLDs to check (e.g., dev,ai,com,net,org,io)
-    #[arg(long, value_delimiter = ',')]
+    /// Comma-separated list of specific TLDs to check (e.g., dev,ai,com,net,org,io or @group)
+    #[arg(long, value_delimiter = ',', env = "DQ_TLDS")]
     tlds: Option<Vec<String>>,
 
+    /// Hide TLDs whose eligibility rules you can't meet (see [eligibility] in the config)
+    #[arg(long)]
+    registrable_only: bool,
+
+    /// Apply a named profile from the config file
+    #[arg(long, env = "DQ_PROFILE")]
+    profile: Option<String>,
+
+    /// Include diagnostic RDAP response headers (Server, Retry-After, rate limits) in NDJSON output
+    #[arg(long)]
+    capture_headers: bool,
+
+    /// Include the registry's raw RDAP JSON or WHOIS text in NDJSON output
+    #[arg(long)]
+    capture_raw: bool,
+
+    /// NDJSON records held for a slow reader before probing pauses (default 1024)
+    #[arg(long, value_name = "RECORDS")]
+    buffer_limit: Option<usize>,
+
+    /// Write results to a file instead of the TUI: .ndjson or .csv, compressed if it ends in .gz or .zst
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+
+    /// Write one result file per TLD into this directory instead, for processing in parallel
+    #[arg(long, value_name = "DIR", conflicts_with = "output")]
+    output_dir: Option<PathBuf>,
+
+    /// With --output-dir, start a new file every N results instead of one per TLD
+    #[arg(long, value_name = "N", requires = "output_dir", value_parser = clap::value_parser!(u64).range(1..))]
+    split_every: Option<u64>,
+
+    /// Result format of --output or --output-dir (default: from the --output extension, else ndjson)
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+
+    /// Compress the results (stdout, --output or --output-dir) regardless of the file extension
+    #[arg(long, value_enum)]
+    compress: Option<Compression>,
+
+    /// Re-verify every available result via WHOIS or DNS before reporting it
+    #[arg(long)]
+    confirm: bool,
+
+    /// Shell command run after the sweep with its summary JSON on stdin (overrides [hooks] summary)
+    #[arg(long, value_name = "CMD")]
+    summary_hook: Option<String>,
+
+    /// Send RDAP and WHOIS traffic through this proxy: http://, socks5:// or socks5h:// (overrides [probe] proxy)
+    #[arg(long, value_name = "URL")]
+    proxy: Option<Proxy>,
+
+    /// Look up server names and ask the DNS checks here: system, cloudflare, google, quad9, an https://<ip>/dns-query URL or DNS server addresses (overrides [probe] resolver)
+    #[arg(long, value_name = "RESOLVER")]
+    resolver: Option<DnsResolver>,
+
+    /// Fast first look: priority TLDs only, DNS before RDAP, 2s timeouts, no retries
+    #[arg(long, conflicts_with = "thorough")]
+    quick: bool,
+
+    /// Careful check: every TLD, retries, and --confirm on available results
+    #[arg(long)]
+    thorough: bool,
+
+    /// Finish within this long (e.g. 30s, 2m, 500ms), setup and saving included: domains not probed by then are reported as skipped
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    deadline: Option<Duration>,
+
+    /// After an --ndjson/--output sweep, print each RDAP server's request counts and latencies to stderr
+    #[arg(long)]
+    metrics: bool,
+
+    /// After an --ndjson/--output sweep, save the probe durations as an HdrHistogram log to this file
+    #[arg(long, value_name = "FILE")]
+    latency_histogram: Option<PathBuf>,
+
+    /// Probe everything again instead of reusing verdicts cached by recent runs
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Record every result in an SQLite file in the cache dir and reuse verdicts found there up to MAX_AGE old
+    #[cfg(feature = "sqlite-cache")]
+    #[arg(
+        long,
+        value_name = "MAX_AGE",
+        value_parser = parse_duration,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "7d",
+        conflicts_with_all = ["no_cache", "thorough"]
+    )]
+    cached: Option<Duration>,
+
+    /// Don't reorder probes or adjust concurrency from previous runs' endpoint history
+    #[arg(long)]
+    no_adaptive: bool,
+
     /// Print the default config to stdout and exit
     #[arg(long)]
     print_default_config: bool,
@@ -148,52 +337,534 @@ struct Args {
     write_default_config: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print JSON Schema documents for dq's NDJSON/JSON output records
+    Schema {
+        /// Record to print (all records when omitted)
+        #[arg(value_enum)]
+        record: Option<SchemaRecord>,
+    },
+    /// Summarize saved NDJSON results: availability per TLD, endpoint latency, errors
+    Stats {
+        /// NDJSON files written by `dq --ndjson` (`-` for stdin)
+        #[arg(required_unless_present = "own")]
+        files: Vec<String>,
+
+        /// Show this machine's own sweeps and requests per RDAP endpoint
+        /// instead (recorded with `[usage] enabled = true`)
+        #[arg(long = "self", conflicts_with = "files")]
+        own: bool,
+
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage the watchlist and shortlist, and share them between machines
+    Watch {
+        #[command(subcommand)]
+        action: WatchAction,
+    },
+    /// Sweep NAME and write a shareable HTML report (status grid, prices, expiring domains)
+    Report {
+        name: String,
+
+        /// Output file (default: NAME-report.html)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Comma-separated TLDs or @group, as for a normal sweep
+        #[arg(long, value_delimiter = ',')]
+        tlds: Option<Vec<String>>,
+    },
+    /// Show the audit log of actions taken (links opened, copies, watchlist changes)
+    Log {
+        /// Only entries of this kind
+        #[arg(long, value_enum)]
+        action: Option<AuditAction>,
+
+        /// Only entries from the last N days
+        #[arg(long)]
+        days: Option<u64>,
+
+        /// Print entries as NDJSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Keep sweeping typo and lookalike variants of a brand, alerting on new registrations
+    Protect {
+        brand: String,
+
+        /// Comma-separated TLDs or @group (default: com,net,org)
+        #[arg(long, value_delimiter = ',')]
+        tlds: Option<Vec<String>>,
+
+        /// Run a single pass and exit
+        #[arg(long)]
+        once: bool,
+    },
+    /// Probe every name matching a pattern under a TLD, printing the available ones
+    Brute {
+        /// `?` any letter, `#` any digit, `*` either, other characters as
+        /// given (`c?t`); or a length, `3` or `3-4`, for all-letter names
+        #[arg(long)]
+        pattern: String,
+
+        /// The TLD to search
+        #[arg(long, default_value = "com")]
+        tld: String,
+
+        /// Probes per second, at most 20
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..=brute::MAX_RATE as i64))]
+        rate: u32,
+
+        /// Don't ask before sweeping more than 1000 names
+        #[arg(long, short)]
+        yes: bool,
+
+        /// Print each available name as NDJSON with its explanation
+        #[arg(long)]
+        json: bool,
+    },
+    /// Probe dictionary words of a length range under some TLDs, printing the available domains
+    Words {
+        /// Word list, one word per line; entries with capitals or punctuation are skipped
+        #[arg(long)]
+        list: PathBuf,
+
+        /// Shortest word, in letters
+        #[arg(long, default_value_t = 4)]
+        min: usize,
+
+        /// Longest word, in letters
+        #[arg(long, default_value_t = 6)]
+        max: usize,
+
+        /// Comma-separated TLDs or @group (default: com)
+        #[arg(long, value_delimiter = ',')]
+        tlds: Option<Vec<String>>,
+
+        /// Start from the first word instead of resuming an interrupted run
+        #[arg(long)]
+        restart: bool,
+
+        /// Print every verdict as NDJSON, not just the available domains
+        #[arg(long)]
+        json: bool,
+    },
+    /// List domains newly registered between the last two watcher passes
+    NewRegistrations {
+        /// Report on the lookalikes of this `dq protect` brand instead of the watchlist
+        #[arg(long)]
+        brand: Option<String>,
+
+        /// Print as NDJSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Send a command to the running `dq watch run` over its control socket
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+    /// Live view of the running watcher: schedule, verdicts per watched name, recent changes
+    #[cfg(feature = "tui")]
+    Status,
+    /// Replace dq with the latest GitHub release, after verifying its checksum and signature
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CtlCommand {
+    /// Print the watcher's state as JSON: schedule, names, verdicts, recent changes
+    Status,
+    /// Watch names (or shortlist them with --shortlist)
+    Add {
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        #[arg(long)]
+        shortlist: bool,
+    },
+    /// Stop watching names
+    Remove {
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    /// Start the next pass now instead of waiting for the interval
+    Recheck,
+    /// Check a name or domain through the watcher
+    Query { name: String },
+}
+
+impl CtlCommand {
+    fn to_control(&self) -> watcher::Control {
+        match self {
+            CtlCommand::Status => watcher::Control::Status,
+            CtlCommand::Add { names, shortlist } => {
+                watcher::Control::Add { names: names.clone(), shortlist: *shortlist }
+            }
+            CtlCommand::Remove { names } => watcher::Control::Remove(names.clone()),
+            CtlCommand::Recheck => watcher::Control::Recheck,
+            CtlCommand::Query { name } => watcher::Control::Query(name.clone()),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum WatchAction {
+    /// Print watched and shortlisted names
+    List,
+    /// Watch names (or shortlist them with --shortlist)
+    Add {
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        #[arg(long)]
+        shortlist: bool,
+    },
+    /// Stop watching names and drop them from the shortlist
+    Remove {
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    /// Write the watchlist, including `[watch] names` from the config, to a file or stdout
+    Export {
+        /// Output file (stdout when omitted)
+        file: Option<PathBuf>,
+
+        /// File format (guessed from the extension, TOML otherwise)
+        #[arg(long, value_enum)]
+        format: Option<ListFormat>,
+    },
+    /// Merge names from an exported watchlist
+    Import {
+        file: PathBuf,
+
+        #[arg(long, value_enum)]
+        format: Option<ListFormat>,
+
+        /// Replace the local watchlist instead of merging
+        #[arg(long)]
+        replace: bool,
+    },
+    /// Keep re-checking the watchlist, printing changes as NDJSON; one watcher per user
+    Run {
+        /// Comma-separated TLDs or @group for bare names (default: priority TLDs)
+        #[arg(long, value_delimiter = ',')]
+        tlds: Option<Vec<String>>,
+    },
+    /// Check the watchlist once, printing changes since the last check, for cron or a CronJob
+    ///
+    /// Exits 0 when nothing changed and 3 when a verdict did.
+    CheckOnce {
+        /// Comma-separated TLDs or @group for bare names (default: priority TLDs)
+        #[arg(long, value_delimiter = ',')]
+        tlds: Option<Vec<String>>,
+    },
+    /// Check a name or domain through the running watcher instead of probing separately
+    Query { name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 enum AvailabilityStatus {
     Available,
     Taken,
+    /// Unregistered, but held back by the registry
+    Reserved,
+    /// Unregistered, but sold at a registry premium price
+    Premium,
     Checking,
     Pending,
     Error,
+    /// Undetermined because the registry was in a maintenance window
+    Maintenance,
+    /// Not probed before the sweep's `--deadline`
+    Skipped,
+    /// Not probed: the TLD's registry doesn't take names like it (too
+    /// short, digits only, bad syntax)
+    Invalid,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a sweep's TLD list came from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum TldSource {
+    /// The IANA root zone list
+    Iana,
+    /// dq's own list, used when IANA couldn't be reached
+    Builtin,
+    /// `--tlds`, `tlds.always` or the TLD of the query
+    Custom,
+}
+
+/// Which spelling of a non-ASCII query a domain is.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum NameForm {
+    /// The name as typed, registered as an internationalized domain
+    Idn,
+    /// The name spelled in ASCII by the TLD's convention (`ü` → `ue` under .de)
+    Ascii,
+}
+
+/// The domains swept for `query` under `tld`: the name as typed and, when
+/// it has letters outside ASCII, its ASCII spelling too.
+fn sweep_domains(query: &str, tld: &str) -> Vec<(String, Option<NameForm>)> {
+    let domain = format!("{}.{}", query, tld).to_lowercase();
+    match transliterate::ascii_form(query, tld) {
+        Some(ascii) => vec![(domain, Some(NameForm::Idn)), (format!("{}.{}", ascii, tld), Some(NameForm::Ascii))],
+        None => vec![(domain, None)],
+    }
+}
+
+/// One probed domain, as emitted by `--ndjson` (or a CSV row of `--output`).
+/// Kept apart from the library's `ProbeResult`, which it is built from:
+/// run, query, pricing and CSV fields are dq's, and `dq schema` publishes
+/// this shape as its output contract.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct DomainCheckResult {
+    /// Identifies the dq run that produced the record
+    #[serde(default)]
+    run_id: String,
+    /// Name part of the query, without TLD
     query: String,
     tld: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    tld_source: Option<TldSource>,
     domain: String,
+    /// For a non-ASCII query: `idn` for the name as typed, `ascii` for its
+    /// transliteration
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    form: Option<NameForm>,
+    /// `null` when availability could not be determined; `false` for
+    /// reserved and premium names as well as taken ones
     available: Option<bool>,
     status: AvailabilityStatus,
+    /// Reason when `status` is `error`, `maintenance` or `invalid`
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    /// Category of `error`: `timeout`, `rate_limited`, `no_endpoint`,
+    /// `transport_error`, `ambiguous_response`, `bootstrap`, `maintenance`
+    /// or `invalid_name`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    error_kind: Option<String>,
+    /// Registration eligibility requirement for the TLD, if any
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    restriction: Option<String>,
+    /// Launch phase of a TLD not yet in general availability (`sunrise`,
+    /// `landrush`, `early_access`, `pre_launch`), when its schedule is known
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    launch_phase: Option<String>,
+    /// First-year registration price in USD, when known
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    price_usd: Option<f64>,
+    /// Price converted to the configured display currency
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    price: Option<f64>,
+    /// ISO 4217 code of `price`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    currency: Option<String>,
+    /// Wall time of the probe in milliseconds
+    #[serde(default)]
+    duration_ms: u64,
+    /// RDAP base URL that was queried
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    endpoint: Option<String>,
+    /// How the verdict was reached, e.g. "RDAP 404 from rdap.verisign.com"
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    explanation: Option<String>,
+    /// What decided the verdict: `rdap`, `whois`, `dns`, `cache` or `custom`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    source: Option<String>,
+    /// `high` for an RDAP answer or NS delegation, `medium` for a WHOIS text
+    /// match or address lookup, `low` when undetermined
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    confidence: Option<String>,
+    /// Captured RDAP response headers (`--capture-headers`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    headers: BTreeMap<String, String>,
+    /// The registry's answer, RDAP JSON or WHOIS text (`--capture-raw`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    raw: Option<String>,
+}
+
+/// Written to stderr as the last line of an `--ndjson`/`--output` sweep
+/// stopped with Ctrl-C or cut short by `--deadline`, and handed to the
+/// `[hooks] summary` command after every such sweep.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct SweepSummary {
+    run_id: String,
+    query: String,
+    interrupted: bool,
+    deadline_reached: bool,
+    /// Records written
+    results: usize,
+    available: usize,
+    taken: usize,
+    /// Records with status `error` or `maintenance`
+    unknown: usize,
+    /// Records with status `skipped`, written for the domains the deadline
+    /// left unprobed
+    skipped: usize,
+    /// Domains never probed, nor written, because of the interruption
+    not_probed: usize,
+    /// How long the probes took; absent when nothing was probed
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    latency: Option<LatencySummary>,
+}
+
+impl DomainCheckResult {
+    /// Columns of `csv_row`; captured headers and raw answers are left out.
+    const CSV_HEADER: &'static str =
+        "run_id,query,tld,tld_source,domain,form,available,status,error,restriction,price_usd,price,currency,duration_ms,endpoint,explanation,source,confidence,launch_phase,error_kind";
+
+    fn csv_row(&self) -> String {
+        let opt = |value: &Option<String>| value.as_deref().map(output::csv_field).unwrap_or_default();
+        let num = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let label = |value: serde_json::Result<serde_json::Value>| {
+            value.ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+        };
+        [
+            output::csv_field(&self.run_id),
+            output::csv_field(&self.query),
+            output::csv_field(&self.tld),
+            label(serde_json::to_value(self.tld_source)),
+            output::csv_field(&self.domain),
+            label(serde_json::to_value(self.form)),
+            self.available.map(|a| a.to_string()).unwrap_or_default(),
+            label(serde_json::to_value(&self.status)),
+            opt(&self.error),
+            opt(&self.restriction),
+            num(self.price_usd),
+            num(self.price),
+            opt(&self.currency),
+            self.duration_ms.to_string(),
+            opt(&self.endpoint),
+            opt(&self.explanation),
+            opt(&self.source),
+            opt(&self.confidence),
+            opt(&self.launch_phase),
+            opt(&self.error_kind),
+        ]
+        .join(",")
+    }
 }
 
+#[cfg(feature = "tui")]
 #[derive(Debug, Clone, PartialEq)]
 enum DomainStatus {
     Pending,
     Checking,
     Available,
     Taken,
+    /// Unregistered, but held back by the registry
+    Reserved,
+    /// Unregistered, but sold at a registry premium price
+    Premium,
     Error(String),
+    /// The registry is in a maintenance window
+    Maintenance,
+    /// The TLD's registry doesn't take names like it
+    Invalid,
+}
+
+#[cfg(feature = "tui")]
+impl DomainStatus {
+    fn from_availability(availability: Availability) -> Self {
+        match availability {
+            Availability::Available => DomainStatus::Available,
+            Availability::Taken => DomainStatus::Taken,
+            Availability::Reserved => DomainStatus::Reserved,
+            Availability::Premium => DomainStatus::Premium,
+            availability if availability.is_maintenance() => DomainStatus::Maintenance,
+            availability if availability.is_invalid_name() => DomainStatus::Invalid,
+            Availability::Unknown { reason } => DomainStatus::Error(reason.to_string()),
+        }
+    }
 }
 
+#[cfg(feature = "tui")]
 struct App {
     query: String,
     input_mode: bool,
     results: Arc<Mutex<HashMap<String, DomainStatus>>>,
+    /// How each TLD's verdict was reached, shown for the selected row
+    explanations: Arc<Mutex<HashMap<String, String>>>,
+    /// Lock posture of taken domains selected so far; `None` while the
+    /// lookup runs or when the record couldn't be read
+    locks: Arc<Mutex<HashMap<String, Option<Locks>>>>,
     tlds: Vec<String>,
     list_state: ListState,
     quit: bool,
     specific_domain: Option<String>,
     specific_domain_status: Arc<Mutex<Option<DomainStatus>>>,
-    tick: usize,
+    /// Verdicts on the alternatives to the specific domain the sweep
+    /// doesn't cover
+    alternative_status: Arc<Mutex<HashMap<String, DomainStatus>>>,
+    /// For a non-ASCII query, its ASCII spelling per TLD and that domain's verdict
+    ascii_status: Arc<Mutex<HashMap<String, (String, DomainStatus)>>>,
+    /// Drives the spinner
+    started: Instant,
     filter_mode: FilterMode,
     toast_message: Option<(String, std::time::Instant)>,
+    /// Save the next drawn frame as `.ans` and `.svg`
+    screenshot_pending: bool,
+    /// Filter expression being typed after `:`
+    filter_input: Option<String>,
+    /// Applied on top of `filter_mode`
+    filter_expr: Option<FilterExpr>,
+    /// `[groups]`, usable in `tld in <group>`
+    groups: BTreeMap<String, Vec<String>>,
+    ranker: TldRanker,
+    prober: Arc<Prober>,
+    theme: Theme,
+    spinner: Spinner,
+    keymap: Keymap,
+    links: LinksConfig,
+    /// Highlighted entry while the open menu is shown
+    open_menu: Option<usize>,
+    /// Domains marked for a bulk registrar search; kept across queries
+    marked: BTreeSet<String>,
+    registrant: Registrant,
+    registrable_only: bool,
+    prices: Arc<Mutex<PriceTable>>,
+    /// Endpoint history updated as results arrive, when adaptive tuning is on
+    history: Option<Arc<Mutex<ProbeHistory>>>,
+    plan: SweepPlan,
+    /// Every TLD before the sweep mode narrows `tlds`
+    all_tlds: Vec<String>,
+    /// Probe settings before the sweep mode preset is applied
+    base_probe_config: ProbeConfig,
+    result_cache: Arc<Mutex<ResultCache>>,
+    usage: UsageRecorder,
+    /// Reuse cached verdicts (off with `--no-cache` and in thorough mode)
+    reuse_cache: bool,
+    /// Cancels the running sweep when a new one starts
+    sweep: Mutex<CancellationToken>,
+    /// Open while the `,` settings screen is shown
+    settings: Option<SettingsScreen>,
+    /// Changed on the settings screen but not yet written to the config file
+    unsaved_settings: BTreeSet<Setting>,
+    /// `[tlds]` lists as the settings screen left them
+    tld_config: TldConfig,
 }
 
+#[cfg(feature = "tui")]
 impl App {
-    fn new(initial_query: Option<String>, specific_tld: Option<String>, tlds: Vec<String>) -> Self {
+    fn new(
+        initial_query: Option<String>,
+        specific_tld: Option<String>,
+        tlds: Vec<String>,
+        probe_config: ProbeConfig,
+        config: &Config,
+    ) -> Self {
         let results = Arc::new(Mutex::new(HashMap::new()));
 
         {
@@ -215,15 +886,62 @@ impl App {
             query: initial_query.unwrap_or_default(),
             input_mode: true,
             results,
+            explanations: Arc::new(Mutex::new(HashMap::new())),
+            locks: Arc::new(Mutex::new(HashMap::new())),
+            all_tlds: tlds.clone(),
             tlds,
             list_state,
             quit: false,
             specific_domain,
             specific_domain_status: Arc::new(Mutex::new(None)),
-            tick: 0,
+            alternative_status: Arc::new(Mutex::new(HashMap::new())),
+            ascii_status: Arc::new(Mutex::new(HashMap::new())),
+            started: Instant::now(),
             filter_mode: FilterMode::All,
             toast_message: None,
+            screenshot_pending: false,
+            filter_input: None,
+            filter_expr: None,
+            groups: config.groups.clone(),
+            ranker: TldRanker::default(),
+            prober: Arc::new(Prober::with_config(probe_config.clone())),
+            base_probe_config: probe_config,
+            plan: SweepPlan::default(),
+            result_cache: Arc::new(Mutex::new(ResultCache::load(config.result_cache_ttl(), config.max_cached_results()))),
+            usage: UsageRecorder::new(config),
+            reuse_cache: config.cache.enabled != Some(false),
+            sweep: Mutex::new(CancellationToken::new()),
+            settings: None,
+            unsaved_settings: BTreeSet::new(),
+            tld_config: config.tlds.clone(),
+            theme: config.theme(),
+            spinner: config.spinner(),
+            keymap: config.keymap(),
+            links: config.links.clone(),
+            open_menu: None,
+            marked: BTreeSet::new(),
+            registrant: config.eligibility.registrant(),
+            registrable_only: config.eligibility.registrable_only.unwrap_or(false),
+            prices: Arc::new(Mutex::new(PriceTable::default())),
+            history: None,
+        }
+    }
+
+    /// Switches sweep preset: rebuilds the prober and narrows or widens the
+    /// TLD list. Callers restart the sweep.
+    fn set_mode(&mut self, mode: SweepMode) {
+        self.plan.mode = mode;
+        self.usage.retire(&self.prober.metrics());
+        self.prober = Arc::new(Prober::with_config(mode.apply(self.base_probe_config.clone())));
+        self.tlds = self.plan.select_tlds(&self.all_tlds);
+
+        let mut res = self.results.lock().unwrap();
+        res.clear();
+        for tld in &self.tlds {
+            res.insert(tld.clone(), DomainStatus::Pending);
         }
+        drop(res);
+        self.list_state.select(Some(0));
     }
 
     fn get_selected_domain(&self) -> Option<String> {
@@ -237,33 +955,319 @@ impl App {
         if let Some(domain) = self.get_selected_domain() {
             if let Ok(mut clipboard) = arboard::Clipboard::new() {
                 if clipboard.set_text(&domain).is_ok() {
+                    audit::record(AuditAction::Copy, &domain);
                     self.toast_message = Some((format!("Copied: {}", domain), std::time::Instant::now()));
                 }
             }
         }
     }
 
-    fn open_selected_in_browser(&mut self) {
+    /// Link targets offered for the selected row; the live site only makes
+    /// sense for taken domains.
+    fn open_menu_entries(&self) -> Vec<LinkTarget> {
+        let selected_taken = self
+            .list_state
+            .selected()
+            .and_then(|i| self.get_filtered_results().get(i).cloned())
+            .is_some_and(|(_, status)| status == DomainStatus::Taken);
+
+        LinkTarget::ALL
+            .into_iter()
+            .filter(|t| *t != LinkTarget::Site || selected_taken)
+            .collect()
+    }
+
+    fn show_open_menu(&mut self) {
+        if self.get_selected_domain().is_some() || !self.marked.is_empty() {
+            self.open_menu = Some(0);
+        }
+    }
+
+    fn open_selected_in_browser(&mut self, target: LinkTarget) {
+        self.open_menu = None;
+        // Marked domains go to the registrar in one search, not a tab each
+        if target == LinkTarget::Registrar && !self.marked.is_empty() {
+            let domains: Vec<String> = self.marked.iter().cloned().collect();
+            let url = self.links.bulk_url(&domains);
+            let _ = open::that(&url);
+            audit::record(AuditAction::Purchase, format!("{} via {}", domains.join(","), url));
+            self.toast_message = Some((
+                format!("Opening {} domains: {}", domains.len(), url),
+                std::time::Instant::now(),
+            ));
+            return;
+        }
         if let Some(domain) = self.get_selected_domain() {
-            let url = format!("https://www.namecheap.com/domains/registration/results/?domain={}", domain);
+            let url = self.links.url(target, &domain);
             let _ = open::that(&url);
-            self.toast_message = Some((format!("Opening: {}", domain), std::time::Instant::now()));
+            let action = match target {
+                LinkTarget::Registrar => AuditAction::Purchase,
+                LinkTarget::Whois => AuditAction::Lookup,
+                LinkTarget::Site => AuditAction::Visit,
+            };
+            audit::record(action, format!("{} via {}", domain, url));
+            self.toast_message = Some((format!("Opening: {}", url), std::time::Instant::now()));
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(domain) = self.get_selected_domain() else {
+            return;
+        };
+        if !self.marked.remove(&domain) {
+            self.marked.insert(domain);
+        }
+        self.scroll_down();
+    }
+
+    fn handle_open_menu_key(&mut self, code: KeyCode) {
+        let entries = self.open_menu_entries();
+        let selected = self.open_menu.unwrap_or(0);
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => self.open_menu = None,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.open_menu = Some((selected + 1).min(entries.len().saturating_sub(1)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.open_menu = Some(selected.saturating_sub(1)),
+            KeyCode::Enter => {
+                if let Some(target) = entries.get(selected) {
+                    self.open_selected_in_browser(*target);
+                }
+            }
+            KeyCode::Char(c) => {
+                if let Some(target) = entries.iter().find(|t| t.key() == c) {
+                    self.open_selected_in_browser(*target);
+                }
+            }
+            _ => {}
         }
     }
 
+    /// Favorite TLDs first, in config order and whatever the filters; then
+    /// the rest that pass them.
     fn get_filtered_results(&self) -> Vec<(String, DomainStatus)> {
-        self.get_sorted_results()
+        let favorites = &self.tld_config.favorites;
+        let (mut pinned, rest): (Vec<_>, Vec<_>) =
+            self.get_sorted_results().into_iter().partition(|(tld, _)| favorites.contains(tld));
+        pinned.sort_by_key(|(tld, _)| favorites.iter().position(|f| f == tld));
+
+        let prices = self.prices.lock().unwrap();
+        let rest = rest
             .into_iter()
             .filter(|(_, status)| match self.filter_mode {
                 FilterMode::All => true,
                 FilterMode::Available => matches!(status, DomainStatus::Available),
                 FilterMode::Taken => matches!(status, DomainStatus::Taken),
             })
-            .collect()
+            .filter(|(tld, _)| !self.registrable_only || is_registrable(tld, &self.registrant))
+            .filter(|(tld, status)| {
+                let Some(expr) = &self.filter_expr else {
+                    return true;
+                };
+                let domain = format!("{}.{}", self.query, tld);
+                expr.matches(&FilterItem {
+                    domain: &domain,
+                    tld,
+                    status: match status {
+                        DomainStatus::Available => StatusKind::Available,
+                        DomainStatus::Taken => StatusKind::Taken,
+                        DomainStatus::Reserved => StatusKind::Reserved,
+                        DomainStatus::Premium => StatusKind::Premium,
+                        DomainStatus::Error(_) | DomainStatus::Maintenance | DomainStatus::Invalid => StatusKind::Error,
+                        DomainStatus::Pending | DomainStatus::Checking => StatusKind::Checking,
+                    },
+                    price: prices.converted(tld),
+                })
+            });
+        pinned.extend(rest);
+        pinned
+    }
+
+    /// Adds or removes the selected TLD from the favorites and saves them
+    /// to the config file right away.
+    fn toggle_favorite(&mut self) {
+        let Some(tld) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.get_filtered_results().get(i).map(|(tld, _)| tld.clone()))
+        else {
+            return;
+        };
+        let favorites = &mut self.tld_config.favorites;
+        let added = match favorites.iter().position(|f| *f == tld) {
+            Some(i) => {
+                favorites.remove(i);
+                false
+            }
+            None => {
+                favorites.push(tld.clone());
+                true
+            }
+        };
+        // Keep the row under the cursor as it moves in or out of the pins
+        if let Some(i) = self.get_filtered_results().iter().position(|(t, _)| *t == tld) {
+            self.list_state.select(Some(i));
+        }
+
+        let saved = BTreeSet::from([Setting::FavoriteTlds]);
+        let message = match config_path() {
+            Some(path) => match settings::save(&path, &saved, &self.base_probe_config, &self.tld_config) {
+                Ok(()) => {
+                    self.unsaved_settings.remove(&Setting::FavoriteTlds);
+                    if added {
+                        format!("Pinned .{}", tld)
+                    } else {
+                        format!("Unpinned .{}", tld)
+                    }
+                }
+                Err(e) => format!("Saving favorites failed: {}", e),
+            },
+            None => "No config directory to save favorites to".to_string(),
+        };
+        self.toast_message = Some((message, std::time::Instant::now()));
+    }
+
+    /// Handles a key while a `:` filter is being typed. Enter applies it (an
+    /// empty filter clears it), Esc cancels.
+    fn handle_filter_input_key(&mut self, code: KeyCode) {
+        let Some(input) = self.filter_input.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.filter_input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter => {
+                let input = self.filter_input.take().unwrap_or_default();
+                if input.trim().is_empty() {
+                    self.filter_expr = None;
+                } else {
+                    match FilterExpr::parse(&input, &self.groups) {
+                        Ok(expr) => self.filter_expr = Some(expr),
+                        Err(e) => {
+                            self.toast_message = Some((format!("Filter: {}", e), std::time::Instant::now()));
+                            self.filter_input = Some(input);
+                            return;
+                        }
+                    }
+                }
+                self.list_state.select(Some(0));
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_settings_key(&mut self, code: KeyCode) {
+        let Some(screen) = self.settings.as_mut() else {
+            return;
+        };
+        let setting = screen.setting();
+
+        if let Some(input) = screen.editing.as_mut() {
+            match code {
+                KeyCode::Esc => screen.editing = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Enter => {
+                    let input = screen.editing.take().unwrap_or_default();
+                    let previous = self.tld_config.clone();
+                    match setting.set(&input, &mut self.base_probe_config, &mut self.tld_config) {
+                        Ok(()) => self.setting_changed(setting, &previous),
+                        Err(e) => {
+                            self.toast_message = Some((e, std::time::Instant::now()));
+                            if let Some(screen) = self.settings.as_mut() {
+                                screen.editing = Some(input);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char(',') => self.close_settings(),
+            KeyCode::Down | KeyCode::Char('j') => {
+                screen.selected = (screen.selected + 1).min(Setting::ALL.len() - 1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => screen.selected = screen.selected.saturating_sub(1),
+            KeyCode::Enter | KeyCode::Char(' ') if setting.is_toggle() => {
+                setting.toggle(&mut self.base_probe_config);
+                let previous = self.tld_config.clone();
+                self.setting_changed(setting, &previous);
+            }
+            KeyCode::Enter => {
+                screen.editing = Some(setting.value(&self.base_probe_config, &self.tld_config));
+            }
+            KeyCode::Char('w') => self.save_settings(),
+            _ => {}
+        }
+    }
+
+    fn setting_changed(&mut self, setting: Setting, previous: &TldConfig) {
+        if let Some(screen) = self.settings.as_mut() {
+            screen.changed = true;
+        }
+        self.unsaved_settings.insert(setting);
+
+        // Newly hidden TLDs go, unhidden ones come back, new pins go first
+        let never: HashSet<&String> = self.tld_config.never.iter().collect();
+        let always = &self.tld_config.always;
+        self.all_tlds.retain(|tld| !never.contains(tld) || always.contains(tld));
+        for tld in previous.never.iter().filter(|tld| !never.contains(tld)) {
+            if !self.all_tlds.contains(tld) {
+                self.all_tlds.push(tld.clone());
+            }
+        }
+        self.pin_tlds();
+    }
+
+    /// Puts `always` and favorite TLDs in the sweep, first, and keeps quick
+    /// mode from dropping them.
+    fn pin_tlds(&mut self) {
+        let config = &self.tld_config;
+        for tld in config.always.iter().chain(&config.favorites).rev() {
+            if !self.all_tlds.contains(tld) {
+                self.all_tlds.insert(0, tld.clone());
+            }
+            if !self.plan.pinned.contains(tld) {
+                self.plan.pinned.push(tld.clone());
+            }
+        }
+    }
+
+    /// Closes the settings screen; changes made on it restart the sweep.
+    fn close_settings(&mut self) {
+        if self.settings.take().is_some_and(|screen| screen.changed) {
+            self.set_mode(self.plan.mode);
+            self.start_checking();
+            self.toast_message = Some(("Settings applied".to_string(), std::time::Instant::now()));
+        }
+    }
+
+    fn save_settings(&mut self) {
+        let message = match config_path() {
+            _ if self.unsaved_settings.is_empty() => "No unsaved settings".to_string(),
+            Some(path) => match settings::save(&path, &self.unsaved_settings, &self.base_probe_config, &self.tld_config) {
+                Ok(()) => {
+                    let count = self.unsaved_settings.len();
+                    self.unsaved_settings.clear();
+                    format!("Saved {} setting(s) to {}", count, path.display())
+                }
+                Err(e) => format!("Saving settings failed: {}", e),
+            },
+            None => "No config directory to save settings to".to_string(),
+        };
+        self.toast_message = Some((message, std::time::Instant::now()));
     }
 
-    fn spinner_frame(&self) -> &'static str {
-        SPINNER_FRAMES[self.tick % SPINNER_FRAMES.len()]
+    fn spinner_frame(&self) -> &str {
+        self.spinner.frame(self.started.elapsed())
     }
 
     fn progress(&self) -> (usize, usize) {
@@ -329,68 +1333,268 @@ impl App {
             return;
         }
 
-        let prober = Prober::with_config(ProbeConfig {
-            timeout: Duration::from_secs(5),
-            whois_fallback: true,
-            max_rate_per_endpoint: 20,
-            max_concurrent_per_endpoint: 10,
+        // Whatever the previous query still has in flight is stale now
+        let cancel = CancellationToken::new();
+        std::mem::replace(&mut *self.sweep.lock().unwrap(), cancel.clone()).cancel();
+
+        let prober = Arc::clone(&self.prober);
+        let reuse_cache = self.reuse_cache && self.plan.mode != SweepMode::Thorough;
+        let cached = |domain: &str| {
+            reuse_cache
+                .then(|| self.result_cache.lock().unwrap().get(domain).map(|c| c.to_probe_result(domain)))
+                .flatten()
+        };
+
+        // When the specific domain is part of the sweep it shares that probe
+        let specific_in_sweep = self.specific_domain.as_ref().and_then(|domain| {
+            let tld = public_suffix(domain)?.to_string();
+            self.tlds.contains(&tld).then_some(tld)
         });
 
-        if let Some(ref domain) = self.specific_domain {
+        if let (Some(domain), None) = (&self.specific_domain, &specific_in_sweep) {
             let domain = domain.clone();
             let status = Arc::clone(&self.specific_domain_status);
-            let prober = prober.clone();
+            let explanations = Arc::clone(&self.explanations);
+            let result_cache = Arc::clone(&self.result_cache);
+            let prober = Arc::clone(&prober);
+            let replayed = cached(&domain);
+            let cancel = cancel.clone();
             
             tokio::spawn(async move {
-                let result = prober.probe_one(&domain).await;
-                
-                let new_status = match result.availability {
-                    Availability::Available => DomainStatus::Available,
-                    Availability::Taken => DomainStatus::Taken,
-                    Availability::Unknown { reason } => DomainStatus::Error(reason),
+                let result = match replayed {
+                    Some(result) => result,
+                    None => {
+                        let Some(result) = cancel.run_until_cancelled(prober.probe_one(&domain)).await else {
+                            return;
+                        };
+                        result_cache.lock().unwrap().record(&result);
+                        result
+                    }
                 };
+                if let Some(tld) = public_suffix(&domain) {
+                    explanations.lock().unwrap().insert(tld.to_string(), result.explanation);
+                }
+                
+                let new_status = DomainStatus::from_availability(result.availability);
                 
                 *status.lock().unwrap() = Some(new_status);
             });
         }
 
+        // Alternatives are probed up front, so they're ready if it's taken
+        let mut alternatives = Vec::new();
+        {
+            let mut statuses = self.alternative_status.lock().unwrap();
+            statuses.clear();
+            for domain in self.alternatives().into_iter().filter(|d| !self.in_sweep(d)) {
+                match cached(&domain) {
+                    Some(result) => {
+                        statuses.insert(domain, DomainStatus::from_availability(result.availability));
+                    }
+                    None => alternatives.push(domain),
+                }
+            }
+        }
+        if !alternatives.is_empty() {
+            let statuses = Arc::clone(&self.alternative_status);
+            let result_cache = Arc::clone(&self.result_cache);
+            let mut stream = Arc::clone(&prober)
+                .probe_stream_owned(alternatives)
+                .take_until(Box::pin(cancel.clone().cancelled_owned()));
+            tokio::spawn(async move {
+                while let Some(result) = stream.next().await {
+                    result_cache.lock().unwrap().record(&result);
+                    let status = DomainStatus::from_availability(result.availability);
+                    statuses.lock().unwrap().insert(result.domain, status);
+                }
+            });
+        }
+
+        // A non-ASCII name is probed in its ASCII spelling too, per TLD
+        let mut spellings = Vec::new();
+        {
+            let mut statuses = self.ascii_status.lock().unwrap();
+            statuses.clear();
+            for tld in &self.tlds {
+                let Some(ascii) = transliterate::ascii_form(&self.query, tld) else {
+                    continue;
+                };
+                let domain = format!("{}.{}", ascii, tld);
+                let status = match cached(&domain) {
+                    Some(result) => DomainStatus::from_availability(result.availability),
+                    None => {
+                        spellings.push(domain.clone());
+                        DomainStatus::Checking
+                    }
+                };
+                statuses.insert(tld.clone(), (domain, status));
+            }
+        }
+        if !spellings.is_empty() {
+            let statuses = Arc::clone(&self.ascii_status);
+            let result_cache = Arc::clone(&self.result_cache);
+            let mut stream = Arc::clone(&prober)
+                .probe_stream_owned(spellings)
+                .take_until(Box::pin(cancel.clone().cancelled_owned()));
+            tokio::spawn(async move {
+                while let Some(result) = stream.next().await {
+                    result_cache.lock().unwrap().record(&result);
+                    let tld = public_suffix(&result.domain).unwrap_or("").to_string();
+                    if let Some(entry) = statuses.lock().unwrap().get_mut(&tld) {
+                        entry.1 = DomainStatus::from_availability(result.availability);
+                    }
+                }
+            });
+        }
+
         let query = self.query.clone();
         let tlds = self.tlds.clone();
         let results = Arc::clone(&self.results);
-
+        let specific_status = Arc::clone(&self.specific_domain_status);
+        let history = self.history.clone();
+        let result_cache = Arc::clone(&self.result_cache);
+        let explanations = Arc::clone(&self.explanations);
+        explanations.lock().unwrap().clear();
+        self.locks.lock().unwrap().clear();
+
+        // Cached verdicts are filled in up front; only the rest is probed
+        let mut domains = Vec::new();
         {
             let mut res = results.lock().unwrap();
+            let mut expl = explanations.lock().unwrap();
             for tld in &tlds {
-                res.insert(tld.clone(), DomainStatus::Checking);
+                let domain = format!("{}.{}", query, tld).to_lowercase();
+                match cached(&domain) {
+                    Some(result) => {
+                        let status = DomainStatus::from_availability(result.availability);
+                        if specific_in_sweep.as_deref() == Some(tld.as_str()) {
+                            *specific_status.lock().unwrap() = Some(status.clone());
+                        }
+                        res.insert(tld.clone(), status);
+                        expl.insert(tld.clone(), result.explanation);
+                    }
+                    None => {
+                        res.insert(tld.clone(), DomainStatus::Checking);
+                        domains.push(domain);
+                    }
+                }
             }
         }
+        // Requests are counted as of the previous sweep; the rest at exit
+        self.usage.record(1, domains.len() as u64, &self.prober.metrics());
 
         tokio::spawn(async move {
-            let domains: Vec<String> = tlds.iter()
-                .map(|tld| format!("{}.{}", query, tld))
-                .collect();
+            if domains.is_empty() {
+                return;
+            }
+            if let Err(e) = prober.check_network().await {
+                let mut res = results.lock().unwrap();
+                for domain in &domains {
+                    let tld = public_suffix(domain).unwrap_or("").to_string();
+                    res.insert(tld, DomainStatus::Error(e.to_string()));
+                }
+                return;
+            }
 
-            let mut stream = prober.probe_stream(domains);
+            let mut stream = prober.probe_stream_owned(domains).take_until(Box::pin(cancel.clone().cancelled_owned()));
 
             while let Some(result) = stream.next().await {
-                let tld = result.domain
-                    .rsplit('.')
-                    .next()
-                    .unwrap_or("")
-                    .to_string();
+                if cancel.is_cancelled() {
+                    break;
+                }
+                result_cache.lock().unwrap().record(&result);
+                if let Some(history) = &history {
+                    history.lock().unwrap().record(&result);
+                }
+
+                let tld = public_suffix(&result.domain).unwrap_or("").to_string();
                 
-                let status = match result.availability {
-                    Availability::Available => DomainStatus::Available,
-                    Availability::Taken => DomainStatus::Taken,
-                    Availability::Unknown { reason } => DomainStatus::Error(reason),
-                };
+                let status = DomainStatus::from_availability(result.availability);
+                explanations.lock().unwrap().insert(tld.clone(), result.explanation);
                 
+                if specific_in_sweep.as_deref() == Some(tld.as_str()) {
+                    *specific_status.lock().unwrap() = Some(status.clone());
+                }
+
                 let mut res = results.lock().unwrap();
                 res.insert(tld, status);
             }
         });
     }
 
+    /// Lock posture of `domain`, looked up with a detailed probe the first
+    /// time it's asked for.
+    fn locks(&self, domain: &str) -> Option<Locks> {
+        let mut locks = self.locks.lock().unwrap();
+        if let Some(known) = locks.get(domain) {
+            return known.clone();
+        }
+        locks.insert(domain.to_string(), None);
+        let (prober, found, domain) = (Arc::clone(&self.prober), Arc::clone(&self.locks), domain.to_string());
+        tokio::spawn(async move {
+            let (_, record) = prober.probe_one_detailed(&domain).await;
+            found.lock().unwrap().insert(domain, record.as_ref().map(Locks::of));
+        });
+        None
+    }
+
+    /// What to offer in place of the specific domain.
+    fn alternatives(&self) -> Vec<String> {
+        let Some(domain) = &self.specific_domain else {
+            return Vec::new();
+        };
+        match parse_domain_query(domain) {
+            (name, Some(tld)) => alternatives::alternatives(&name, &tld, &self.ranker.ranked_tlds()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// `domain` is the query under one of the swept TLDs.
+    fn in_sweep(&self, domain: &str) -> bool {
+        domain
+            .strip_prefix(&format!("{}.", self.query.to_lowercase()))
+            .is_some_and(|tld| self.tlds.iter().any(|t| t == tld))
+    }
+
+    /// The alternatives with their verdicts so far, from the sweep where it
+    /// covers them.
+    fn alternative_rows(&self) -> Vec<(String, DomainStatus)> {
+        let results = self.results.lock().unwrap();
+        let statuses = self.alternative_status.lock().unwrap();
+        self.alternatives()
+            .into_iter()
+            .map(|domain| {
+                let status = if self.in_sweep(&domain) {
+                    let tld = &domain[self.query.len() + 1..];
+                    results.get(tld).cloned()
+                } else {
+                    statuses.get(&domain).cloned()
+                };
+                (domain, status.unwrap_or(DomainStatus::Checking))
+            })
+            .collect()
+    }
+
+    /// Alternatives are shown, and picked with 1-9, once the specific
+    /// domain turns out taken.
+    fn alternatives_shown(&self) -> bool {
+        *self.specific_domain_status.lock().unwrap() == Some(DomainStatus::Taken)
+    }
+
+    /// Makes the `index`th alternative the specific domain and searches it.
+    fn pick_alternative(&mut self, index: usize) {
+        let Some((domain, _)) = self.alternative_rows().into_iter().nth(index) else {
+            return;
+        };
+        let (name, _) = parse_domain_query(&domain);
+        self.query = name;
+        self.specific_domain = Some(domain.clone());
+        *self.specific_domain_status.lock().unwrap() = None;
+        self.list_state.select(Some(0));
+        self.toast_message = Some((format!("Searching {}", domain), std::time::Instant::now()));
+        self.start_checking();
+    }
+
     fn get_sorted_results(&self) -> Vec<(String, DomainStatus)> {
         let results = self.results.lock().unwrap();
         let mut sorted: Vec<_> = self
@@ -399,70 +1603,164 @@ impl App {
             .map(|tld| (tld.clone(), results.get(tld).cloned().unwrap_or(DomainStatus::Pending)))
             .collect();
 
+        // Priority TLDs first in rank order, then by status and alphabetically
         sorted.sort_by(|a, b| {
-            // First sort by priority (priority TLDs first)
-            let a_priority = PRIORITY_TLDS.iter().position(|&t| t == a.0.as_str());
-            let b_priority = PRIORITY_TLDS.iter().position(|&t| t == b.0.as_str());
-
-            match (a_priority, b_priority) {
-                (Some(a_pos), Some(b_pos)) => {
-                    // Both are priority, maintain priority order
-                    a_pos.cmp(&b_pos)
-                }
-                (Some(_), None) => std::cmp::Ordering::Less, // a is priority, comes first
-                (None, Some(_)) => std::cmp::Ordering::Greater, // b is priority, comes first
-                (None, None) => {
-                    // Neither is priority, sort by status then alphabetically
-                    let order_a = status_order(&a.1);
-                    let order_b = status_order(&b.1);
-                    order_a.cmp(&order_b).then_with(|| a.0.cmp(&b.0))
-                }
-            }
+            self.ranker
+                .compare(&a.0, &b.0)
+                .then_with(|| status_order(&a.1).cmp(&status_order(&b.1)))
+                .then_with(|| a.0.cmp(&b.0))
         });
 
         sorted
     }
 }
 
-const PRIORITY_TLDS: &[&str] = &[
-    "com", "net", "org", "io", "ai", "dev", "app", "co", "me", "tech",
-    "xyz", "online", "site", "store", "shop", "blog", "cloud", "digital",
-    "eu", "us", "info", "email", "pro", "live", "zone", "team", "solutions"
-];
-
 fn get_builtin_tlds() -> Vec<String> {
-    PRIORITY_TLDS.iter().map(|s| s.to_string()).collect()
-}
-
-/// Sort TLDs with priority TLDs first, then alphabetically
-fn sort_tlds_with_priority(mut tlds: Vec<String>) -> Vec<String> {
-    tlds.sort_by(|a, b| {
-        let a_priority = PRIORITY_TLDS.iter().position(|&t| t == a.as_str());
-        let b_priority = PRIORITY_TLDS.iter().position(|&t| t == b.as_str());
-
-        match (a_priority, b_priority) {
-            (Some(a_pos), Some(b_pos)) => a_pos.cmp(&b_pos),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.cmp(b),
-        }
-    });
-
-    tlds
+    TldRanker::default().ranked_tlds()
 }
 
+#[cfg(feature = "tui")]
 fn status_order(status: &DomainStatus) -> u8 {
     match status {
         DomainStatus::Available => 0,
         DomainStatus::Checking => 1,
         DomainStatus::Pending => 2,
-        DomainStatus::Taken => 3,
-        DomainStatus::Error(_) => 4,
+        DomainStatus::Premium => 3,
+        DomainStatus::Reserved => 4,
+        DomainStatus::Taken => 5,
+        DomainStatus::Maintenance => 6,
+        DomainStatus::Error(_) => 7,
+        DomainStatus::Invalid => 8,
+    }
+}
+
+fn main() {
+    crash::install();
+    if let Err(e) = run() {
+        eprintln!("{}", crash::describe(e.as_ref()));
+        std::process::exit(1);
+    }
+}
+
+/// A lone word that names a subcommand (`dq schema`) runs it rather than
+/// searching for it; say how to search instead.
+fn hint_bare_subcommand() {
+    let words: Vec<String> = std::env::args().skip(1).collect();
+    if let [word] = &words[..] {
+        if Args::command().find_subcommand(word).is_some() && io::stderr().is_terminal() {
+            eprintln!("Running `dq {0}`; to search for {0}.* use `dq -- {0}`", word);
+        }
     }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let mut args = Args::parse();
+    let mut report_output = None;
+    hint_bare_subcommand();
+
+    // e.g. `RUST_LOG=librdap_storm=debug` to see every request and wait
+    if std::env::var_os("RUST_LOG").is_some() {
+        tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .with_writer(io::stderr)
+            .init();
+    }
+
+    match args.command {
+        Some(Command::Schema { record }) => {
+            print_schema(record)?;
+            return Ok(());
+        }
+        Some(Command::Stats { own: true, json, .. }) => {
+            match UsageStats::load() {
+                Some(usage) if json => println!("{}", serde_json::to_string_pretty(&usage)?),
+                Some(usage) => usage.print(),
+                None => eprintln!("Nothing recorded yet; set `[usage] enabled = true` in the config or DQ_USAGE=true"),
+            }
+            return Ok(());
+        }
+        Some(Command::Stats { ref files, json, .. }) => {
+            let mut stats = stats::collect_stats(files)?;
+            stats.endpoint_notices = ProbeHistory::load().notices().clone();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                stats.print();
+            }
+            return Ok(());
+        }
+        Some(Command::Watch { ref action }) => {
+            return run_watch(action, args.profile.as_deref());
+        }
+        Some(Command::Report { ref name, ref output, ref tlds }) => {
+            // A report is a non-interactive sweep of NAME
+            report_output = Some(output.clone().unwrap_or_else(|| PathBuf::from(format!("{}-report.html", name))));
+            args.query = Some(name.clone());
+            if tlds.is_some() {
+                args.tlds = tlds.clone();
+            }
+        }
+        Some(Command::Protect { ref brand, ref tlds, once }) => {
+            return run_protect(brand, tlds.clone(), once, args.profile.as_deref());
+        }
+        Some(Command::Brute { ref pattern, ref tld, rate, yes, json }) => {
+            return run_brute(pattern, tld, rate, yes, json, args.profile.as_deref());
+        }
+        Some(Command::Words { ref list, min, max, ref tlds, restart, json }) => {
+            return run_words(list, (min, max), tlds.clone(), restart, json, args.profile.as_deref());
+        }
+        Some(Command::Ctl { ref command }) => {
+            return run_ctl(&command.to_control());
+        }
+        #[cfg(feature = "tui")]
+        Some(Command::Status) => {
+            return status::run(load_config(args.profile.as_deref())?.theme());
+        }
+        Some(Command::NewRegistrations { ref brand, json }) => {
+            let set = brand.as_deref().map_or(watcher::SNAPSHOT_SET.to_string(), protect::snapshot_set);
+            let Ok([latest, previous]) = <[Snapshot; 2]>::try_from(Snapshot::latest(&set, 2)) else {
+                eprintln!("Error: fewer than two snapshots of {} yet; run another pass first", set);
+                std::process::exit(1);
+            };
+            for registration in latest.newly_registered(&previous) {
+                if json {
+                    println!("{}", serde_json::to_string(&registration)?);
+                } else {
+                    println!(
+                        "{:<40}  {:<30}  {}",
+                        registration.domain,
+                        registration.entry.registrar.as_deref().unwrap_or("-"),
+                        registration.entry.created.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "self-update")]
+        Some(Command::SelfUpdate { check }) => {
+            return update::run(check);
+        }
+        Some(Command::Log { action, days, json }) => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let since = days.map_or(0, |d| now.saturating_sub(d * 86400));
+            for entry in audit::entries() {
+                if entry.at < since || action.is_some_and(|a| a != entry.action) {
+                    continue;
+                }
+                if json {
+                    println!("{}", serde_json::to_string(&entry)?);
+                } else {
+                    println!("{}  {:<13}  {}", audit::format_utc(entry.at), entry.action.label(), entry.detail);
+                }
+            }
+            return Ok(());
+        }
+        None => {}
+    }
 
     if args.print_default_config {
         println!("{}", get_default_config_toml());
@@ -483,30 +1781,125 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-    let config = load_config();
+    let mut config = match load_config(args.profile.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprint!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
+    audit::set_max_bytes(config.audit_log_max_bytes());
+
+    if args.registrable_only {
+        config.eligibility.registrable_only = Some(true);
+    }
+    if args.summary_hook.is_some() {
+        config.hooks.summary = args.summary_hook.clone();
+    }
+
+    let mut probe_config = config.probe_config();
+    if args.capture_headers {
+        probe_config.capture_headers = DIAGNOSTIC_HEADERS.iter().map(|h| h.to_string()).collect();
+    }
+    if args.capture_raw {
+        probe_config.capture_raw = true;
+    }
+    if args.confirm {
+        probe_config.confirm_available = true;
+    }
+    if let Some(proxy) = args.proxy {
+        probe_config.proxy = Some(proxy);
+    }
+    if let Some(resolver) = args.resolver {
+        probe_config.resolver = resolver;
+    }
+    #[cfg(feature = "sqlite-cache")]
+    if let Some(max_age) = args.cached {
+        probe_config.result_db = cache::result_db_path();
+        probe_config.result_db_max_age = Some(max_age);
+    }
+    let mode = if args.quick {
+        SweepMode::Quick
+    } else if args.thorough {
+        SweepMode::Thorough
+    } else {
+        SweepMode::Normal
+    };
+    if args.no_cache || mode == SweepMode::Thorough {
+        config.cache.enabled = Some(false);
+    }
 
+    let batch = args.ndjson || report_output.is_some();
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(async {
         let user_specified_tlds = args.tlds.is_some();
+        let client = reqwest::Client::new();
+        let deadline = args.deadline.map(|d| started + d.saturating_sub(DEADLINE_MARGIN));
 
-        let default_tlds = if let Some(custom_tlds) = args.tlds {
-            custom_tlds
+        let (default_tlds, tld_source) = if let Some(custom_tlds) = args.tlds {
+            let tlds = config.expand_groups(custom_tlds).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            });
+            (tlds, TldSource::Custom)
         } else {
-            let client = reqwest::Client::new();
-            match fetch_iana_tlds(&client).await {
-                Ok(tlds) => tlds,
-                Err(e) => {
+            match within(deadline, cache::iana_tlds(&client)).await {
+                Some(Ok(tlds)) => (tlds, TldSource::Iana),
+                Some(Err(e)) => {
                     eprintln!("Warning: Failed to fetch from IANA ({}), using built-in list", e);
-                    get_builtin_tlds()
+                    (get_builtin_tlds(), TldSource::Builtin)
                 }
+                None => {
+                    eprintln!("Warning: --deadline passed fetching the IANA TLD list, using built-in list");
+                    (get_builtin_tlds(), TldSource::Builtin)
+                }
+            }
+        };
+
+        let (default_tlds, merged) = dedupe_tlds(default_tlds);
+        let mut context = RunContext::new(tld_source, default_tlds.iter().cloned().collect(), deadline);
+        context.metrics = args.metrics;
+        context.latency_histogram = args.latency_histogram.clone();
+        if user_specified_tlds {
+            for (tld, sources) in &merged {
+                eprintln!("Note: .{} given {} times ({}), checking it once", tld, sources.len(), sources.join(", "));
             }
+        }
+
+        let default_tlds = TldRanker::default().sort(default_tlds);
+        let mut default_tlds = apply_config_to_tlds(default_tlds, &config);
+
+        let mut state = SessionState {
+            history: (!args.no_adaptive).then(ProbeHistory::load),
         };
+        if let (Some(store), Some(history)) = (RemoteStore::from_config(&config.remote), state.history.as_mut()) {
+            match within(deadline, store.pull::<ProbeHistory>(HISTORY_FILE)).await {
+                Some(Ok(Some((shared, _)))) => history.merge(shared),
+                Some(Ok(None)) | None => {}
+                Some(Err(e)) => eprintln!("Warning: {}, using local probe history", e),
+            }
+        }
+        if let Some(history) = state.history.as_mut() {
+            history.expire(config.history_max_age());
+        }
+        if let Some(history) = &state.history {
+            default_tlds = history.order(default_tlds, &TldRanker::default());
+            history.tune(&mut probe_config);
+        }
+        // The TUI keeps restricted TLDs around so the filter can be toggled
+        if batch && config.eligibility.registrable_only == Some(true) {
+            let registrant = config.eligibility.registrant();
+            default_tlds.retain(|tld| is_registrable(tld, &registrant));
+        }
 
-        let default_tlds = sort_tlds_with_priority(default_tlds);
-        let default_tlds = apply_config_to_tlds(default_tlds, &config);
+        let mut plan = SweepPlan { mode, pinned: config.tlds.always.clone() };
+        if user_specified_tlds {
+            plan.pinned.extend(default_tlds.iter().cloned());
+        }
 
         let (query, extracted_tld, tlds) = if let Some(q) = args.query {
             let (base_name, extracted_tld) = parse_domain_query(&q);
+            plan.pinned.extend(extracted_tld.clone());
 
             let final_tlds = if user_specified_tlds {
                 default_tlds
@@ -517,74 +1910,826 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             };
 
             (base_name, extracted_tld, final_tlds)
-        } else if args.ndjson {
+        } else if args.ndjson || args.output.is_some() {
             eprintln!("Error: Query required in NDJSON mode");
             std::process::exit(1);
         } else {
-            return run_tui(None, None, default_tlds).await;
+            return run_tui(None, None, default_tlds, probe_config, &config, state, plan).await;
         };
 
-        if args.ndjson {
-            run_ndjson(query, tlds).await
+        if let Some(output) = report_output {
+            let tlds = plan.select_tlds(&tlds);
+            run_report(query, tlds, mode.apply(probe_config), &config, state, context.deadline, output).await
+        } else if args.ndjson || args.output.is_some() || args.output_dir.is_some() || args.compress.is_some() {
+            let tlds = plan.select_tlds(&tlds);
+            let output = OutputOptions {
+                path: args.output,
+                dir: args.output_dir,
+                split_every: args.split_every.map(|n| n as usize),
+                format: args.format,
+                compress: args.compress,
+                buffer_limit: args.buffer_limit.unwrap_or(output::DEFAULT_BUFFER_LIMIT),
+            };
+            run_ndjson(query, tlds, mode.apply(probe_config), &config, state, context, output).await
         } else {
-            run_tui(Some(query), extracted_tld, tlds).await
+            run_tui(Some(query), extracted_tld, tlds, probe_config, &config, state, plan).await
         }
     })
 }
 
-async fn run_ndjson(query: String, tlds: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
-    let prober = Prober::with_config(ProbeConfig {
-        timeout: Duration::from_secs(5),
-        whois_fallback: true,
-        max_rate_per_endpoint: 20,
-        max_concurrent_per_endpoint: 10,
-    });
+/// Applies `change` to the watchlist. With a remote backend the shared copy
+/// is authoritative (so removals propagate); the local file mirrors it and
+/// seeds it the first time.
+fn update_watchlist<F>(remote: Option<&RemoteStore>, mut change: F) -> Result<Watchlist, Box<dyn std::error::Error>>
+where
+    F: FnMut(&mut Watchlist),
+{
+    let local = Watchlist::load()?;
+    let watchlist = match remote {
+        Some(store) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(store.update(WATCHLIST_FILE, |shared: Option<Watchlist>| {
+                let mut watchlist = shared.unwrap_or_else(|| local.clone());
+                change(&mut watchlist);
+                watchlist
+            }))?
+        }
+        None => {
+            let mut watchlist = local;
+            change(&mut watchlist);
+            watchlist
+        }
+    };
+    watchlist.save()?;
+    Ok(watchlist)
+}
 
-    let domains: Vec<String> = tlds.iter()
-        .map(|tld| format!("{}.{}", query, tld))
-        .collect();
+/// The shared watchlist when a remote backend is configured and reachable,
+/// the local one otherwise.
+fn current_watchlist(remote: Option<&RemoteStore>) -> Result<Watchlist, String> {
+    let watchlist = Watchlist::load()?;
+    let Some(store) = remote else {
+        return Ok(watchlist);
+    };
+    let shared = tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())
+        .and_then(|rt| rt.block_on(store.pull::<Watchlist>(WATCHLIST_FILE)));
+    match shared {
+        Ok(Some((shared, _))) => {
+            let _ = shared.save();
+            Ok(shared)
+        }
+        Ok(None) => Ok(watchlist),
+        Err(e) => {
+            eprintln!("Warning: {}, showing the local watchlist", e);
+            Ok(watchlist)
+        }
+    }
+}
+
+fn run_watch(action: &WatchAction, profile: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config(profile).ok();
+    if let Some(config) = &config {
+        audit::set_max_bytes(config.audit_log_max_bytes());
+    }
+    let remote = config.as_ref().and_then(|c| RemoteStore::from_config(&c.remote));
+    let remote = remote.as_ref();
+
+    match action {
+        WatchAction::List => {
+            let watchlist = current_watchlist(remote)?;
+            for name in &watchlist.names {
+                println!("{}", name);
+            }
+            for name in &watchlist.shortlist {
+                println!("{} (shortlist)", name);
+            }
+        }
+        WatchAction::Add { names, shortlist } => {
+            update_watchlist(remote, |watchlist| watchlist.add(names, *shortlist))?;
+            let list = if *shortlist { "shortlist" } else { "watchlist" };
+            audit::record(AuditAction::WatchAdd, format!("{} to {}", names.join(", "), list));
+        }
+        WatchAction::Remove { names } => {
+            update_watchlist(remote, |watchlist| watchlist.remove(names))?;
+            audit::record(AuditAction::WatchRemove, names.join(", "));
+        }
+        WatchAction::Export { file, format } => {
+            let mut watchlist = current_watchlist(remote)?;
+            // Names watched through the config travel with the export
+            if let Some(config) = &config {
+                watchlist.add(&config.watch.names, false);
+            }
+            let format = format.unwrap_or_else(|| {
+                file.as_deref().map(ListFormat::from_path).unwrap_or(ListFormat::Toml)
+            });
+            let content = watchlist.export(format)?;
+            match file {
+                Some(path) => std::fs::write(path, content)?,
+                None => print!("{}", content),
+            }
+            let destination = file.as_ref().map_or("stdout".to_string(), |p| p.display().to_string());
+            audit::record(
+                AuditAction::WatchExport,
+                format!("{} name(s) to {}", watchlist.names.len() + watchlist.shortlist.len(), destination),
+            );
+        }
+        WatchAction::Import { file, format, replace } => {
+            let format = format.unwrap_or_else(|| ListFormat::from_path(file));
+            let content = std::fs::read_to_string(file)?;
+            let imported = Watchlist::parse(&content, format)
+                .map_err(|e| format!("{}: {}", file.display(), e))?;
+            let mut added = 0;
+            update_watchlist(remote, |watchlist| {
+                if *replace {
+                    *watchlist = Watchlist::default();
+                }
+                added = watchlist.merge(imported.clone());
+            })?;
+            eprintln!("Imported {} new name(s) from {}", added, file.display());
+            audit::record(AuditAction::WatchImport, format!("{} new name(s) from {}", added, file.display()));
+        }
+        WatchAction::Run { tlds } | WatchAction::CheckOnce { tlds } => {
+            let once = matches!(action, WatchAction::CheckOnce { .. });
+            let config = config.unwrap_or_default();
+            let lock = match instance::acquire()? {
+                Ok(lock) => lock,
+                Err(running) => {
+                    let pid = running.pid.map_or(String::new(), |pid| format!(" (pid {})", pid));
+                    eprintln!("Error: a dq watcher is already running{}; use `dq watch query NAME` to ask it", pid);
+                    std::process::exit(1);
+                }
+            };
+            let tlds = match tlds {
+                Some(tlds) => dedupe_tlds(config.expand_groups(tlds.clone())?).0,
+                None => TldRanker::default().ranked_tlds(),
+            };
+            let tlds = apply_config_to_tlds(tlds, &config);
+
+            let rt = tokio::runtime::Runtime::new()?;
+            let changed = rt.block_on(async {
+                let prober = Prober::with_config(config.probe_config());
+                if let Err(e) = prober.check_network().await {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                if once {
+                    watcher::check_once(lock, prober, &config, tlds).await
+                } else {
+                    watcher::run(lock, prober, &config, tlds).await.map(|_| 0)
+                }
+            })?;
+            if changed > 0 {
+                std::process::exit(watcher::CHANGED_EXIT);
+            }
+        }
+        WatchAction::Query { name } => run_ctl(&watcher::Control::Query(name.clone()))?,
+    }
+    Ok(())
+}
+
+fn run_protect(
+    brand: &str,
+    tlds: Option<Vec<String>>,
+    once: bool,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config(profile)?;
+    let tlds = match tlds {
+        Some(tlds) => dedupe_tlds(config.expand_groups(tlds)?).0,
+        None => protect::DEFAULT_PROTECT_TLDS.iter().map(|t| t.to_string()).collect(),
+    };
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let prober = Prober::with_config(config.probe_config());
+        if let Err(e) = prober.check_network().await {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        protect::run(prober, brand, &tlds, config.watch_interval(), once, &UsageRecorder::new(&config)).await
+    })?;
+    Ok(())
+}
+
+fn run_brute(
+    pattern: &str,
+    tld: &str,
+    rate: u32,
+    yes: bool,
+    json: bool,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config(profile)?;
+    let tld = tld.trim_start_matches('.').to_lowercase();
+    if !config::is_valid_tld(&tld) {
+        eprintln!("Error: '{}' is not a TLD", tld);
+        std::process::exit(1);
+    }
+    let patterns = match brute::Pattern::parse(pattern) {
+        Ok(patterns) => patterns,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let size = patterns.iter().map(brute::Pattern::size).fold(0, u64::saturating_add);
+    if size > brute::MAX_SPACE {
+        eprintln!(
+            "Error: '{}' matches {} names, over the {} dq brute will try; pin down more characters",
+            pattern,
+            size,
+            brute::MAX_SPACE
+        );
+        std::process::exit(1);
+    }
+    if size > brute::CONFIRM_ABOVE && !yes && !brute::confirm(size, &tld, rate)? {
+        std::process::exit(1);
+    }
+
+    // Most short names are taken, and an NS lookup says so without
+    // bothering the registry
+    let mut probe_config = config.probe_config();
+    probe_config.dns_first = true;
+    probe_config.max_rate_per_endpoint = probe_config.max_rate_per_endpoint.min(rate);
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let prober = Prober::with_config(probe_config);
+        if let Err(e) = prober.check_network().await {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        brute::run(prober, patterns, &tld, rate, json, &UsageRecorder::new(&config)).await
+    })?;
+    Ok(())
+}
+
+fn run_words(
+    list: &Path,
+    (min, max): (usize, usize),
+    tlds: Option<Vec<String>>,
+    restart: bool,
+    json: bool,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config(profile)?;
+    let tlds = dedupe_tlds(config.expand_groups(tlds.unwrap_or_else(|| vec!["com".to_string()]))?).0;
+    if min == 0 || min > max {
+        eprintln!("Error: --min must be at least 1 and no more than --max");
+        std::process::exit(1);
+    }
+    let words = match words::read_words(list, min, max) {
+        Ok(words) => words,
+        Err(e) => {
+            eprintln!("Error: could not read {}: {}", list.display(), e);
+            std::process::exit(1);
+        }
+    };
+    if words.is_empty() {
+        eprintln!("Error: no words of {} to {} letters in {}", min, max, list.display());
+        std::process::exit(1);
+    }
+    let checkpoint = words::Checkpoint::new(list, (min, max), &tlds, words.len());
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let prober = Prober::with_config(config.probe_config());
+        if let Err(e) = prober.check_network().await {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        words::run(prober, words, checkpoint, restart, json, &UsageRecorder::new(&config)).await
+    })?;
+    Ok(())
+}
 
-    let mut stream = prober.probe_stream(domains);
+/// Sends one control request to the running watcher and prints its reply.
+/// Exits with 1 when no watcher answers or it reports an error.
+fn run_ctl(request: &watcher::Control) -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let mut failed = false;
+    let sent = rt.block_on(instance::send(&request.to_line(), |line| {
+        failed |= serde_json::from_str::<serde_json::Value>(line).is_ok_and(|v| v.get("error").is_some());
+        println!("{}", line);
+    }));
+    if let Err(e) = sent {
+        eprintln!("Error: no dq watcher answered ({}); start one with `dq watch run`", e);
+        std::process::exit(1);
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Saves probe history locally and, with a remote backend, merges it into
+/// the shared copy.
+async fn save_history(mut history: ProbeHistory, config: &Config) {
+    if let Some(store) = RemoteStore::from_config(&config.remote) {
+        let merged = store
+            .update(HISTORY_FILE, |shared: Option<ProbeHistory>| {
+                if let Some(shared) = shared {
+                    history.merge(shared);
+                }
+                history.clone()
+            })
+            .await;
+        if let Err(e) = merged {
+            eprintln!("Warning: {}", e);
+        }
+    }
+    history.save();
+}
+
+async fn run_report(
+    query: String,
+    tlds: Vec<String>,
+    probe_config: ProbeConfig,
+    config: &Config,
+    state: SessionState,
+    deadline: Option<Instant>,
+    output: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let concurrency = probe_config.max_concurrent_per_endpoint as usize * 10;
+    let prober = Prober::with_config(probe_config);
+    let mut history = state.history;
+    let finish_by = deadline.map(|at| at + DEADLINE_MARGIN);
+    let mut prices = LivePrices::start(config);
+
+    if let Some(Err(e)) = within(deadline, prober.check_network()).await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
+    // Detailed probes, so taken domains come with registrar and expiry
+    let interrupt = Interrupt::install();
+    let mut stream = futures::stream::iter(interrupt.guard(tlds.iter().map(|tld| format!("{}.{}", query, tld).to_lowercase())))
+        .map(|domain| {
+            let prober = &prober;
+            async move { prober.probe_one_detailed(&domain).await }
+        })
+        .buffer_unordered(concurrency)
+        .take_until(Box::pin(RunContext::deadline_passed(deadline)));
+
+    let mut rows = Vec::new();
+    while let Some((result, record)) = stream.next().await {
+        if let Some(history) = history.as_mut() {
+            history.record(&result);
+        }
+        let tld = public_suffix(&result.domain).unwrap_or("").to_string();
+        rows.push(ReportRow {
+            available: match result.availability {
+                Availability::Available => Some(true),
+                Availability::Taken | Availability::Reserved | Availability::Premium => Some(false),
+                Availability::Unknown { .. } => None,
+            },
+            price: None,
+            price_value: None,
+            registrar: record.as_ref().and_then(|r| r.registrar.clone()),
+            dnssec: record.as_ref().and_then(|r| r.dnssec),
+            locks: record.as_ref().map(Locks::of),
+            expires: record.and_then(|r| r.expires),
+            explanation: result.explanation,
+            domain: result.domain,
+            tld,
+        });
+    }
+    drop(stream);
+    let probed = rows.len();
+
+    // Past --deadline the rest of the TLDs are listed unprobed
+    if !interrupt.is_set() && deadline.is_some_and(|at| Instant::now() >= at) {
+        let answered: HashSet<String> = rows.iter().map(|row| row.tld.clone()).collect();
+        for tld in tlds.iter().filter(|tld| !answered.contains(*tld)) {
+            rows.push(ReportRow {
+                available: None,
+                price: None,
+                price_value: None,
+                registrar: None,
+                expires: None,
+                dnssec: None,
+                locks: None,
+                explanation: "Skipped: --deadline reached before it was probed".to_string(),
+                domain: format!("{}.{}", query, tld).to_lowercase(),
+                tld: tld.clone(),
+            });
+        }
+    }
+
+    // Prices were fetched alongside the sweep; without them by the
+    // deadline the cached ones do
+    within(finish_by, prices.finish()).await;
+    let prices = prices.current().await;
+    for row in &mut rows {
+        row.price = prices.display(&row.tld);
+        row.price_value = prices.converted(&row.tld);
+    }
+
+    // Keep the sweep's TLD order rather than completion order
+    rows.sort_by_key(|row| tlds.iter().position(|t| *t == row.tld));
+    std::fs::write(&output, report::render_html(&query, &rows))?;
+    eprintln!("Wrote {}", output.display());
+    audit::record(AuditAction::Report, format!("{} ({} TLDs) to {}", query, rows.len(), output.display()));
+    UsageRecorder::new(config).record(1, probed as u64, &prober.metrics());
+
+    if let Some(mut history) = history {
+        history.record_help(prober.endpoint_help());
+        within(finish_by, save_history(history, config)).await;
+    }
+
+    if interrupt.is_set() {
+        eprintln!("Interrupted: the report covers {} of {} TLDs", rows.len(), tlds.len());
+        std::process::exit(INTERRUPTED_EXIT);
+    }
+
+    Ok(())
+}
 
-    while let Some(result) = stream.next().await {
-        let tld = result.domain
-            .rsplit('.')
-            .next()
-            .unwrap_or("")
-            .to_string();
+/// What every record of a sweep says about the run that produced it,
+/// when the run has to end and what to report after it.
+struct RunContext {
+    run_id: String,
+    tld_source: TldSource,
+    /// The TLDs of that source; any others were added by hand
+    listed: HashSet<String>,
+    /// When probing stops for `--deadline`
+    deadline: Option<Instant>,
+    /// `--metrics`
+    metrics: bool,
+    /// `--latency-histogram`
+    latency_histogram: Option<PathBuf>,
+}
+
+impl RunContext {
+    fn new(tld_source: TldSource, listed: HashSet<String>, deadline: Option<Instant>) -> Self {
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        // Tells apart runs started in the same millisecond
+        let random = RandomState::new().build_hasher().finish();
+        Self {
+            run_id: format!("{:x}-{:04x}", millis, random & 0xffff),
+            tld_source,
+            listed,
+            deadline,
+            metrics: false,
+            latency_histogram: None,
+        }
+    }
+
+    /// Resolves at the deadline, never without one.
+    async fn deadline_passed(deadline: Option<Instant>) {
+        match deadline {
+            Some(at) => tokio::time::sleep_until(at.into()).await,
+            None => futures::future::pending().await,
+        }
+    }
+
+    fn tld_source(&self, tld: &str) -> TldSource {
+        if self.listed.contains(tld) {
+            self.tld_source
+        } else {
+            TldSource::Custom
+        }
+    }
+}
+
+/// Runs `step` unless `deadline` passes first, `None` then; steps before
+/// and after the sweep are bounded by `--deadline` too.
+async fn within<T>(deadline: Option<Instant>, step: impl std::future::Future<Output = T>) -> Option<T> {
+    match deadline {
+        Some(at) => tokio::time::timeout_at(at.into(), step).await.ok(),
+        None => Some(step.await),
+    }
+}
+
+/// On-disk state a sweep starts from.
+struct SessionState {
+    /// Endpoint history, unless adaptive tuning is off
+    history: Option<ProbeHistory>,
+}
+
+async fn run_ndjson(
+    query: String,
+    tlds: Vec<String>,
+    probe_config: ProbeConfig,
+    config: &Config,
+    state: SessionState,
+    context: RunContext,
+    output: OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = output.format();
+    let header = (format == Format::Csv).then(|| DomainCheckResult::CSV_HEADER.to_string());
+    let writer = LineWriter::spawn(&output, header)?;
+    let prober = Prober::with_config(probe_config);
+    let mut history = state.history;
+    let mut result_cache = ResultCache::load(config.result_cache_ttl(), config.max_cached_results());
+    let reuse_cache = config.cache.enabled != Some(false);
+    let finish_by = context.deadline.map(|at| at + DEADLINE_MARGIN);
+    // Records carry cached prices until fresh ones arrive
+    let mut prices = LivePrices::start(config);
+
+    if let Some(Err(e)) = within(context.deadline, prober.check_network()).await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    let publisher = match within(context.deadline, Publisher::connect(&config.publish)).await.flatten().transpose() {
+        Ok(publisher) => publisher,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut replayed = Vec::new();
+    let mut domains = Vec::new();
+    let mut forms = HashMap::new();
+    for tld in &tlds {
+        for (domain, form) in sweep_domains(&query, tld) {
+            if let Some(form) = form {
+                forms.insert(domain.clone(), form);
+            }
+            match result_cache.get(&domain).filter(|_| reuse_cache) {
+                Some(cached) => replayed.push(cached.to_probe_result(&domain)),
+                None => domains.push(domain),
+            }
+        }
+    }
+
+    // Cached verdicts go out first, flagged so they aren't re-recorded.
+    // Ctrl-C stops new probes; the ones in flight are still written
+    let interrupt = Interrupt::install();
+    let mut summary = SweepSummary {
+        run_id: context.run_id.clone(),
+        query: query.clone(),
+        interrupted: false,
+        results: 0,
+        available: 0,
+        taken: 0,
+        unknown: 0,
+        deadline_reached: false,
+        skipped: 0,
+        not_probed: replayed.len() + domains.len(),
+        latency: None,
+    };
+    let mut unanswered: HashSet<String> = replayed.iter().map(|r| r.domain.clone()).chain(domains.iter().cloned()).collect();
+    let mut stream = futures::stream::iter(replayed)
+        .map(|result| (result, true))
+        .chain(prober.probe_stream(interrupt.guard(domains)).map(|result| (result, false)))
+        .take_until(Box::pin(RunContext::deadline_passed(context.deadline)));
+    let mut warned_whois = false;
+    let mut warned_endpoints = HashSet::new();
+    let mut warned_circuits = HashSet::new();
+    let mut writing = true;
+    let mut probed = 0;
+    let mut latency = ProbeLatency::new();
+
+    while let Some((result, replayed)) = stream.next().await {
+        if !warned_whois && !prober.whois_available() {
+            eprintln!("Warning: outbound port 43 looks blocked, WHOIS fallback disabled for this run");
+            warned_whois = true;
+        }
+        for endpoint in prober.unreliable_endpoints() {
+            if warned_endpoints.insert(endpoint.clone()) {
+                eprintln!("Warning: {} reports registered canaries as available; its results are marked unknown", endpoint);
+            }
+        }
+        for endpoint in prober.open_circuits() {
+            if warned_circuits.insert(endpoint.clone()) {
+                eprintln!("Warning: {} keeps failing; skipping it for now", endpoint);
+            }
+        }
+
+        if !replayed {
+            probed += 1;
+            latency.record(result.duration);
+            result_cache.record(&result);
+            if let Some(history) = history.as_mut() {
+                history.record(&result);
+            }
+        }
+
+        let tld = public_suffix(&result.domain).unwrap_or("").to_string();
         
         let (available, status, error) = match result.availability {
             Availability::Available => (Some(true), AvailabilityStatus::Available, None),
             Availability::Taken => (Some(false), AvailabilityStatus::Taken, None),
+            Availability::Reserved => (Some(false), AvailabilityStatus::Reserved, None),
+            Availability::Premium => (Some(false), AvailabilityStatus::Premium, None),
+            Availability::Unknown { reason: reason @ ProbeError::Maintenance } => {
+                (None, AvailabilityStatus::Maintenance, Some(reason))
+            }
+            Availability::Unknown { reason: reason @ ProbeError::InvalidName } => {
+                (None, AvailabilityStatus::Invalid, Some(reason))
+            }
             Availability::Unknown { reason } => (None, AvailabilityStatus::Error, Some(reason)),
         };
+        let error_kind = error.as_ref().map(|reason| reason.kind().to_string());
+        let error = error.map(|reason| reason.to_string());
         
+        let restriction = restriction_for(&tld).map(|r| r.requirement.to_string());
+        let launch_phase = prober.launch_phase(&tld).map(|(phase, _)| phase.name().to_string());
+        let prices = prices.current().await;
+        let price_usd = prices.get(&tld);
+        let price = prices.converted(&tld);
+        let currency = price.map(|_| prices.currency().to_string());
         let check_result = DomainCheckResult {
+            run_id: context.run_id.clone(),
             query: query.clone(),
+            tld_source: Some(context.tld_source(&tld)),
             tld,
+            form: forms.get(&result.domain).copied(),
             domain: result.domain,
             available,
             status,
             error,
+            error_kind,
+            restriction,
+            launch_phase,
+            price_usd,
+            price,
+            currency,
+            duration_ms: result.duration.as_millis() as u64,
+            endpoint: result.endpoint,
+            explanation: Some(result.explanation),
+            source: Some(result.source.name().to_string()),
+            confidence: Some(result.confidence.name().to_string()),
+            headers: result.headers.into_iter().collect(),
+            raw: result.raw,
         };
         
-        if let Ok(json) = serde_json::to_string(&check_result) {
-            println!("{}", json);
-            io::stdout().flush()?;
+        let line = match format {
+            Format::Ndjson => serde_json::to_string(&check_result)?,
+            Format::Csv => check_result.csv_row(),
+        };
+        if let Some(publisher) = &publisher {
+            publisher.send(&check_result.tld, &context.run_id, serde_json::to_string(&check_result)?).await;
+        }
+        if !writer.send(&check_result.tld, line).await {
+            writing = false;
+            break;
+        }
+        unanswered.remove(&check_result.domain);
+        summary.results += 1;
+        summary.not_probed = summary.not_probed.saturating_sub(1);
+        match check_result.available {
+            Some(true) => summary.available += 1,
+            Some(false) => summary.taken += 1,
+            None => summary.unknown += 1,
+        }
+    }
+    drop(stream);
+
+    // Cut off by --deadline: what didn't get an answer is still accounted for
+    let cut_off = context.deadline.is_some_and(|at| Instant::now() >= at);
+    if writing && cut_off && !interrupt.is_set() && !unanswered.is_empty() {
+        summary.deadline_reached = true;
+        let prices = prices.current().await;
+        let planned = tlds.iter().flat_map(|tld| sweep_domains(&query, tld).into_iter().map(move |(domain, form)| (tld, domain, form)));
+        for (tld, domain, form) in planned {
+            if !unanswered.remove(&domain) {
+                continue;
+            }
+            let check_result = DomainCheckResult {
+                run_id: context.run_id.clone(),
+                query: query.clone(),
+                tld: tld.clone(),
+                tld_source: Some(context.tld_source(tld)),
+                domain,
+                form,
+                available: None,
+                status: AvailabilityStatus::Skipped,
+                error: Some("Deadline reached".to_string()),
+                error_kind: None,
+                restriction: restriction_for(tld).map(|r| r.requirement.to_string()),
+                launch_phase: prober.launch_phase(tld).map(|(phase, _)| phase.name().to_string()),
+                price_usd: prices.get(tld),
+                price: prices.converted(tld),
+                currency: prices.converted(tld).map(|_| prices.currency().to_string()),
+                duration_ms: 0,
+                endpoint: None,
+                explanation: Some("Skipped: --deadline reached before it was probed".to_string()),
+                source: None,
+                confidence: None,
+                headers: BTreeMap::new(),
+                raw: None,
+            };
+            let line = match format {
+                Format::Ndjson => serde_json::to_string(&check_result)?,
+                Format::Csv => check_result.csv_row(),
+            };
+            if let Some(publisher) = &publisher {
+                publisher.send(tld, &context.run_id, serde_json::to_string(&check_result)?).await;
+            }
+            if !writer.send(tld, line).await {
+                break;
+            }
+            summary.results += 1;
+            summary.skipped += 1;
+            summary.not_probed = summary.not_probed.saturating_sub(1);
+        }
+    }
+
+    let written = writer.finish();
+    if let (Ok(()), Some(path)) = (&written, output.path.as_ref().or(output.dir.as_ref())) {
+        audit::record(AuditAction::Export, format!("{} result(s) of {} to {}", summary.results, query, path.display()));
+    }
+    if let Some(publisher) = publisher {
+        within(finish_by, publisher.finish()).await;
+    }
+    result_cache.save();
+    UsageRecorder::new(config).record(1, probed, &prober.metrics());
+    if let Some(mut history) = history {
+        history.record_help(prober.endpoint_help());
+        within(finish_by, save_history(history, config)).await;
+    }
+
+    summary.interrupted = interrupt.is_set();
+    summary.latency = latency.summary();
+    if let Some(path) = &context.latency_histogram {
+        if let Err(e) = latency.write(path) {
+            eprintln!("Warning: could not write {}: {}", path.display(), e);
         }
     }
+    within(finish_by, run_summary_hook(config.hooks.summary.as_deref(), &summary)).await;
 
+    match written {
+        // The reader went away (`dq -j foo | head`); nothing left to say
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+        written => written?,
+    }
+    if context.metrics {
+        print_metrics(&prober.metrics(), summary.latency.as_ref());
+    }
+    if summary.interrupted || summary.deadline_reached {
+        eprintln!("{}", serde_json::to_string(&summary)?);
+    }
+    if summary.interrupted {
+        std::process::exit(INTERRUPTED_EXIT);
+    }
     Ok(())
 }
 
-async fn run_tui(initial_query: Option<String>, specific_tld: Option<String>, tlds: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+/// The `--metrics` table, on stderr so it stays out of the records.
+fn print_metrics(metrics: &[(String, EndpointMetrics)], latency: Option<&LatencySummary>) {
+    if let Some(l) = latency {
+        eprintln!(
+            "{} probes: mean {} ms, p50 {} ms, p90 {} ms, p99 {} ms, p99.9 {} ms, max {} ms",
+            l.count, l.mean_ms, l.p50_ms, l.p90_ms, l.p99_ms, l.p999_ms, l.max_ms
+        );
+    }
+    if metrics.is_empty() {
+        return;
+    }
+    let ms = |latency: Option<Duration>| latency.map(|l| l.as_millis().to_string()).unwrap_or_default();
+    eprintln!(
+        "{:>8} {:>6} {:>6} {:>6} {:>8} {:>6} {:>7} {:>7} {:>7}  endpoint",
+        "requests", "200", "404", "429", "timeouts", "errors", "p50 ms", "p95 ms", "p99 ms"
+    );
+    for (endpoint, m) in metrics {
+        eprintln!(
+            "{:>8} {:>6} {:>6} {:>6} {:>8} {:>6} {:>7} {:>7} {:>7}  {}",
+            m.requests,
+            m.ok,
+            m.not_found,
+            m.rate_limited,
+            m.timeouts,
+            m.errors,
+            ms(m.p50),
+            ms(m.p95),
+            ms(m.p99),
+            endpoint
+        );
+    }
+}
+
+#[cfg(feature = "tui")]
+async fn run_tui(
+    initial_query: Option<String>,
+    specific_tld: Option<String>,
+    tlds: Vec<String>,
+    probe_config: ProbeConfig,
+    config: &Config,
+    state: SessionState,
+    plan: SweepPlan,
+) -> Result<(), Box<dyn std::error::Error>> {
+    install_panic_hook();
+    enter_terminal()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(initial_query, specific_tld, tlds);
+    let mut app = App::new(initial_query, specific_tld, tlds, probe_config, config);
+    app.history = state.history.map(|h| Arc::new(Mutex::new(h)));
+    let mode = plan.mode;
+    app.plan = plan;
+    app.pin_tlds();
+    app.set_mode(mode);
+
+    let prices = Arc::clone(&app.prices);
+    let price_config = config.clone();
+    tokio::spawn(async move {
+        let table = load_prices(&reqwest::Client::new(), &price_config).await;
+        *prices.lock().unwrap() = table;
+    });
+
     if !app.query.is_empty() {
         app.input_mode = false;
         app.start_checking();
@@ -592,13 +2737,15 @@ async fn run_tui(initial_query: Option<String>, specific_tld: Option<String>, tl
 
     let res = run_app(&mut terminal, &mut app).await;
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    leave_terminal()?;
+
+    app.result_cache.lock().unwrap().save();
+    app.usage.record(0, 0, &app.prober.metrics());
+    if let Some(history) = &app.history {
+        let mut history = history.lock().unwrap().clone();
+        history.record_help(app.prober.endpoint_help());
+        save_history(history, config).await;
+    }
 
     if let Err(err) = res {
         println!("{:?}", err)
@@ -607,31 +2754,114 @@ async fn run_tui(initial_query: Option<String>, specific_tld: Option<String>, tl
     Ok(())
 }
 
+/// Headless builds only stream; the interactive search needs the `tui`
+/// feature.
+#[cfg(not(feature = "tui"))]
+async fn run_tui(
+    _initial_query: Option<String>,
+    _specific_tld: Option<String>,
+    _tlds: Vec<String>,
+    _probe_config: ProbeConfig,
+    _config: &Config,
+    _state: SessionState,
+    _plan: SweepPlan,
+) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("Error: this dq was built without the TUI; use --ndjson, --output or `dq report`");
+    std::process::exit(2);
+}
+
+#[cfg(feature = "tui")]
+fn enter_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+}
+
+#[cfg(feature = "tui")]
+fn leave_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show)
+}
+
+#[cfg(feature = "tui")]
+/// Restores the terminal before the default panic message is printed, so a
+/// panic mid-sweep doesn't leave the shell in raw mode on the alternate screen.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = leave_terminal();
+        default_hook(info);
+    }));
+}
+
+/// Ctrl-Z arrives as a key press in raw mode, so stop ourselves explicitly:
+/// restore the terminal, raise SIGTSTP, and set it up again on SIGCONT.
+#[cfg(all(feature = "tui", unix))]
+fn suspend<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    leave_terminal()?;
+    // SAFETY: raise() only delivers a signal to the calling process.
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+    enter_terminal()?;
+    terminal.clear()
+}
+
+#[cfg(all(feature = "tui", not(unix)))]
+fn suspend<B: Backend>(_terminal: &mut Terminal<B>) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
 async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
     loop {
-        app.tick = app.tick.wrapping_add(1);
-        
         if let Some((_, created)) = &app.toast_message {
             if created.elapsed() > Duration::from_secs(2) {
                 app.toast_message = None;
             }
         }
         
-        terminal.draw(|f| ui(f, app))?;
+        let frame = terminal.draw(|f| ui(f, app))?;
+        if app.screenshot_pending {
+            app.screenshot_pending = false;
+            let message = match screenshot::save(frame.buffer, &app.query) {
+                Ok(path) => {
+                    audit::record(AuditAction::Screenshot, path.display().to_string());
+                    format!("Saved {} (+ .svg)", path.display())
+                }
+                Err(e) => format!("Screenshot failed: {}", e),
+            };
+            app.toast_message = Some((message, std::time::Instant::now()));
+        }
 
         if app.quit {
             break;
         }
 
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if app.input_mode {
+        // Redraw at least as often as the spinner turns
+        let poll = app.spinner.interval.map_or(REDRAW_INTERVAL, |i| i.min(REDRAW_INTERVAL));
+        if event::poll(poll)? {
+            let event = event::read()?;
+            if let Event::Resize(..) = event {
+                // Force a full repaint so the reflowed layout leaves no stale cells
+                terminal.clear()?;
+            }
+            if let Event::Key(key) = event {
+                if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    suspend(terminal)?;
+                    continue;
+                }
+
+                if app.settings.is_some() {
+                    app.handle_settings_key(key.code);
+                } else if app.open_menu.is_some() {
+                    app.handle_open_menu_key(key.code);
+                } else if app.filter_input.is_some() {
+                    app.handle_filter_input_key(key.code);
+                } else if app.input_mode {
                     match key.code {
-                        KeyCode::Enter => {
-                            if !app.query.is_empty() {
-                                app.input_mode = false;
-                                app.start_checking();
-                            }
+                        KeyCode::Enter if !app.query.is_empty() => {
+                            app.input_mode = false;
+                            app.start_checking();
                         }
                         KeyCode::Char(c) => {
                             app.query.push(c);
@@ -645,42 +2875,76 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
                         _ => {}
                     }
                 } else {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => {
+                    match app.keymap.action(key.code) {
+                        Some(KeyAction::Quit) => {
                             app.quit = true;
                         }
-                        KeyCode::Down | KeyCode::Char('j') => {
+                        Some(KeyAction::Down) => {
                             app.scroll_down();
                         }
-                        KeyCode::Up | KeyCode::Char('k') => {
+                        Some(KeyAction::Up) => {
                             app.scroll_up();
                         }
-                        KeyCode::PageDown => {
+                        Some(KeyAction::PageDown) => {
                             app.scroll_page_down();
                         }
-                        KeyCode::PageUp => {
+                        Some(KeyAction::PageUp) => {
                             app.scroll_page_up();
                         }
-                        KeyCode::Home | KeyCode::Char('g') => {
+                        Some(KeyAction::Top) => {
                             app.scroll_to_top();
                         }
-                        KeyCode::End | KeyCode::Char('G') => {
+                        Some(KeyAction::Bottom) => {
                             app.scroll_to_bottom();
                         }
-                        KeyCode::Char('i') => {
+                        Some(KeyAction::Edit) => {
                             app.input_mode = true;
                         }
-                        KeyCode::Enter | KeyCode::Char('y') => {
+                        Some(KeyAction::Copy) => {
                             app.copy_selected_to_clipboard();
                         }
-                        KeyCode::Char('o') => {
-                            app.open_selected_in_browser();
+                        Some(KeyAction::Open) => {
+                            app.show_open_menu();
                         }
-                        KeyCode::Tab | KeyCode::Char('f') => {
+                        Some(KeyAction::Filter) => {
                             app.filter_mode = app.filter_mode.next();
                             app.list_state.select(Some(0));
                         }
-                        _ => {}
+                        Some(KeyAction::ToggleRegistrable) => {
+                            app.registrable_only = !app.registrable_only;
+                            app.list_state.select(Some(0));
+                        }
+                        Some(KeyAction::ToggleMode) => {
+                            app.set_mode(app.plan.mode.next());
+                            app.toast_message = Some((
+                                format!("Mode: {}", app.plan.mode.label()),
+                                std::time::Instant::now(),
+                            ));
+                            app.start_checking();
+                        }
+                        Some(KeyAction::Screenshot) => {
+                            app.screenshot_pending = true;
+                        }
+                        Some(KeyAction::FilterExpr) => {
+                            let current = app.filter_expr.as_ref().map(|e| e.source().to_string());
+                            app.filter_input = Some(current.unwrap_or_default());
+                        }
+                        Some(KeyAction::Settings) => {
+                            app.settings = Some(SettingsScreen::default());
+                        }
+                        Some(KeyAction::Favorite) => {
+                            app.toggle_favorite();
+                        }
+                        Some(KeyAction::Mark) => {
+                            app.toggle_mark();
+                        }
+                        None => {
+                            if let KeyCode::Char(c @ '1'..='9') = key.code {
+                                if app.alternatives_shown() {
+                                    app.pick_alternative(c as usize - '1' as usize);
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -690,18 +2954,37 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
     Ok(())
 }
 
+#[cfg(feature = "tui")]
+/// Below this size the bordered layout does not fit; render `ui_compact`.
+const MIN_FULL_WIDTH: u16 = 50;
+#[cfg(feature = "tui")]
+const MIN_FULL_HEIGHT: u16 = 14;
+
+#[cfg(feature = "tui")]
 fn ui(f: &mut Frame, app: &mut App) {
+    let area = f.area();
+    if area.width < MIN_FULL_WIDTH || area.height < MIN_FULL_HEIGHT {
+        ui_compact(f, app);
+        return;
+    }
+
+    let theme = app.theme;
     let has_specific = app.specific_domain.is_some();
+    let alternatives = match app.alternatives_shown() {
+        true => alternative_lines(app, area.width.saturating_sub(2) as usize),
+        false => Vec::new(),
+    };
     let has_toast = app.toast_message.is_some();
     
     let mut constraints = vec![Constraint::Length(3)];
     
     if has_specific {
-        constraints.push(Constraint::Length(3));
+        constraints.push(Constraint::Length(3 + alternatives.len() as u16));
     }
     
     constraints.push(Constraint::Length(1));
     constraints.push(Constraint::Min(1));
+    constraints.push(Constraint::Length(1));
     
     if has_toast {
         constraints.push(Constraint::Length(1));
@@ -716,19 +2999,49 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     let mut chunk_idx = 0;
     
-    let input_text = if app.input_mode {
+    let input_text = if let Some(filter) = &app.filter_input {
+        format!("Filter: :{}_  (status= len<= tldlen< price< tld in <group>; Enter to apply)", filter)
+    } else if app.input_mode {
         format!("Query: {}_", app.query)
     } else {
         format!("Query: {} (press 'i' to edit)", app.query)
     };
 
+    let bootstrap_state = app.prober.bootstrap_state();
+    let bootstrap_color = match bootstrap_state {
+        BootstrapState::NotStarted => Color::DarkGray,
+        BootstrapState::InProgress => Color::Yellow,
+        BootstrapState::Ready(_) => Color::Green,
+        BootstrapState::Bundled(_) => Color::Yellow,
+        BootstrapState::Failed(_) => Color::Red,
+    };
+    let mut title_spans = vec![
+        Span::raw("Domain Search"),
+        Span::raw(" ─ RDAP: "),
+        Span::styled(bootstrap_state.to_string(), Style::default().fg(bootstrap_color)),
+    ];
+    if !app.prober.whois_available() {
+        title_spans.push(Span::styled(
+            " ─ WHOIS disabled (port 43 blocked)",
+            Style::default().fg(Color::Red),
+        ));
+    }
+    let unreliable = app.prober.unreliable_endpoints().len();
+    if unreliable > 0 {
+        title_spans.push(Span::styled(
+            format!(" ─ {} unreliable endpoint(s)", unreliable),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    let title = Line::from(title_spans);
+
     let input = Paragraph::new(input_text)
-        .style(if app.input_mode {
+        .style(if app.input_mode || app.filter_input.is_some() {
             Style::default().fg(Color::Yellow)
         } else {
             Style::default()
         })
-        .block(Block::default().borders(Borders::ALL).title("Domain Search"));
+        .block(Block::default().borders(Borders::ALL).title(title));
     f.render_widget(input, chunks[chunk_idx]);
     chunk_idx += 1;
 
@@ -737,20 +3050,40 @@ fn ui(f: &mut Frame, app: &mut App) {
             let status = app.specific_domain_status.lock().unwrap().clone();
             
             let (symbol, color, status_text) = match &status {
-                Some(DomainStatus::Available) => ("✓", Color::Green, "AVAILABLE".to_string()),
-                Some(DomainStatus::Taken) => ("✗", Color::Red, "TAKEN".to_string()),
-                Some(DomainStatus::Checking) => (app.spinner_frame(), Color::Yellow, "Checking...".to_string()),
-                Some(DomainStatus::Error(e)) => ("!", Color::Magenta, e.clone()),
-                Some(DomainStatus::Pending) | None => (app.spinner_frame(), Color::Yellow, "Checking...".to_string()),
+                Some(DomainStatus::Available) => ("✓", theme.available, "AVAILABLE".to_string()),
+                Some(DomainStatus::Taken) => ("✗", theme.taken, "TAKEN".to_string()),
+                Some(DomainStatus::Reserved) => ("⊘", theme.taken, "RESERVED BY REGISTRY".to_string()),
+                Some(DomainStatus::Premium) => ("$", theme.checking, "PREMIUM PRICE".to_string()),
+                Some(DomainStatus::Checking) => (app.spinner_frame(), theme.checking, "Checking...".to_string()),
+                Some(DomainStatus::Error(e)) => ("!", theme.error, e.clone()),
+                Some(DomainStatus::Maintenance) => ("~", theme.checking, "REGISTRY MAINTENANCE".to_string()),
+                Some(DomainStatus::Invalid) => ("-", Color::DarkGray, "INVALID FOR THIS TLD".to_string()),
+                Some(DomainStatus::Pending) | None => (app.spinner_frame(), theme.checking, "Checking...".to_string()),
             };
             
-            let line = Line::from(vec![
+            let mut spans = vec![
                 Span::styled(format!("  {} ", symbol), Style::default().fg(color).add_modifier(Modifier::BOLD)),
                 Span::styled(domain.clone(), Style::default().fg(color).add_modifier(Modifier::BOLD)),
                 Span::styled(format!("  {}", status_text), Style::default().fg(color)),
-            ]);
+            ];
+            let tld = domain.rsplit('.').next().unwrap_or_default();
+            if let Some(restriction) = restriction_for(tld) {
+                spans.push(Span::styled(
+                    format!("  ⚑ {} ({})", restriction.requirement, restriction.note),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if let Some((phase, next)) = app.prober.launch_phase(tld) {
+                let until = next.map(|date| format!(" until {}", date)).unwrap_or_default();
+                spans.push(Span::styled(
+                    format!("  ◷ {}{}, not open to everyone yet", phase, until),
+                    Style::default().fg(theme.checking),
+                ));
+            }
+            let mut lines = vec![Line::from(spans)];
+            lines.extend(alternatives);
             
-            let specific_widget = Paragraph::new(line)
+            let specific_widget = Paragraph::new(lines)
                 .block(Block::default().borders(Borders::ALL).title("Specific Domain"));
             f.render_widget(specific_widget, chunks[chunk_idx]);
         }
@@ -758,14 +3091,14 @@ fn ui(f: &mut Frame, app: &mut App) {
     }
 
     let (done, total) = app.progress();
-    let pct = if total > 0 { (done * 100) / total } else { 0 };
+    let pct = (done * 100).checked_div(total).unwrap_or(0);
     let bar_width = (f.area().width as usize).saturating_sub(20);
-    let filled = (bar_width * done) / total.max(1);
-    let bar: String = "█".repeat(filled) + &"░".repeat(bar_width - filled);
+    let filled = ((bar_width * done) / total.max(1)).min(bar_width);
+    let bar: String = "█".repeat(filled) + &"░".repeat(bar_width.saturating_sub(filled));
     
     let progress_line = Line::from(vec![
-        Span::styled(format!(" {} ", app.spinner_frame()), Style::default().fg(Color::Cyan)),
-        Span::styled(bar, Style::default().fg(Color::Green)),
+        Span::styled(format!(" {} ", app.spinner_frame()), Style::default().fg(theme.accent)),
+        Span::styled(bar, Style::default().fg(theme.available)),
         Span::styled(format!(" {:>3}% ({}/{})", pct, done, total), Style::default().fg(Color::DarkGray)),
     ]);
     f.render_widget(Paragraph::new(progress_line), chunks[chunk_idx]);
@@ -773,6 +3106,8 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     let results_chunk = chunks[chunk_idx];
     chunk_idx += 1;
+    let details_chunk = chunks[chunk_idx];
+    chunk_idx += 1;
     
     let toast_chunk = if has_toast {
         let c = chunks[chunk_idx];
@@ -786,16 +3121,26 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     let results = app.get_filtered_results();
     let spinner = app.spinner_frame();
+    let price_table = Arc::clone(&app.prices);
+    let prices = price_table.lock().unwrap();
+    let spellings = app.ascii_status.lock().unwrap().clone();
+    let look = |status: &DomainStatus| -> (&str, Color, String) {
+        match status {
+            DomainStatus::Available => ("✓", theme.available, "Available".to_string()),
+            DomainStatus::Taken => ("✗", theme.taken, "Taken".to_string()),
+            DomainStatus::Reserved => ("⊘", theme.taken, "Reserved".to_string()),
+            DomainStatus::Premium => ("$", theme.checking, "Premium".to_string()),
+            DomainStatus::Checking => (spinner, theme.checking, "Checking...".to_string()),
+            DomainStatus::Pending => ("○", Color::DarkGray, "Pending".to_string()),
+            DomainStatus::Error(e) => ("!", theme.error, e.clone()),
+            DomainStatus::Maintenance => ("~", theme.checking, "Registry maintenance".to_string()),
+            DomainStatus::Invalid => ("-", Color::DarkGray, "Invalid for this TLD".to_string()),
+        }
+    };
     let items: Vec<ListItem> = results
         .iter()
         .map(|(tld, status)| {
-            let (symbol, color, text): (&str, Color, String) = match status {
-                DomainStatus::Available => ("✓", Color::Green, "Available".to_string()),
-                DomainStatus::Taken => ("✗", Color::Red, "Taken".to_string()),
-                DomainStatus::Checking => (spinner, Color::Yellow, "Checking...".to_string()),
-                DomainStatus::Pending => ("○", Color::DarkGray, "Pending".to_string()),
-                DomainStatus::Error(e) => ("!", Color::Magenta, e.clone()),
-            };
+            let (symbol, color, text) = look(status);
 
             let domain = if !app.query.is_empty() {
                 format!("{}.{}", app.query, tld)
@@ -803,15 +3148,40 @@ fn ui(f: &mut Frame, app: &mut App) {
                 format!("*.{}", tld)
             };
 
-            let line = Line::from(vec![
+            let price = prices.display(tld).unwrap_or_default();
+
+            let mut spans = vec![
                 Span::styled(format!("{} ", symbol), Style::default().fg(color).add_modifier(Modifier::BOLD)),
-                Span::styled(format!("{:<30}", domain), Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{:<30}", domain), Style::default().fg(theme.accent)),
+                Span::styled(format!("{:>9}  ", price), Style::default().fg(Color::DarkGray)),
                 Span::styled(text, Style::default().fg(color)),
-            ]);
+            ];
+            if let Some((ascii, status)) = spellings.get(tld) {
+                let (symbol, color, _) = look(status);
+                spans.push(Span::styled(format!("  ascii: {} {}", ascii, symbol), Style::default().fg(color)));
+            }
+            if let Some(restriction) = restriction_for(tld) {
+                spans.push(Span::styled(
+                    format!("  ⚑ {}", restriction.requirement),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if let Some((phase, _)) = app.prober.launch_phase(tld) {
+                spans.push(Span::styled(format!("  ◷ {}", phase), Style::default().fg(theme.checking)));
+            }
+            if app.tld_config.favorites.contains(tld) {
+                spans.push(Span::styled("  ★", Style::default().fg(theme.accent)));
+            }
+            if app.marked.contains(&domain) {
+                spans.push(Span::styled("  ●", Style::default().fg(theme.accent)));
+            }
+            let line = Line::from(spans);
 
             ListItem::new(line)
         })
         .collect();
+    // The open menu filters results again, which takes the lock
+    drop(prices);
 
     let all_results = app.get_sorted_results();
     let available_count = all_results.iter().filter(|(_, s)| matches!(s, DomainStatus::Available)).count();
@@ -824,8 +3194,15 @@ fn ui(f: &mut Frame, app: &mut App) {
     };
 
     let title = format!(
-        "Results {} - Tab/f to filter",
-        filter_indicator
+        "Results {}{}{}{}{} - Tab/f to filter",
+        filter_indicator,
+        if app.marked.is_empty() { String::new() } else { format!(" [{} marked]", app.marked.len()) },
+        app.filter_expr.as_ref().map(|e| format!(" [:{}]", e.source())).unwrap_or_default(),
+        if app.registrable_only { " [registrable only]" } else { "" },
+        match app.plan.mode {
+            SweepMode::Normal => String::new(),
+            mode => format!(" [{}]", mode.label()),
+        }
     );
 
     let results_list = List::new(items)
@@ -840,6 +3217,29 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     f.render_stateful_widget(results_list, results_chunk, &mut app.list_state);
 
+    let selected = app.list_state.selected().and_then(|i| results.get(i));
+    if let Some((tld, status)) = selected {
+        let explanation = app.explanations.lock().unwrap().get(tld).cloned();
+        // Taken domains lead with their lock posture, for portfolio audits
+        let locks = (*status == DomainStatus::Taken)
+            .then(|| app.locks(&format!("{}.{}", app.query, tld).to_lowercase()))
+            .flatten();
+        let mut spans = Vec::new();
+        if let Some(locks) = locks {
+            let (text, color) = match (locks.registry, locks.transfer) {
+                (true, _) => ("🔒 registry lock".to_string(), theme.available),
+                (false, true) => (format!("🔒 {}", locks.statuses.join(", ")), theme.available),
+                (false, false) => ("⚠ no transfer lock".to_string(), theme.error),
+            };
+            spans.push(Span::styled(format!(" {} ", text), Style::default().fg(color).add_modifier(Modifier::BOLD)));
+        }
+        if let Some(explanation) = explanation {
+            spans.push(Span::styled(" why: ", Style::default().fg(theme.accent)));
+            spans.push(Span::styled(explanation, Style::default().fg(Color::DarkGray)));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), details_chunk);
+    }
+
     if let Some(chunk) = toast_chunk {
         if let Some((msg, _)) = &app.toast_message {
             let toast = Paragraph::new(Line::from(vec![
@@ -851,13 +3251,242 @@ fn ui(f: &mut Frame, app: &mut App) {
     }
 
     let help_text = if app.input_mode {
-        "Enter: Search | Esc: Quit"
+        "Enter: Search | Esc: Quit".to_string()
     } else {
-        "↑↓/jk: Scroll | Tab/f: Filter | Enter/y: Copy | o: Open | i: Edit | q: Quit"
+        let key = |action| app.keymap.key_for(action).map(String::from).unwrap_or_default();
+        format!(
+            "↑↓/{}{}: Scroll | Tab/{}: Filter | Enter/{}: Copy | {}: Open | {}: Mode | {}: Edit | {}: Settings | {}: Quit",
+            key(KeyAction::Down),
+            key(KeyAction::Up),
+            key(KeyAction::Filter),
+            key(KeyAction::Copy),
+            key(KeyAction::Open),
+            key(KeyAction::ToggleMode),
+            key(KeyAction::Edit),
+            key(KeyAction::Settings),
+            key(KeyAction::Quit),
+        )
     };
 
     let help = Paragraph::new(help_text)
         .style(Style::default().fg(Color::DarkGray))
         .block(Block::default().borders(Borders::ALL).title("Help"));
     f.render_widget(help, help_chunk);
+
+    if let Some(selected) = app.open_menu {
+        render_open_menu(f, app, selected);
+    }
+    if let Some(screen) = &app.settings {
+        render_settings(f, app, screen);
+    }
+}
+
+#[cfg(feature = "tui")]
+/// The specific domain's alternatives as `1 ✓ name.io` entries, packed
+/// into lines of `width`.
+fn alternative_lines(app: &App, width: usize) -> Vec<Line<'static>> {
+    let theme = app.theme;
+    let mut lines = Vec::new();
+    let mut spans = vec![Span::styled("  try: ", Style::default().fg(Color::DarkGray))];
+    let mut used = 7;
+    for (i, (domain, status)) in app.alternative_rows().into_iter().enumerate() {
+        let (symbol, color) = match status {
+            DomainStatus::Available => ("✓", theme.available),
+            DomainStatus::Taken => ("✗", theme.taken),
+            DomainStatus::Reserved => ("⊘", theme.taken),
+            DomainStatus::Premium => ("$", theme.checking),
+            DomainStatus::Error(_) => ("!", theme.error),
+            DomainStatus::Maintenance => ("~", theme.checking),
+            DomainStatus::Invalid => ("-", Color::DarkGray),
+            DomainStatus::Checking | DomainStatus::Pending => (app.spinner_frame(), theme.checking),
+        };
+        let entry = format!("{} {}   ", symbol, domain);
+        let entry_width = entry.chars().count() + 2;
+        if used + entry_width > width && spans.len() > 1 {
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            spans.push(Span::raw("       "));
+            used = 7;
+        }
+        spans.push(Span::styled(format!("{} ", i + 1), Style::default().fg(theme.accent)));
+        spans.push(Span::styled(entry, Style::default().fg(color)));
+        used += entry_width;
+    }
+    lines.push(Line::from(spans));
+    lines
+}
+
+#[cfg(feature = "tui")]
+fn render_settings(f: &mut Frame, app: &App, screen: &SettingsScreen) {
+    let items: Vec<ListItem> = Setting::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, setting)| {
+            let (table, key) = setting.key();
+            let value = match &screen.editing {
+                Some(input) if i == screen.selected => format!("{}_", input),
+                _ => setting.value(&app.base_probe_config, &app.tld_config),
+            };
+            let marker = if app.unsaved_settings.contains(setting) { "*" } else { " " };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}{:<36}", marker, format!("{}.{}", table, key)), Style::default().fg(app.theme.accent)),
+                Span::styled(value, Style::default().fg(if screen.editing.is_some() && i == screen.selected {
+                    Color::Yellow
+                } else {
+                    Color::White
+                })),
+            ]))
+        })
+        .collect();
+
+    let area = f.area();
+    let width = 72.min(area.width);
+    let height = (Setting::ALL.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Settings ─ Enter: change | w: save to config | Esc: close"),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+    let mut state = ListState::default();
+    state.select(Some(screen.selected));
+
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut state);
+}
+
+#[cfg(feature = "tui")]
+fn render_open_menu(f: &mut Frame, app: &App, selected: usize) {
+    let entries = app.open_menu_entries();
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|t| match t {
+            LinkTarget::Registrar if !app.marked.is_empty() => {
+                ListItem::new(format!("{}  {} ({})", t.key(), t.label(), app.marked.len()))
+            }
+            _ => ListItem::new(format!("{}  {}", t.key(), t.label())),
+        })
+        .collect();
+
+    let area = f.area();
+    let width = 32.min(area.width);
+    let height = (entries.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let title = match app.marked.len() {
+        0 => app.get_selected_domain().unwrap_or_default(),
+        n => format!("{} marked", n),
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+    let mut state = ListState::default();
+    state.select(Some(selected));
+
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut state);
+}
+
+#[cfg(feature = "tui")]
+/// Single-column fallback for tiny terminals: query line, bare result list
+/// and a one-line help, with no borders or progress bar.
+fn ui_compact(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let area = f.area();
+    let width = area.width as usize;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    let (done, total) = app.progress();
+    let header = if let Some(filter) = &app.filter_input {
+        format!(":{}_", filter)
+    } else if app.input_mode {
+        format!("> {}_", app.query)
+    } else {
+        format!("{} {} {}/{}", app.spinner_frame(), app.query, done, total)
+    };
+    f.render_widget(
+        Paragraph::new(truncate(&header, width)).style(Style::default().fg(theme.accent)),
+        chunks[0],
+    );
+
+    let spinner = app.spinner_frame();
+    let items: Vec<ListItem> = app
+        .get_filtered_results()
+        .iter()
+        .map(|(tld, status)| {
+            let (symbol, color) = match status {
+                DomainStatus::Available => ("✓", theme.available),
+                DomainStatus::Taken => ("✗", theme.taken),
+                DomainStatus::Reserved => ("⊘", theme.taken),
+                DomainStatus::Premium => ("$", theme.checking),
+                DomainStatus::Checking => (spinner, theme.checking),
+                DomainStatus::Pending => ("○", Color::DarkGray),
+                DomainStatus::Error(_) => ("!", theme.error),
+                DomainStatus::Maintenance => ("~", theme.checking),
+                DomainStatus::Invalid => ("-", Color::DarkGray),
+            };
+            let domain = format!("{}.{}", app.query, tld);
+            let pin = if app.tld_config.favorites.contains(tld) { "★" } else { " " };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}{}", symbol, pin), Style::default().fg(color)),
+                Span::raw(truncate(&domain, width.saturating_sub(4))),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(Color::DarkGray))
+        .highlight_symbol(">");
+    f.render_stateful_widget(list, chunks[1], &mut app.list_state);
+
+    let help = if app.input_mode {
+        "Enter:go Esc:quit".to_string()
+    } else {
+        let key = |action| app.keymap.key_for(action).map(String::from).unwrap_or_default();
+        format!(
+            "{}{}:move {}:filter {}:copy {}:quit",
+            key(KeyAction::Down),
+            key(KeyAction::Up),
+            key(KeyAction::Filter),
+            key(KeyAction::Copy),
+            key(KeyAction::Quit),
+        )
+    };
+    f.render_widget(
+        Paragraph::new(truncate(&help, width)).style(Style::default().fg(Color::DarkGray)),
+        chunks[2],
+    );
+
+    if let Some(selected) = app.open_menu {
+        render_open_menu(f, app, selected);
+    }
+    if let Some(screen) = &app.settings {
+        render_settings(f, app, screen);
+    }
+}
+
+#[cfg(feature = "tui")]
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    out.push('…');
+    out
 }
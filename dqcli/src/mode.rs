@@ -0,0 +1,84 @@
+use librdap_storm::{ProbeConfig, TldRanker};
+use std::time::Duration;
+
+/// Probe presets for the two common workflows: a fast first look and a
+/// careful final check before buying.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SweepMode {
+    #[default]
+    Normal,
+    /// Priority TLDs only, DNS before RDAP, 2s timeouts, no retries or WHOIS
+    Quick,
+    /// Every TLD, retries, and a confirmation pass on available results
+    Thorough,
+}
+
+const QUICK_TIMEOUT: Duration = Duration::from_secs(2);
+const THOROUGH_MIN_TIMEOUT: Duration = Duration::from_secs(10);
+const THOROUGH_RETRIES: u32 = 2;
+
+impl SweepMode {
+    #[cfg(feature = "tui")]
+    pub fn next(self) -> Self {
+        match self {
+            SweepMode::Normal => SweepMode::Quick,
+            SweepMode::Quick => SweepMode::Thorough,
+            SweepMode::Thorough => SweepMode::Normal,
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn label(self) -> &'static str {
+        match self {
+            SweepMode::Normal => "normal",
+            SweepMode::Quick => "quick",
+            SweepMode::Thorough => "thorough",
+        }
+    }
+
+    pub fn apply(self, mut config: ProbeConfig) -> ProbeConfig {
+        match self {
+            SweepMode::Normal => {}
+            SweepMode::Quick => {
+                config.timeout = QUICK_TIMEOUT;
+                config.dns_first = true;
+                config.retries = 0;
+                config.rate_limit_requeues = 0;
+                config.whois_fallback = false;
+                config.confirm_available = false;
+            }
+            SweepMode::Thorough => {
+                config.timeout = config.timeout.max(THOROUGH_MIN_TIMEOUT);
+                config.retries = config.retries.max(THOROUGH_RETRIES);
+                config.confirm_available = true;
+                config.canary_check = true;
+            }
+        }
+        config
+    }
+}
+
+/// The chosen mode plus the TLDs quick mode must never drop: `[tlds]
+/// always`, the TLD typed in the query, or an explicit `--tlds` list.
+#[derive(Debug, Clone, Default)]
+pub struct SweepPlan {
+    pub mode: SweepMode,
+    pub pinned: Vec<String>,
+}
+
+impl SweepPlan {
+    /// Quick mode keeps only ranked priority TLDs and pinned ones; other
+    /// modes keep all.
+    pub fn select_tlds(&self, tlds: &[String]) -> Vec<String> {
+        match self.mode {
+            SweepMode::Quick => {
+                let ranker = TldRanker::default();
+                tlds.iter()
+                    .filter(|tld| ranker.weight(tld).is_some() || self.pinned.contains(tld))
+                    .cloned()
+                    .collect()
+            }
+            SweepMode::Normal | SweepMode::Thorough => tlds.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use clap::ValueEnum;
+use flate2::{write::GzEncoder, Compression as GzLevel};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+};
+use tokio::sync::mpsc;
+
+/// NDJSON records queued for the output when `--buffer-limit` isn't given.
+pub const DEFAULT_BUFFER_LIMIT: usize = 1024;
+/// zstd's default; fast enough to keep up with a sweep.
+const ZSTD_LEVEL: i32 = 3;
+/// Files `--output-dir` keeps open at once; past this one is closed and
+/// later appended to.
+const MAX_OPEN_FILES: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// From a `.gz` or `.zst` file extension.
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "gz" => Some(Compression::Gzip),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Ndjson,
+    Csv,
+}
+
+impl Format {
+    /// CSV for `.csv`, also under a compression extension; NDJSON otherwise.
+    fn from_path(path: &Path) -> Self {
+        let path = match Compression::from_path(path) {
+            Some(_) => path.with_extension(""),
+            None => path.to_path_buf(),
+        };
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Format::Csv,
+            _ => Format::Ndjson,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Ndjson => "ndjson",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+/// `--output`, `--output-dir`, `--split-every`, `--format`, `--compress`
+/// and `--buffer-limit`.
+#[derive(Debug, Clone, Default)]
+pub struct OutputOptions {
+    pub path: Option<PathBuf>,
+    /// One file per TLD here, or per `split_every` results
+    pub dir: Option<PathBuf>,
+    pub split_every: Option<usize>,
+    pub format: Option<Format>,
+    pub compress: Option<Compression>,
+    pub buffer_limit: usize,
+}
+
+impl OutputOptions {
+    /// `--format`, else what the `--output` extension says, else NDJSON.
+    pub fn format(&self) -> Format {
+        self.format
+            .or_else(|| self.path.as_deref().map(Format::from_path))
+            .unwrap_or(Format::Ndjson)
+    }
+}
+
+/// Where sweep results go: stdout or a file, optionally compressed.
+pub enum Sink {
+    Plain(Box<dyn Write + Send>),
+    Gzip(GzEncoder<Box<dyn Write + Send>>),
+    Zstd(zstd::Encoder<'static, Box<dyn Write + Send>>),
+}
+
+impl Sink {
+    fn wrap(out: Box<dyn Write + Send>, compress: Option<Compression>) -> io::Result<Self> {
+        Ok(match compress {
+            Some(Compression::Gzip) => Sink::Gzip(GzEncoder::new(out, GzLevel::default())),
+            Some(Compression::Zstd) => Sink::Zstd(zstd::Encoder::new(out, ZSTD_LEVEL)?),
+            None => Sink::Plain(out),
+        })
+    }
+
+    /// Opens `path` (stdout when `None`). Compression follows `compress`,
+    /// else the `.gz`/`.zst` extension.
+    fn open(path: Option<&Path>, compress: Option<Compression>) -> io::Result<Self> {
+        let out: Box<dyn Write + Send> = match path {
+            Some(path) => Box::new(File::create(path)?),
+            None => Box::new(io::stdout()),
+        };
+        Self::wrap(out, compress.or_else(|| path.and_then(Compression::from_path)))
+    }
+
+    /// Flushes and writes the compression trailer.
+    fn finish(self) -> io::Result<()> {
+        let mut out = match self {
+            Sink::Plain(out) => out,
+            Sink::Gzip(encoder) => encoder.finish()?,
+            Sink::Zstd(encoder) => encoder.finish()?,
+        };
+        out.flush()
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Plain(out) => out.write(buf),
+            Sink::Gzip(out) => out.write(buf),
+            Sink::Zstd(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Plain(out) => out.flush(),
+            Sink::Gzip(out) => out.flush(),
+            Sink::Zstd(out) => out.flush(),
+        }
+    }
+}
+
+fn finish(out: BufWriter<Sink>) -> io::Result<()> {
+    out.into_inner().map_err(|e| e.into_error())?.finish()
+}
+
+/// The files of `--output-dir`, opened as results for them arrive.
+struct SplitFiles {
+    dir: PathBuf,
+    every: Option<usize>,
+    /// e.g. `ndjson.gz`
+    extension: String,
+    compress: Option<Compression>,
+    header: Option<String>,
+    open: HashMap<String, BufWriter<Sink>>,
+    /// Files this run has started, so a reopened one is appended to
+    created: HashSet<String>,
+    written: usize,
+}
+
+impl SplitFiles {
+    fn write(&mut self, key: &str, line: &str) -> io::Result<()> {
+        let name = match self.every {
+            Some(every) => format!("part-{:05}", self.written / every),
+            None => key.replace(['/', '\\'], "_"),
+        };
+        self.written += 1;
+
+        if !self.open.contains_key(&name) {
+            // Parts are filled one after the other; per-TLD files stay open
+            // up to a cap, then one is closed to make room
+            let full = self.every.is_some() || self.open.len() >= MAX_OPEN_FILES;
+            if let Some(close) = self.open.keys().next().cloned().filter(|_| full) {
+                finish(self.open.remove(&close).unwrap())?;
+            }
+            let path = self.dir.join(format!("{}.{}", name, self.extension));
+            let fresh = self.created.insert(name.clone());
+            let file = if fresh {
+                File::create(path)?
+            } else {
+                // gzip members and zstd frames may follow one another
+                OpenOptions::new().append(true).open(path)?
+            };
+            let mut out = BufWriter::new(Sink::wrap(Box::new(file), self.compress)?);
+            if let Some(header) = self.header.as_ref().filter(|_| fresh) {
+                writeln!(out, "{}", header)?;
+            }
+            self.open.insert(name.clone(), out);
+        }
+        writeln!(self.open.get_mut(&name).unwrap(), "{}", line)
+    }
+
+    fn finish(self) -> io::Result<()> {
+        self.open.into_values().try_for_each(finish)
+    }
+}
+
+/// Quotes a CSV field when it holds a comma, quote or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+enum Target {
+    Single(BufWriter<Sink>),
+    Split(SplitFiles),
+}
+
+/// Writes lines on a thread of its own. At most `limit` lines wait in the
+/// queue; once it is full `send` waits, so a slow reader of the output
+/// holds up the sweep instead of memory growing with it.
+pub struct LineWriter {
+    tx: mpsc::Sender<(String, String)>,
+    thread: JoinHandle<io::Result<()>>,
+}
+
+impl LineWriter {
+    /// Opens the output `options` name. `header` starts every file.
+    pub fn spawn(options: &OutputOptions, header: Option<String>) -> io::Result<Self> {
+        let mut target = match &options.dir {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                let mut extension = options.format().extension().to_string();
+                if let Some(compress) = options.compress {
+                    extension = format!("{}.{}", extension, compress.extension());
+                }
+                Target::Split(SplitFiles {
+                    dir: dir.clone(),
+                    every: options.split_every.map(|n| n.max(1)),
+                    extension,
+                    compress: options.compress,
+                    header,
+                    open: HashMap::new(),
+                    created: HashSet::new(),
+                    written: 0,
+                })
+            }
+            None => {
+                let mut out = BufWriter::new(Sink::open(options.path.as_deref(), options.compress)?);
+                if let Some(header) = header {
+                    writeln!(out, "{}", header)?;
+                }
+                Target::Single(out)
+            }
+        };
+
+        let (tx, mut rx) = mpsc::channel::<(String, String)>(options.buffer_limit.max(1));
+        // Compressors lose ratio with every flush, so only plain output is
+        // kept current for a reader following along
+        let live = matches!(&target, Target::Single(out) if matches!(out.get_ref(), Sink::Plain(_)));
+        let thread = std::thread::spawn(move || {
+            while let Some((key, line)) = rx.blocking_recv() {
+                match &mut target {
+                    Target::Single(out) => {
+                        writeln!(out, "{}", line)?;
+                        // Flush whenever the sweep is ahead of us, so lines
+                        // still arrive promptly when results trickle in
+                        if live && rx.is_empty() {
+                            out.flush()?;
+                        }
+                    }
+                    Target::Split(files) => files.write(&key, &line)?,
+                }
+            }
+            match target {
+                Target::Single(out) => finish(out),
+                Target::Split(files) => files.finish(),
+            }
+        });
+        Ok(Self { tx, thread })
+    }
+
+    /// Queues a line for the file of `key` (the TLD) under `--output-dir`,
+    /// waiting while the queue is full. Returns false once the writer has
+    /// stopped; `finish` tells why.
+    pub async fn send(&self, key: &str, line: String) -> bool {
+        self.tx.send((key.to_string(), line)).await.is_ok()
+    }
+
+    /// Writes out what is queued and returns the first write error.
+    pub fn finish(self) -> io::Result<()> {
+        drop(self.tx);
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("output thread panicked")))
+    }
+}
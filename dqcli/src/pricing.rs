@@ -0,0 +1,260 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::JoinHandle;
+
+use crate::config::Config;
+
+const PRICING_URL: &str = "https://api.porkbun.com/api/json/v3/pricing/get";
+const RATES_URL: &str = "https://open.er-api.com/v6/latest/USD";
+const DEFAULT_TTL_HOURS: u64 = 24;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Registration prices in USD keyed by TLD, plus the rate to convert them
+/// into the configured display currency.
+#[derive(Debug, Clone)]
+pub struct PriceTable {
+    prices: HashMap<String, f64>,
+    currency: String,
+    rate: f64,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        Self { prices: HashMap::new(), currency: "USD".to_string(), rate: 1.0 }
+    }
+}
+
+impl PriceTable {
+    pub fn get(&self, tld: &str) -> Option<f64> {
+        self.prices.get(&tld.to_lowercase()).copied()
+    }
+
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// Price in the display currency.
+    pub fn converted(&self, tld: &str) -> Option<f64> {
+        self.get(tld).map(|usd| usd * self.rate)
+    }
+
+    pub fn display(&self, tld: &str) -> Option<String> {
+        let price = self.converted(tld)?;
+        Some(match self.currency.as_str() {
+            "USD" => format!("${:.2}", price),
+            "EUR" => format!("€{:.2}", price),
+            "GBP" => format!("£{:.2}", price),
+            other => format!("{} {:.2}", other, price),
+        })
+    }
+}
+
+/// Cached map of values (prices or exchange rates) with its fetch time.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cache {
+    /// Unix seconds
+    fetched_at: u64,
+    #[serde(alias = "prices")]
+    values: BTreeMap<String, f64>,
+}
+
+impl Cache {
+    fn is_fresh(&self, ttl_secs: u64) -> bool {
+        now_secs().saturating_sub(self.fetched_at) < ttl_secs
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RatesResponse {
+    result: String,
+    #[serde(default)]
+    rates: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PricingResponse {
+    status: String,
+    #[serde(default)]
+    pricing: BTreeMap<String, PricingEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PricingEntry {
+    registration: String,
+}
+
+fn cache_path(name: &str) -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("dq").join(name))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn read_cache(name: &str) -> Option<Cache> {
+    let content = std::fs::read_to_string(cache_path(name)?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_cache(name: &str, cache: &Cache) {
+    if let Some(path) = cache_path(name) {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string(cache) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+async fn fetch_prices(client: &Client) -> Result<BTreeMap<String, f64>, reqwest::Error> {
+    let response: PricingResponse = client
+        .post(PRICING_URL)
+        .timeout(FETCH_TIMEOUT)
+        .json(&serde_json::json!({}))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if response.status != "SUCCESS" {
+        return Ok(BTreeMap::new());
+    }
+
+    Ok(response
+        .pricing
+        .into_iter()
+        .filter_map(|(tld, entry)| {
+            entry.registration.parse().ok().map(|price| (tld.to_lowercase(), price))
+        })
+        .collect())
+}
+
+async fn fetch_rates(client: &Client) -> Result<BTreeMap<String, f64>, reqwest::Error> {
+    let response: RatesResponse = client
+        .get(RATES_URL)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if response.result != "success" {
+        return Ok(BTreeMap::new());
+    }
+    Ok(response.rates)
+}
+
+/// Returns the cached values when fresh, otherwise fetches and caches new
+/// ones, falling back to a stale cache if the fetch fails.
+async fn cached_or_fetch<F, Fut>(name: &str, ttl_secs: u64, fetch: F) -> BTreeMap<String, f64>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<BTreeMap<String, f64>, reqwest::Error>>,
+{
+    let cached = read_cache(name);
+    if let Some(cache) = cached.as_ref().filter(|c| c.is_fresh(ttl_secs)) {
+        return cache.values.clone();
+    }
+
+    match fetch().await {
+        Ok(values) if !values.is_empty() => {
+            let cache = Cache { fetched_at: now_secs(), values };
+            write_cache(name, &cache);
+            cache.values
+        }
+        _ => cached.map(|c| c.values).unwrap_or_default(),
+    }
+}
+
+/// `[pricing] currency`, unless prices are shown in USD anyway.
+fn display_currency(config: &Config) -> Option<String> {
+    config.pricing.currency.as_deref().map(str::to_uppercase).filter(|c| c != "USD")
+}
+
+/// The price table from registrar `prices` and exchange `rates`, with the
+/// config's `[prices]` filling in any TLD the pricing source doesn't cover,
+/// converted to `[pricing] currency` when set.
+fn price_table(config: &Config, prices: BTreeMap<String, f64>, rates: &BTreeMap<String, f64>) -> PriceTable {
+    let mut table = PriceTable::default();
+    table.prices.extend(prices);
+    for (tld, price) in &config.prices {
+        table.prices.entry(tld.to_lowercase()).or_insert(*price);
+    }
+    // Without a rate, keep showing USD rather than a wrong number
+    if let Some((currency, rate)) = display_currency(config).and_then(|c| rates.get(&c).map(|rate| (c, *rate))) {
+        table.currency = currency;
+        table.rate = rate;
+    }
+    table
+}
+
+/// Builds the price table from live registrar pricing and rates, through
+/// the on-disk cache.
+pub async fn load_prices(client: &Client, config: &Config) -> PriceTable {
+    let ttl = config.pricing.ttl_hours.unwrap_or(DEFAULT_TTL_HOURS) * 3600;
+    let mut prices = BTreeMap::new();
+    if config.pricing.enabled.unwrap_or(true) {
+        prices = cached_or_fetch("prices.json", ttl, || fetch_prices(client)).await;
+    }
+    let mut rates = BTreeMap::new();
+    if display_currency(config).is_some() {
+        rates = cached_or_fetch("rates.json", ttl, || fetch_rates(client)).await;
+    }
+    price_table(config, prices, &rates)
+}
+
+/// The price table from the on-disk cache however old, and the config,
+/// without waiting on the network: what streamed results carry until
+/// `load_prices` finishes in the background.
+pub fn cached_prices(config: &Config) -> PriceTable {
+    let cached = |name| read_cache(name).map(|c| c.values).unwrap_or_default();
+    let mut prices = BTreeMap::new();
+    if config.pricing.enabled.unwrap_or(true) {
+        prices = cached("prices.json");
+    }
+    price_table(config, prices, &cached("rates.json"))
+}
+
+/// Prices for batch output: the cached table right away, replaced by the
+/// fetched one once `load_prices` is done in the background.
+pub struct LivePrices {
+    table: PriceTable,
+    fetching: Option<JoinHandle<PriceTable>>,
+}
+
+impl LivePrices {
+    pub fn start(config: &Config) -> Self {
+        let price_config = config.clone();
+        let fetching = tokio::spawn(async move { load_prices(&Client::new(), &price_config).await });
+        Self { table: cached_prices(config), fetching: Some(fetching) }
+    }
+
+    /// The newest table there is, without waiting for the fetch.
+    pub async fn current(&mut self) -> &PriceTable {
+        if self.fetching.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.finish().await;
+        }
+        &self.table
+    }
+
+    /// The fetched table, waiting for it.
+    pub async fn finish(&mut self) -> &PriceTable {
+        if let Some(fetching) = self.fetching.take() {
+            if let Ok(table) = fetching.await {
+                self.table = table;
+            }
+        }
+        &self.table
+    }
+}
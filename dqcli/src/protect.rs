@@ -0,0 +1,199 @@
+use futures::StreamExt;
+use librdap_storm::{Availability, Prober};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    audit::{self, AuditAction},
+    snapshot::{RegisteredEntry, Snapshot},
+    typosquat::{variants, VariantKind},
+    usage::UsageRecorder,
+};
+
+/// TLDs swept when `dq protect` gets no `--tlds`; every TLD multiplies
+/// the dozens of variants per brand.
+pub const DEFAULT_PROTECT_TLDS: &[&str] = &["com", "net", "org"];
+
+/// A lookalike domain seen registered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Registration {
+    /// Unix seconds of the pass that first saw it taken
+    pub first_seen: u64,
+    pub kind: VariantKind,
+}
+
+/// Lookalikes of one brand known to be registered, kept in the state dir
+/// so a restart doesn't re-alert on all of them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProtectHistory {
+    #[serde(default)]
+    pub registered: BTreeMap<String, Registration>,
+    /// Passes completed, across runs
+    #[serde(default)]
+    pub passes: u64,
+}
+
+/// Printed as NDJSON for each lookalike seen registered for the first time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectAlert {
+    pub at: u64,
+    pub brand: String,
+    pub domain: String,
+    pub kind: VariantKind,
+    /// Found by the very first pass, so possibly registered long ago
+    pub baseline: bool,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub registrar: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub created: Option<String>,
+    pub explanation: String,
+}
+
+/// Snapshot set of a brand's lookalikes, for `dq new-registrations`.
+pub fn snapshot_set(brand: &str) -> String {
+    format!("protect-{}", brand.trim().to_lowercase())
+}
+
+fn history_path(brand: &str) -> Option<PathBuf> {
+    let file: String = brand
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|p| p.join("dq").join("protect").join(format!("{}.json", file)))
+}
+
+impl ProtectHistory {
+    pub fn load(brand: &str) -> Self {
+        history_path(brand)
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, brand: &str) -> io::Result<()> {
+        let Some(path) = history_path(brand) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// One sweep over every variant of `brand` under `tlds`; returns the
+/// alerts for lookalikes not registered before.
+async fn pass(
+    prober: &Prober,
+    brand: &str,
+    tlds: &[String],
+    history: &mut ProtectHistory,
+    usage: &UsageRecorder,
+) -> Vec<ProtectAlert> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut kinds = HashMap::new();
+    for (name, kind) in variants(brand) {
+        for tld in tlds {
+            kinds.insert(format!("{}.{}", name, tld), kind);
+        }
+    }
+
+    let baseline = history.passes == 0;
+    let previous = Snapshot::latest(&snapshot_set(brand), 1).pop().unwrap_or_default();
+    let mut snapshot = Snapshot { at: now, registered: BTreeMap::new() };
+    let domains: Vec<String> = kinds.keys().cloned().collect();
+    let probes = domains.len() as u64;
+    let mut results = prober.probe_stream_detailed(domains);
+    let mut alerts = Vec::new();
+    let mut determined = false;
+    while let Some((result, record)) = results.next().await {
+        determined |= !result.availability.is_unknown();
+        let entry = match result.availability {
+            Availability::Taken => RegisteredEntry {
+                registrar: record.as_ref().and_then(|r| r.registrar.clone()),
+                created: record.and_then(|r| r.created),
+            },
+            // Undetermined this pass: keep what the last snapshot knew, so
+            // it doesn't show up as new once it answers again
+            Availability::Unknown { .. } => match previous.registered.get(&result.domain) {
+                Some(entry) => entry.clone(),
+                None => continue,
+            },
+            Availability::Available | Availability::Reserved | Availability::Premium => continue,
+        };
+        snapshot.registered.insert(result.domain.clone(), entry.clone());
+        if !result.availability.is_taken() || history.registered.contains_key(&result.domain) {
+            continue;
+        }
+        let Some(&kind) = kinds.get(&result.domain) else {
+            continue;
+        };
+        history.registered.insert(result.domain.clone(), Registration { first_seen: now, kind });
+        alerts.push(ProtectAlert {
+            at: now,
+            brand: brand.to_string(),
+            domain: result.domain,
+            kind,
+            baseline,
+            registrar: entry.registrar,
+            created: entry.created,
+            explanation: result.explanation,
+        });
+    }
+    usage.record(1, probes, &prober.metrics());
+    // A pass that learned nothing (offline, say) doesn't end the baseline
+    if determined {
+        history.passes += 1;
+        if let Err(e) = snapshot.save(&snapshot_set(brand)) {
+            eprintln!("Warning: could not save snapshot: {}", e);
+        }
+    }
+    alerts.sort_by(|a, b| a.domain.cmp(&b.domain));
+    alerts
+}
+
+/// Sweeps lookalikes of `brand` every `interval` (once with `once`),
+/// printing an alert per newly registered one. The first pass ever
+/// reports what is already registered, flagged `baseline`.
+pub async fn run(
+    prober: Prober,
+    brand: &str,
+    tlds: &[String],
+    interval: Duration,
+    once: bool,
+    usage: &UsageRecorder,
+) -> io::Result<()> {
+    let mut history = ProtectHistory::load(brand);
+    loop {
+        let alerts = pass(&prober, brand, tlds, &mut history, usage).await;
+        for alert in &alerts {
+            println!("{}", serde_json::to_string(alert)?);
+            if !alert.baseline {
+                eprintln!("Alert: {} is newly registered ({} of {})", alert.domain, alert.kind.label(), brand);
+                audit::record(AuditAction::ProtectAlert, format!("{} ({} of {})", alert.domain, alert.kind.label(), brand));
+            }
+        }
+        io::stdout().flush()?;
+        history.save(brand)?;
+        eprintln!(
+            "{} new lookalike registration(s) of {}; {} known",
+            alerts.len(),
+            brand,
+            history.registered.len()
+        );
+        if once {
+            return Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,115 @@
+use reqwest::{
+    header::{AUTHORIZATION, ETAG, IF_MATCH, IF_NONE_MATCH},
+    Client, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+
+use crate::config::RemoteConfig;
+
+pub const WATCHLIST_FILE: &str = "watchlist.json";
+pub const HISTORY_FILE: &str = "probe-history.json";
+
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Attempts at a conditional PUT before giving up on a busy file.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Team-shared state behind a plain HTTP GET/PUT server that honours
+/// `If-Match` and `If-None-Match` (WebDAV, or anything similar).
+#[derive(Debug, Clone)]
+pub struct RemoteStore {
+    client: Client,
+    base: String,
+    token: Option<String>,
+}
+
+impl RemoteStore {
+    /// `None` unless `[remote] url` is set.
+    pub fn from_config(config: &RemoteConfig) -> Option<Self> {
+        let base = config.url.as_deref()?.trim_end_matches('/').to_string();
+        Some(Self {
+            client: Client::new(),
+            base,
+            token: config.token.clone().filter(|t| !t.is_empty()),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, name: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}/{}", self.base, name))
+            .timeout(REMOTE_TIMEOUT);
+        match &self.token {
+            Some(token) => request.header(AUTHORIZATION, format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+
+    /// Fetches `name` and its ETag; `None` when it doesn't exist yet.
+    pub async fn pull<T: DeserializeOwned>(&self, name: &str) -> Result<Option<(T, Option<String>)>, String> {
+        let response = self
+            .request(reqwest::Method::GET, name)
+            .send()
+            .await
+            .map_err(|e| format!("remote {}: {}", name, e))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("remote {}: HTTP {}", name, response.status()));
+        }
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let value = response
+            .json()
+            .await
+            .map_err(|e| format!("remote {}: {}", name, e))?;
+        Ok(Some((value, etag)))
+    }
+
+    /// Writes `value`; with an ETag the write only succeeds if nobody else
+    /// wrote in between, without one only if the file still doesn't exist.
+    /// Returns false on such a conflict.
+    async fn push<T: Serialize>(&self, name: &str, value: &T, etag: Option<&str>) -> Result<bool, String> {
+        let body = serde_json::to_string(value).map_err(|e| e.to_string())?;
+        let mut request = self
+            .request(reqwest::Method::PUT, name)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+        request = match etag {
+            Some(etag) => request.header(IF_MATCH, etag),
+            // Two instances creating the file at once mustn't both succeed
+            None => request.header(IF_NONE_MATCH, "*"),
+        };
+        let response = request.send().await.map_err(|e| format!("remote {}: {}", name, e))?;
+        match response.status() {
+            StatusCode::PRECONDITION_FAILED => Ok(false),
+            status if status.is_success() => Ok(true),
+            status => Err(format!("remote {}: HTTP {}", name, status)),
+        }
+    }
+
+    /// Read-modify-write of `name`: `apply` gets the current remote value
+    /// and returns the one to store, retried if another instance wrote
+    /// concurrently. Returns what was stored.
+    pub async fn update<T, F>(&self, name: &str, mut apply: F) -> Result<T, String>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnMut(Option<T>) -> T,
+    {
+        for _ in 0..MAX_ATTEMPTS {
+            let (remote, etag) = match self.pull(name).await? {
+                Some((value, etag)) => (Some(value), etag),
+                None => (None, None),
+            };
+            let value = apply(remote);
+            if self.push(name, &value, etag.as_deref()).await? {
+                return Ok(value);
+            }
+        }
+        Err(format!("remote {}: kept changing, try again", name))
+    }
+}
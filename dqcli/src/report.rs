@@ -0,0 +1,232 @@
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use librdap_storm::DomainRecord;
+
+use crate::audit::format_utc;
+
+/// Taken domains expiring within this many days are highlighted.
+const EXPIRY_HIGHLIGHT_DAYS: i64 = 90;
+
+/// One TLD of the sweep, as shown in the report.
+#[derive(Debug, Clone)]
+pub struct ReportRow {
+    pub tld: String,
+    pub domain: String,
+    /// `Some(true)` available, `Some(false)` taken, `None` unknown
+    pub available: Option<bool>,
+    pub explanation: String,
+    /// Formatted in the display currency
+    pub price: Option<String>,
+    /// Numeric price, for sorting
+    pub price_value: Option<f64>,
+    pub registrar: Option<String>,
+    /// RFC 3339 expiration date from RDAP
+    pub expires: Option<String>,
+    /// Whether a taken domain is DNSSEC-signed, when known
+    pub dnssec: Option<bool>,
+    /// Lock posture of a taken domain, when its record could be read
+    pub locks: Option<Locks>,
+}
+
+/// What a registration record says about a domain's locks.
+#[derive(Debug, Clone)]
+pub struct Locks {
+    /// EPP codes, see `LOCK_STATUSES`
+    pub statuses: Vec<&'static str>,
+    pub transfer: bool,
+    pub registry: bool,
+}
+
+impl Locks {
+    pub fn of(record: &DomainRecord) -> Self {
+        Self { statuses: record.locks(), transfer: record.transfer_locked(), registry: record.registry_locked() }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Days since 1970-01-01 for a civil date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Days from `now_secs` until an RFC 3339 date; negative when past.
+fn days_until(date: &str, now_secs: u64) -> Option<i64> {
+    let mut parts = date.get(..10)?.split('-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Some(days_from_civil(year, month, day) - (now_secs / 86400) as i64)
+}
+
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #222; }
+h1 { margin-bottom: 0.2rem; }
+.meta { color: #666; margin-top: 0; }
+.summary { display: flex; gap: 1.5rem; margin: 1rem 0 1.5rem; }
+.summary div { padding: 0.6rem 1rem; border-radius: 6px; background: #f3f3f3; }
+.summary b { display: block; font-size: 1.6rem; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(7rem, 1fr)); gap: 4px; }
+.cell { padding: 0.4rem; border-radius: 4px; font-size: 0.85rem; color: #fff; }
+.cell small { display: block; opacity: 0.85; }
+.available { background: #2e9d4c; }
+.taken { background: #c0392b; }
+.unknown { background: #8a8a8a; }
+.expiring { outline: 3px solid #f1c40f; }
+.unlocked { color: #c0392b; font-weight: bold; }
+table { border-collapse: collapse; margin-top: 0.5rem; }
+td, th { padding: 0.3rem 0.8rem; border-bottom: 1px solid #ddd; text-align: left; }
+"#;
+
+/// Renders a self-contained HTML page: summary counts, a TLD grid colored
+/// by status, the cheapest available domains, taken ones expiring soon and
+/// the lock posture of taken ones.
+pub fn render_html(query: &str, rows: &[ReportRow]) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let count = |available: Option<bool>| rows.iter().filter(|r| r.available == available).count();
+    let expiring = |row: &ReportRow| {
+        row.available == Some(false)
+            && row
+                .expires
+                .as_deref()
+                .and_then(|e| days_until(e, now))
+                .is_some_and(|d| d <= EXPIRY_HIGHLIGHT_DAYS)
+    };
+
+    let unlocked = |row: &ReportRow| {
+        row.available == Some(false) && row.locks.as_ref().is_some_and(|locks| !locks.transfer)
+    };
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>dq report: {q}</title><style>{STYLE}</style></head><body>\n\
+         <h1>{q}</h1>\n<p class=\"meta\">{n} TLDs checked {at} UTC</p>\n",
+        q = escape(query),
+        n = rows.len(),
+        at = format_utc(now),
+    );
+
+    let _ = writeln!(
+        html,
+        "<div class=\"summary\"><div><b>{}</b>available</div><div><b>{}</b>taken</div><div><b>{}</b>unknown</div><div><b>{}</b>expiring within {} days</div><div><b>{}</b>without transfer lock</div></div>",
+        count(Some(true)),
+        count(Some(false)),
+        count(None),
+        rows.iter().filter(|r| expiring(r)).count(),
+        EXPIRY_HIGHLIGHT_DAYS,
+        rows.iter().filter(|r| unlocked(r)).count(),
+    );
+
+    html.push_str("<div class=\"grid\">\n");
+    for row in rows {
+        let class = match row.available {
+            Some(true) => "available",
+            Some(false) => "taken",
+            None => "unknown",
+        };
+        let mut title = row.explanation.clone();
+        if let Some(registrar) = &row.registrar {
+            let _ = write!(title, "\nRegistrar: {}", registrar);
+        }
+        if let Some(expires) = &row.expires {
+            let _ = write!(title, "\nExpires: {}", expires);
+        }
+        if let Some(signed) = row.dnssec {
+            let _ = write!(title, "\nDNSSEC: {}", if signed { "signed" } else { "unsigned" });
+        }
+        if let Some(locks) = row.locks.as_ref().filter(|_| row.available == Some(false)) {
+            let statuses = if locks.statuses.is_empty() { "none".to_string() } else { locks.statuses.join(", ") };
+            let _ = write!(title, "\nLocks: {}", statuses);
+        }
+        let _ = writeln!(
+            html,
+            "<div class=\"cell {}{}\" title=\"{}\">.{}<small>{}</small></div>",
+            class,
+            if expiring(row) { " expiring" } else { "" },
+            escape(&title),
+            escape(&row.tld),
+            escape(row.price.as_deref().unwrap_or("")),
+        );
+    }
+    html.push_str("</div>\n");
+
+    let mut available: Vec<&ReportRow> = rows.iter().filter(|r| r.available == Some(true)).collect();
+    available.sort_by(|a, b| {
+        let price = |r: &ReportRow| r.price_value.unwrap_or(f64::INFINITY);
+        price(a).total_cmp(&price(b))
+    });
+    if !available.is_empty() {
+        html.push_str("<h2>Available</h2>\n<table><tr><th>Domain</th><th>Price</th></tr>\n");
+        for row in available {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(&row.domain),
+                escape(row.price.as_deref().unwrap_or("-")),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let mut soon: Vec<&ReportRow> = rows.iter().filter(|r| expiring(r)).collect();
+    soon.sort_by(|a, b| a.expires.cmp(&b.expires));
+    if !soon.is_empty() {
+        html.push_str("<h2>Expiring soon</h2>\n<table><tr><th>Domain</th><th>Expires</th><th>Registrar</th></tr>\n");
+        for row in soon {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&row.domain),
+                escape(row.expires.as_deref().unwrap_or("")),
+                escape(row.registrar.as_deref().unwrap_or("-")),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let locked: Vec<&ReportRow> = rows.iter().filter(|r| r.available == Some(false) && r.locks.is_some()).collect();
+    if !locked.is_empty() {
+        html.push_str("<h2>Lock posture</h2>\n<table><tr><th>Domain</th><th>Registrar</th><th>Transfer lock</th><th>Registry lock</th><th>Lock statuses</th></tr>\n");
+        // Unlocked ones first, they're what an audit is after
+        let mut locked = locked;
+        locked.sort_by_key(|row| !unlocked(row));
+        for row in locked {
+            let Some(locks) = &row.locks else {
+                continue;
+            };
+            let yes_no = |yes: bool| if yes { "yes" } else { "no" };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td{}>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&row.domain),
+                escape(row.registrar.as_deref().unwrap_or("-")),
+                if unlocked(row) { " class=\"unlocked\"" } else { "" },
+                yes_no(locks.transfer),
+                yes_no(locks.registry),
+                if locks.statuses.is_empty() { "none".to_string() } else { escape(&locks.statuses.join(", ")) },
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
@@ -0,0 +1,44 @@
+use clap::ValueEnum;
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+use crate::{DomainCheckResult, SweepSummary};
+
+/// Machine-readable records dq emits.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemaRecord {
+    /// One NDJSON line per probed domain
+    Result,
+    /// Last stderr line of a sweep stopped with Ctrl-C, and the summary hook's input
+    Summary,
+}
+
+impl SchemaRecord {
+    fn schema(self) -> Value {
+        let schema = match self {
+            SchemaRecord::Result => schema_for!(DomainCheckResult),
+            SchemaRecord::Summary => schema_for!(SweepSummary),
+        };
+        serde_json::to_value(schema).unwrap_or(Value::Null)
+    }
+}
+
+/// Prints the JSON Schema for `record`, or an object keyed by record name
+/// holding every schema when no record is given.
+pub fn print_schema(record: Option<SchemaRecord>) -> Result<(), serde_json::Error> {
+    let doc = match record {
+        Some(record) => record.schema(),
+        None => {
+            let mut all = json!({});
+            for record in SchemaRecord::value_variants() {
+                if let Some(name) = record.to_possible_value() {
+                    all[name.get_name()] = record.schema();
+                }
+            }
+            all
+        }
+    };
+
+    println!("{}", serde_json::to_string_pretty(&doc)?);
+    Ok(())
+}
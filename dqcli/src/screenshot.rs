@@ -0,0 +1,213 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use std::{
+    fmt::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const CELL_WIDTH: u32 = 9;
+const CELL_HEIGHT: u32 = 18;
+const SVG_FOREGROUND: &str = "#d0d0d0";
+const SVG_BACKGROUND: &str = "#1c1c1c";
+
+/// The 16 ANSI colors as xterm draws them.
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Index into the 16-color palette for named colors.
+fn palette_index(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        _ => return None,
+    })
+}
+
+/// SGR parameters selecting `color` as foreground (or background).
+fn sgr_color(color: Color, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    if let Some(i) = palette_index(color) {
+        let code = if i < 8 { base + i as u32 } else { base + 60 + (i as u32 - 8) };
+        return Some(code.to_string());
+    }
+    match color {
+        Color::Rgb(r, g, b) => Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+        Color::Indexed(i) => Some(format!("{};5;{}", base + 8, i)),
+        _ => None,
+    }
+}
+
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    if let Some(i) = palette_index(color) {
+        return Some(PALETTE[i as usize]);
+    }
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(i) if i < 16 => Some(PALETTE[i as usize]),
+        Color::Indexed(i) if i < 232 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = i - 16;
+            Some((level(i / 36), level(i / 6 % 6), level(i % 6)))
+        }
+        Color::Indexed(i) => {
+            let v = 8 + (i - 232) * 10;
+            Some((v, v, v))
+        }
+        _ => None,
+    }
+}
+
+fn hex(color: Color, default: &str) -> String {
+    rgb(color).map_or_else(|| default.to_string(), |(r, g, b)| format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// The frame as text with ANSI escapes, viewable with `cat` or `less -R`.
+pub fn to_ansi(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        let mut current = None;
+        for x in area.left()..area.right() {
+            let Some(cell) = buffer.cell((x, y)) else {
+                continue;
+            };
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if current != Some(style) {
+                let mut params = vec!["0".to_string()];
+                for (flag, code) in [
+                    (Modifier::BOLD, "1"),
+                    (Modifier::DIM, "2"),
+                    (Modifier::ITALIC, "3"),
+                    (Modifier::UNDERLINED, "4"),
+                    (Modifier::REVERSED, "7"),
+                ] {
+                    if cell.modifier.contains(flag) {
+                        params.push(code.to_string());
+                    }
+                }
+                params.extend(sgr_color(cell.fg, false));
+                params.extend(sgr_color(cell.bg, true));
+                let _ = write!(out, "\x1b[{}m", params.join(";"));
+                current = Some(style);
+            }
+            out.push_str(cell.symbol());
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The frame as a standalone SVG with a dark terminal background.
+pub fn to_svg(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let (width, height) = (area.width as u32 * CELL_WIDTH, area.height as u32 * CELL_HEIGHT);
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+         font-family=\"Menlo, Consolas, 'DejaVu Sans Mono', monospace\" font-size=\"14\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"{SVG_BACKGROUND}\"/>",
+        w = width,
+        h = height,
+    );
+
+    for (row, y) in (area.top()..area.bottom()).enumerate() {
+        let baseline = row as u32 * CELL_HEIGHT + CELL_HEIGHT - 5;
+        let mut text = String::new();
+        for (col, x) in (area.left()..area.right()).enumerate() {
+            let Some(cell) = buffer.cell((x, y)) else {
+                continue;
+            };
+            let (mut fg, mut bg) = (hex(cell.fg, SVG_FOREGROUND), hex(cell.bg, SVG_BACKGROUND));
+            if cell.modifier.contains(Modifier::REVERSED) {
+                std::mem::swap(&mut fg, &mut bg);
+            }
+            let x_px = col as u32 * CELL_WIDTH;
+            if bg != SVG_BACKGROUND {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x_px,
+                    row as u32 * CELL_HEIGHT,
+                    CELL_WIDTH,
+                    CELL_HEIGHT,
+                    bg
+                );
+            }
+            if cell.symbol().trim().is_empty() {
+                continue;
+            }
+            let weight = if cell.modifier.contains(Modifier::BOLD) { " font-weight=\"bold\"" } else { "" };
+            let _ = write!(
+                text,
+                "<tspan x=\"{}\" fill=\"{}\"{}>{}</tspan>",
+                x_px,
+                fg,
+                weight,
+                escape(cell.symbol())
+            );
+        }
+        if !text.is_empty() {
+            let _ = writeln!(svg, "<text y=\"{}\" xml:space=\"preserve\">{}</text>", baseline, text);
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Writes `dq-<query>-<unix time>.ans` and `.svg` to the current directory
+/// and returns the ANSI file's path.
+pub fn save(buffer: &Buffer, query: &str) -> std::io::Result<PathBuf> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let name: String = query
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let base = if name.is_empty() { format!("dq-{}", stamp) } else { format!("dq-{}-{}", name, stamp) };
+
+    let ansi = PathBuf::from(format!("{}.ans", base));
+    std::fs::write(&ansi, to_ansi(buffer))?;
+    std::fs::write(format!("{}.svg", base), to_svg(buffer))?;
+    Ok(ansi)
+}
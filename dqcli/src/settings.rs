@@ -0,0 +1,205 @@
+use crate::config::TldConfig;
+use librdap_storm::ProbeConfig;
+use std::{collections::BTreeSet, io, path::Path, time::Duration};
+use toml_edit::{value, Array, DocumentMut};
+
+/// One line of the `,` settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Setting {
+    WhoisFallback,
+    CanaryCheck,
+    ConfirmAvailable,
+    DnsFirst,
+    DiscoverLimits,
+    DeferDuringMaintenance,
+    TimeoutSecs,
+    MaxRatePerEndpoint,
+    MaxConcurrentPerEndpoint,
+    Retries,
+    BackoffBaseMs,
+    AlwaysTlds,
+    NeverTlds,
+    FavoriteTlds,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 14] = [
+        Setting::WhoisFallback,
+        Setting::CanaryCheck,
+        Setting::ConfirmAvailable,
+        Setting::DnsFirst,
+        Setting::DiscoverLimits,
+        Setting::DeferDuringMaintenance,
+        Setting::TimeoutSecs,
+        Setting::MaxRatePerEndpoint,
+        Setting::MaxConcurrentPerEndpoint,
+        Setting::Retries,
+        Setting::BackoffBaseMs,
+        Setting::AlwaysTlds,
+        Setting::NeverTlds,
+        Setting::FavoriteTlds,
+    ];
+
+    /// Table and key in the config file.
+    pub fn key(self) -> (&'static str, &'static str) {
+        match self {
+            Setting::WhoisFallback => ("probe", "whois_fallback"),
+            Setting::CanaryCheck => ("probe", "canary_check"),
+            Setting::ConfirmAvailable => ("probe", "confirm_available"),
+            Setting::DnsFirst => ("probe", "dns_first"),
+            Setting::DiscoverLimits => ("probe", "discover_limits"),
+            Setting::DeferDuringMaintenance => ("probe", "defer_during_maintenance"),
+            Setting::TimeoutSecs => ("probe", "timeout_secs"),
+            Setting::MaxRatePerEndpoint => ("probe", "max_rate_per_endpoint"),
+            Setting::MaxConcurrentPerEndpoint => ("probe", "max_concurrent_per_endpoint"),
+            Setting::Retries => ("probe", "retries"),
+            Setting::BackoffBaseMs => ("probe", "backoff_base_ms"),
+            Setting::AlwaysTlds => ("tlds", "always"),
+            Setting::NeverTlds => ("tlds", "never"),
+            Setting::FavoriteTlds => ("tlds", "favorites"),
+        }
+    }
+
+    pub fn is_toggle(self) -> bool {
+        self.flag(&ProbeConfig::default()).is_some()
+    }
+
+    fn flag(self, probe: &ProbeConfig) -> Option<bool> {
+        match self {
+            Setting::WhoisFallback => Some(probe.whois_fallback),
+            Setting::CanaryCheck => Some(probe.canary_check),
+            Setting::ConfirmAvailable => Some(probe.confirm_available),
+            Setting::DnsFirst => Some(probe.dns_first),
+            Setting::DiscoverLimits => Some(probe.discover_limits),
+            Setting::DeferDuringMaintenance => Some(probe.defer_during_maintenance),
+            _ => None,
+        }
+    }
+
+    fn number(self, probe: &ProbeConfig) -> Option<u64> {
+        match self {
+            Setting::TimeoutSecs => Some(probe.timeout.as_secs()),
+            Setting::MaxRatePerEndpoint => Some(probe.max_rate_per_endpoint.into()),
+            Setting::MaxConcurrentPerEndpoint => Some(probe.max_concurrent_per_endpoint.into()),
+            Setting::Retries => Some(probe.retries.into()),
+            Setting::BackoffBaseMs => Some(probe.backoff_base.as_millis() as u64),
+            _ => None,
+        }
+    }
+
+    fn list(self, tlds: &TldConfig) -> Option<&Vec<String>> {
+        match self {
+            Setting::AlwaysTlds => Some(&tlds.always),
+            Setting::NeverTlds => Some(&tlds.never),
+            Setting::FavoriteTlds => Some(&tlds.favorites),
+            _ => None,
+        }
+    }
+
+    /// The current value as shown, and as prefilled for editing.
+    pub fn value(self, probe: &ProbeConfig, tlds: &TldConfig) -> String {
+        if let Some(flag) = self.flag(probe) {
+            return if flag { "on" } else { "off" }.to_string();
+        }
+        if let Some(number) = self.number(probe) {
+            return number.to_string();
+        }
+        self.list(tlds).map(|list| list.join(",")).unwrap_or_default()
+    }
+
+    /// Flips an on/off setting; others are left alone.
+    pub fn toggle(self, probe: &mut ProbeConfig) {
+        match self {
+            Setting::WhoisFallback => probe.whois_fallback ^= true,
+            Setting::CanaryCheck => probe.canary_check ^= true,
+            Setting::ConfirmAvailable => probe.confirm_available ^= true,
+            Setting::DnsFirst => probe.dns_first ^= true,
+            Setting::DiscoverLimits => probe.discover_limits ^= true,
+            Setting::DeferDuringMaintenance => probe.defer_during_maintenance ^= true,
+            _ => {}
+        }
+    }
+
+    /// Applies an edited value: a number, or comma-separated TLDs.
+    pub fn set(self, input: &str, probe: &mut ProbeConfig, tlds: &mut TldConfig) -> Result<(), String> {
+        if self.list(tlds).is_some() {
+            let list = input
+                .split(',')
+                .map(|tld| tld.trim().trim_start_matches('.').to_lowercase())
+                .filter(|tld| !tld.is_empty())
+                .collect();
+            match self {
+                Setting::AlwaysTlds => tlds.always = list,
+                Setting::NeverTlds => tlds.never = list,
+                _ => tlds.favorites = list,
+            }
+            return Ok(());
+        }
+
+        let number: u64 = input.trim().parse().map_err(|_| format!("{}: expected a number", self.key().1))?;
+        // Rates and slots of 0 would stall every probe
+        if number == 0 && !matches!(self, Setting::Retries | Setting::BackoffBaseMs) {
+            return Err(format!("{}: must be greater than 0", self.key().1));
+        }
+        let small = || u32::try_from(number).map_err(|_| format!("{}: too large", self.key().1));
+        match self {
+            Setting::TimeoutSecs => probe.timeout = Duration::from_secs(number),
+            Setting::MaxRatePerEndpoint => probe.max_rate_per_endpoint = small()?,
+            Setting::MaxConcurrentPerEndpoint => probe.max_concurrent_per_endpoint = small()?,
+            Setting::Retries => probe.retries = small()?,
+            Setting::BackoffBaseMs => probe.backoff_base = Duration::from_millis(number),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// The settings screen: the highlighted line and, while one is being
+/// edited, its text.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsScreen {
+    pub selected: usize,
+    pub editing: Option<String>,
+    /// Something changed since the screen was opened
+    pub changed: bool,
+}
+
+impl SettingsScreen {
+    pub fn setting(&self) -> Setting {
+        Setting::ALL[self.selected.min(Setting::ALL.len() - 1)]
+    }
+}
+
+/// Writes `settings` into the config file at `path`, creating it if needed.
+/// Comments and everything else in the file are kept.
+pub fn save(path: &Path, settings: &BTreeSet<Setting>, probe: &ProbeConfig, tlds: &TldConfig) -> io::Result<()> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+
+    for setting in settings {
+        let (table, key) = setting.key();
+        let item = if let Some(flag) = setting.flag(probe) {
+            value(flag)
+        } else if let Some(number) = setting.number(probe) {
+            value(number as i64)
+        } else {
+            let list = setting.list(tlds).cloned().unwrap_or_default();
+            value(list.into_iter().collect::<Array>())
+        };
+        if !doc.contains_key(table) {
+            doc[table] = toml_edit::table();
+        }
+        doc[table][key] = item;
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, doc.to_string())
+}
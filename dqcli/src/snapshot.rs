@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io, path::PathBuf};
+
+/// Snapshots kept per monitored set; older ones are deleted.
+const MAX_SNAPSHOTS: usize = 30;
+
+/// Registration details of one taken domain at snapshot time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisteredEntry {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub registrar: Option<String>,
+    /// RFC 3339 creation date from RDAP or WHOIS
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub created: Option<String>,
+}
+
+/// The taken domains of a monitored set (the watchlist, or a `dq protect`
+/// brand) as one pass found them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix seconds
+    pub at: u64,
+    pub registered: BTreeMap<String, RegisteredEntry>,
+}
+
+/// A domain taken in the newest snapshot but not the one before.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewRegistration {
+    pub domain: String,
+    #[serde(flatten)]
+    pub entry: RegisteredEntry,
+    /// When the snapshot that first saw it was taken
+    pub seen_at: u64,
+}
+
+fn set_dir(set: &str) -> Option<PathBuf> {
+    let name: String = set
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|p| p.join("dq").join("snapshots").join(name))
+}
+
+/// Snapshot files of `set`, oldest first.
+fn files(set: &str) -> Vec<PathBuf> {
+    let Some(entries) = set_dir(set).and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    // Named by zero-padded timestamp, so name order is time order
+    files.sort();
+    files
+}
+
+impl Snapshot {
+    /// Writes the snapshot and prunes the set to the newest `MAX_SNAPSHOTS`.
+    pub fn save(&self, set: &str) -> io::Result<()> {
+        let Some(dir) = set_dir(set) else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(format!("{:012}.json", self.at)), serde_json::to_string_pretty(self)?)?;
+
+        let files = files(set);
+        for old in &files[..files.len().saturating_sub(MAX_SNAPSHOTS)] {
+            let _ = std::fs::remove_file(old);
+        }
+        Ok(())
+    }
+
+    /// The newest snapshots of `set`, newest first, unreadable ones skipped.
+    pub fn latest(set: &str, count: usize) -> Vec<Snapshot> {
+        files(set)
+            .iter()
+            .rev()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .take(count)
+            .collect()
+    }
+
+    /// Domains taken here that `previous` didn't have.
+    pub fn newly_registered(&self, previous: &Snapshot) -> Vec<NewRegistration> {
+        self.registered
+            .iter()
+            .filter(|(domain, _)| !previous.registered.contains_key(*domain))
+            .map(|(domain, entry)| NewRegistration { domain: domain.clone(), entry: entry.clone(), seen_at: self.at })
+            .collect()
+    }
+}
@@ -0,0 +1,214 @@
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{AvailabilityStatus, DomainCheckResult};
+
+/// How many endpoints to list in the fastest/slowest tables.
+const ENDPOINT_RANKING_LEN: usize = 5;
+
+#[derive(Debug, Default, Serialize)]
+pub struct SweepStats {
+    pub records: usize,
+    pub skipped_lines: usize,
+    pub available: usize,
+    pub taken: usize,
+    /// Unregistered but held back, or premium-priced, by the registry
+    pub reserved: usize,
+    pub premium: usize,
+    pub errors: usize,
+    /// Undetermined because the registry was in a maintenance window
+    pub maintenance: usize,
+    /// Never probed because the sweep hit its `--deadline`
+    pub skipped: usize,
+    /// Never probed because the TLD doesn't take such names
+    pub invalid: usize,
+    pub tlds: BTreeMap<String, TldStats>,
+    pub fastest_endpoints: Vec<EndpointStats>,
+    pub slowest_endpoints: Vec<EndpointStats>,
+    pub error_reasons: BTreeMap<String, usize>,
+    /// RDAP `/help` notices remembered from earlier runs
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoint_notices: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TldStats {
+    pub total: usize,
+    pub available: usize,
+    pub taken: usize,
+    pub errors: usize,
+    /// Available share of definitive answers, `None` when there were none
+    pub availability_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStats {
+    pub endpoint: String,
+    pub requests: usize,
+    pub median_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Reads NDJSON result records from `paths` (`-` for stdin).
+pub fn collect_stats(paths: &[String]) -> io::Result<SweepStats> {
+    let mut stats = SweepStats::default();
+    let mut latencies: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+
+    for path in paths {
+        let reader: Box<dyn BufRead> = if path == "-" {
+            Box::new(BufReader::new(io::stdin()))
+        } else {
+            Box::new(BufReader::new(File::open(Path::new(path))?))
+        };
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<DomainCheckResult>(&line) {
+                Ok(record) => stats.add(record, &mut latencies),
+                Err(_) => stats.skipped_lines += 1,
+            }
+        }
+    }
+
+    for tld in stats.tlds.values_mut() {
+        let decided = tld.available + tld.taken;
+        if decided > 0 {
+            tld.availability_rate = Some(tld.available as f64 / decided as f64);
+        }
+    }
+
+    let mut endpoints: Vec<EndpointStats> = latencies
+        .into_iter()
+        .map(|(endpoint, mut samples)| {
+            samples.sort_unstable();
+            EndpointStats {
+                endpoint,
+                requests: samples.len(),
+                median_ms: samples[samples.len() / 2],
+                max_ms: samples[samples.len() - 1],
+            }
+        })
+        .collect();
+    endpoints.sort_by_key(|e| e.median_ms);
+    stats.fastest_endpoints = endpoints.iter().take(ENDPOINT_RANKING_LEN).cloned().collect();
+    stats.slowest_endpoints = endpoints.iter().rev().take(ENDPOINT_RANKING_LEN).cloned().collect();
+
+    Ok(stats)
+}
+
+impl SweepStats {
+    fn add(&mut self, record: DomainCheckResult, latencies: &mut BTreeMap<String, Vec<u64>>) {
+        self.records += 1;
+        let tld = self.tlds.entry(record.tld.clone()).or_default();
+        tld.total += 1;
+
+        match record.status {
+            AvailabilityStatus::Available => {
+                self.available += 1;
+                tld.available += 1;
+            }
+            AvailabilityStatus::Taken => {
+                self.taken += 1;
+                tld.taken += 1;
+            }
+            AvailabilityStatus::Error => {
+                self.errors += 1;
+                tld.errors += 1;
+                let reason = record.error.unwrap_or_else(|| "unknown".to_string());
+                *self.error_reasons.entry(reason).or_default() += 1;
+            }
+            AvailabilityStatus::Reserved => self.reserved += 1,
+            AvailabilityStatus::Premium => self.premium += 1,
+            AvailabilityStatus::Maintenance => self.maintenance += 1,
+            AvailabilityStatus::Skipped => self.skipped += 1,
+            AvailabilityStatus::Invalid => self.invalid += 1,
+            AvailabilityStatus::Checking | AvailabilityStatus::Pending => {}
+        }
+
+        if let Some(endpoint) = record.endpoint {
+            latencies.entry(endpoint).or_default().push(record.duration_ms);
+        }
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{} records: {} available, {} taken, {} errors",
+            self.records, self.available, self.taken, self.errors
+        );
+        if self.reserved + self.premium > 0 {
+            println!("({} reserved, {} premium)", self.reserved, self.premium);
+        }
+        if self.maintenance > 0 {
+            println!("({} during registry maintenance)", self.maintenance);
+        }
+        if self.skipped > 0 {
+            println!("({} skipped at the deadline)", self.skipped);
+        }
+        if self.invalid > 0 {
+            println!("({} invalid for their TLD, not probed)", self.invalid);
+        }
+        if self.skipped_lines > 0 {
+            println!("({} unreadable lines skipped)", self.skipped_lines);
+        }
+
+        println!("\nAvailability by TLD:");
+        let mut tlds: Vec<_> = self.tlds.iter().collect();
+        tlds.sort_by(|a, b| {
+            b.1.availability_rate
+                .unwrap_or(-1.0)
+                .total_cmp(&a.1.availability_rate.unwrap_or(-1.0))
+                .then_with(|| a.0.cmp(b.0))
+        });
+        for (name, tld) in tlds {
+            let rate = tld
+                .availability_rate
+                .map(|r| format!("{:>5.1}%", r * 100.0))
+                .unwrap_or_else(|| "    -".to_string());
+            println!(
+                "  .{:<16} {}  ({} available, {} taken, {} errors)",
+                name, rate, tld.available, tld.taken, tld.errors
+            );
+        }
+
+        if !self.fastest_endpoints.is_empty() {
+            println!("\nFastest endpoints (median):");
+            for e in &self.fastest_endpoints {
+                println!("  {:>6} ms  {} ({} requests)", e.median_ms, e.endpoint, e.requests);
+            }
+            println!("\nSlowest endpoints (median):");
+            for e in &self.slowest_endpoints {
+                println!(
+                    "  {:>6} ms  {} ({} requests, max {} ms)",
+                    e.median_ms, e.endpoint, e.requests, e.max_ms
+                );
+            }
+        }
+
+        if !self.error_reasons.is_empty() {
+            println!("\nErrors:");
+            let mut reasons: Vec<_> = self.error_reasons.iter().collect();
+            reasons.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            for (reason, count) in reasons {
+                println!("  {:>6}  {}", count, reason);
+            }
+        }
+
+        if !self.endpoint_notices.is_empty() {
+            println!("\nEndpoint notices:");
+            for (endpoint, notices) in &self.endpoint_notices {
+                println!("  {}", endpoint);
+                for notice in notices {
+                    println!("    - {}", notice);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame, Terminal,
+};
+use std::{
+    io,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::runtime::Runtime;
+
+use crate::{
+    audit::format_utc,
+    config::Theme,
+    instance,
+    watcher::{Control, WatcherStatus},
+};
+
+/// How often the running watcher is asked for its status.
+const REFRESH: Duration = Duration::from_secs(2);
+
+/// The status shown, and whether it came from a running watcher rather
+/// than the state file one left behind.
+struct StatusView {
+    status: Option<WatcherStatus>,
+    live: bool,
+    message: Option<String>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `90` as `1m`, `7200` as `2h`, `5400` as `1h30m`.
+fn span_text(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 if secs % 3600 < 60 => format!("{}h", secs / 3600),
+        3600..=86399 => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+fn ago(at: u64) -> String {
+    format!("{} ago", span_text(now_secs().saturating_sub(at)))
+}
+
+fn verdict(available: Option<bool>) -> &'static str {
+    match available {
+        Some(true) => "available",
+        Some(false) => "taken",
+        None => "unknown",
+    }
+}
+
+/// e.g. `taken → available`; first sightings have no previous verdict.
+fn change_text(previous: Option<bool>, available: Option<bool>) -> String {
+    match previous {
+        Some(_) => format!("{} → {}", verdict(previous), verdict(available)),
+        None => format!("first seen {}", verdict(available)),
+    }
+}
+
+async fn fetch() -> StatusView {
+    let mut status = None;
+    let sent = instance::send(&Control::Status.to_line(), |line| {
+        status = serde_json::from_str(line).ok();
+    })
+    .await;
+    match (sent, status) {
+        (Ok(()), Some(status)) => StatusView { status: Some(status), live: true, message: None },
+        _ => StatusView { status: WatcherStatus::load_saved(), live: false, message: None },
+    }
+}
+
+/// `dq status`: the running watcher's schedule, verdicts per watched name
+/// and recent changes, refreshed every couple of seconds. Without a
+/// running watcher the last saved status is shown.
+pub fn run(theme: Theme) -> Result<(), Box<dyn std::error::Error>> {
+    let rt = Runtime::new()?;
+    let mut view = rt.block_on(fetch());
+    if view.status.is_none() {
+        eprintln!("Error: no dq watcher is running or has run yet; start one with `dq watch run`");
+        std::process::exit(1);
+    }
+
+    crate::install_panic_hook();
+    crate::enter_terminal()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let res = run_view(&mut terminal, &rt, &mut view, theme);
+    crate::leave_terminal()?;
+    res.map_err(Into::into)
+}
+
+fn run_view<B: Backend>(terminal: &mut Terminal<B>, rt: &Runtime, view: &mut StatusView, theme: Theme) -> io::Result<()> {
+    let mut fetched = Instant::now();
+    loop {
+        terminal.draw(|f| ui(f, view, theme))?;
+
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                    KeyCode::Char('r') => {
+                        let sent = rt.block_on(instance::send(&Control::Recheck.to_line(), |_| {}));
+                        let message = match sent {
+                            Ok(()) => "Pass started".to_string(),
+                            Err(e) => format!("Recheck failed: {}", e),
+                        };
+                        *view = rt.block_on(fetch());
+                        view.message = Some(message);
+                        fetched = Instant::now();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if fetched.elapsed() >= REFRESH {
+            let message = view.message.take();
+            *view = rt.block_on(fetch());
+            view.message = message;
+            fetched = Instant::now();
+        }
+    }
+}
+
+fn ui(f: &mut Frame, view: &StatusView, theme: Theme) {
+    let Some(status) = &view.status else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(3),
+            Constraint::Length(12.min(status.recent_changes.len() as u16 + 2).max(3)),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
+
+    let (title, title_color) = if view.live {
+        (format!("Watcher ─ running, pid {}", status.pid), theme.available)
+    } else {
+        ("Watcher ─ not running, last saved status".to_string(), theme.error)
+    };
+    let pass = if status.checking && view.live {
+        "checking now".to_string()
+    } else {
+        match (status.last_pass, status.next_pass) {
+            (Some(last), Some(next)) if view.live => {
+                format!("last {}, next in {}", ago(last), span_text(next.saturating_sub(now_secs())))
+            }
+            (Some(last), _) => format!("last {}", ago(last)),
+            _ => "no pass finished yet".to_string(),
+        }
+    };
+    // `dq watch check-once` leaves no interval behind
+    let schedule = match status.interval_secs {
+        0 => "one-shot checks".to_string(),
+        secs => format!("every {}", span_text(secs)),
+    };
+    let header = vec![
+        Line::from(format!(
+            "Started {} UTC ({}) · {} · TLDs {}",
+            format_utc(status.started_at),
+            ago(status.started_at),
+            schedule,
+            status.tlds.join(", ")
+        )),
+        Line::from(format!("Pass {}: {}", status.passes, pass)),
+    ];
+    f.render_widget(
+        Paragraph::new(header).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(title, Style::default().fg(title_color).add_modifier(Modifier::BOLD))),
+        ),
+        chunks[0],
+    );
+
+    let watches: Vec<ListItem> = status
+        .names
+        .iter()
+        .map(|name| {
+            let mut spans = vec![Span::styled(format!("{:<24}", name), Style::default().fg(theme.accent))];
+            for (domain, available) in &status.verdicts {
+                let shown = if name.contains('.') {
+                    (domain == name).then_some(domain.as_str())
+                } else {
+                    domain.strip_prefix(name.as_str()).and_then(|rest| rest.strip_prefix('.'))
+                };
+                if let Some(shown) = shown {
+                    let (symbol, color) = if *available { ("✓", theme.available) } else { ("✗", theme.taken) };
+                    spans.push(Span::styled(format!(" {} {}", symbol, shown), Style::default().fg(color)));
+                }
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    f.render_widget(
+        List::new(watches).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Watches ({})", status.names.len())),
+        ),
+        chunks[1],
+    );
+
+    let changes: Vec<ListItem> = status
+        .recent_changes
+        .iter()
+        .rev()
+        .map(|event| {
+            let color = match event.available {
+                Some(true) => theme.available,
+                Some(false) => theme.taken,
+                None => theme.error,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}  ", format_utc(event.at)), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{:<32}", event.domain), Style::default().fg(theme.accent)),
+                Span::styled(change_text(event.previous, event.available), Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+    f.render_widget(
+        List::new(changes).block(Block::default().borders(Borders::ALL).title("Recent changes")),
+        chunks[2],
+    );
+
+    let help = match &view.message {
+        Some(message) => format!("{} | r: recheck | q: quit", message),
+        None => "r: recheck | q: quit".to_string(),
+    };
+    f.render_widget(Paragraph::new(help).style(Style::default().fg(Color::DarkGray)), chunks[3]);
+}
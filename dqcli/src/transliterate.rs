@@ -0,0 +1,65 @@
+/// Letters and how they are spelled in ASCII.
+type Spelling = &'static [(char, &'static str)];
+
+/// Registries' own ASCII spelling of their national letters, e.g. DENIC's
+/// `ü` → `ue`; checked before `FOLDS` for names under these TLDs.
+const CONVENTIONS: &[(&[&str], Spelling)] = &[
+    (&["de", "at", "ch", "li"], &[('ä', "ae"), ('ö', "oe"), ('ü', "ue"), ('ß', "ss")]),
+    (&["no", "dk"], &[('æ', "ae"), ('ø', "oe"), ('å', "aa")]),
+    (&["se", "fi", "ax"], &[('å', "a"), ('ä', "a"), ('ö', "o")]),
+    (&["is"], &[('þ', "th"), ('ð', "d"), ('æ', "ae"), ('ö', "o")]),
+];
+
+/// Latin letters with diacritics and the ASCII they fold to elsewhere.
+const FOLDS: &[(&str, &str)] = &[
+    ("àáâãäåāăą", "a"),
+    ("çćĉċč", "c"),
+    ("ďđ", "d"),
+    ("èéêëēĕėęě", "e"),
+    ("ĝğġģ", "g"),
+    ("ĥħ", "h"),
+    ("ìíîïĩīĭįı", "i"),
+    ("ĵ", "j"),
+    ("ķ", "k"),
+    ("ĺļľŀł", "l"),
+    ("ñńņňŉ", "n"),
+    ("òóôõöøōŏő", "o"),
+    ("ŕŗř", "r"),
+    ("śŝşšș", "s"),
+    ("ţťŧț", "t"),
+    ("ùúûüũūŭůűų", "u"),
+    ("ŵ", "w"),
+    ("ýÿŷ", "y"),
+    ("źżž", "z"),
+    ("ß", "ss"),
+    ("æ", "ae"),
+    ("œ", "oe"),
+    ("þ", "th"),
+    ("ð", "d"),
+];
+
+/// `name` spelled in ASCII the way `tld`'s registry does it (`münchen`
+/// → `muenchen` under .de), for names with letters outside ASCII. `None`
+/// for ASCII names and for scripts with no Latin spelling.
+pub fn ascii_form(name: &str, tld: &str) -> Option<String> {
+    if name.is_ascii() {
+        return None;
+    }
+    let tld = tld.rsplit('.').next().unwrap_or(tld);
+    let convention = CONVENTIONS
+        .iter()
+        .find(|(tlds, _)| tlds.contains(&tld))
+        .map_or(&[][..], |(_, letters)| letters);
+    let mut ascii = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_ascii() {
+            ascii.push(c);
+        } else if let Some((_, spelled)) = convention.iter().find(|(letter, _)| *letter == c) {
+            ascii.push_str(spelled);
+        } else {
+            ascii.push_str(FOLDS.iter().find(|(letters, _)| letters.contains(c))?.1);
+        }
+    }
+    // Folding can lengthen a label past DNS's 63 octets
+    (ascii.len() <= 63).then_some(ascii)
+}
@@ -0,0 +1,212 @@
+use librdap_storm::{public_suffix, Availability, EndpointHelp, ProbeConfig, ProbeResult, TldRanker};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Weight of the newest sample in the moving averages.
+const SMOOTHING: f64 = 0.3;
+pub const DEFAULT_HISTORY_MAX_AGE_DAYS: u64 = 30;
+/// Samples needed before an endpoint's error rate influences concurrency.
+const MIN_SAMPLES: u64 = 20;
+/// Error rate above which the starting concurrency is halved.
+const HIGH_ERROR_RATE: f64 = 0.2;
+
+/// Latency and error history carried between runs, used to pick the
+/// starting concurrency and to probe historically fast TLDs first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProbeHistory {
+    #[serde(default)]
+    endpoints: BTreeMap<String, EndpointHistory>,
+    #[serde(default)]
+    tlds: BTreeMap<String, TldHistory>,
+    /// Notices from each endpoint's RDAP `/help`, when limit discovery ran
+    #[serde(default)]
+    notices: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct EndpointHistory {
+    samples: u64,
+    /// Moving average, milliseconds
+    latency_ms: f64,
+    /// Moving average share of probes that ended in an error
+    error_rate: f64,
+    /// Unix seconds of the latest sample; 0 in files written before expiry existed
+    #[serde(default)]
+    last_seen: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TldHistory {
+    latency_ms: f64,
+    /// RDAP endpoint that answered last time
+    #[serde(default)]
+    endpoint: Option<String>,
+    #[serde(default)]
+    last_seen: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|p| p.join("dq").join("probe-history.json"))
+}
+
+fn smooth(previous: f64, sample: f64, first: bool) -> f64 {
+    if first {
+        sample
+    } else {
+        previous + SMOOTHING * (sample - previous)
+    }
+}
+
+impl ProbeHistory {
+    pub fn load() -> Self {
+        history_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = history_path() {
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            if let Ok(json) = serde_json::to_string(self) {
+                let _ = std::fs::write(path, json);
+            }
+        }
+    }
+
+    pub fn record(&mut self, result: &ProbeResult) {
+        let latency = result.duration.as_millis() as f64;
+        let failed = matches!(result.availability, Availability::Unknown { .. });
+        let now = now_secs();
+
+        if let Some(tld) = public_suffix(&result.domain) {
+            let first = !self.tlds.contains_key(tld);
+            let entry = self.tlds.entry(tld.to_string()).or_default();
+            entry.latency_ms = smooth(entry.latency_ms, latency, first);
+            entry.last_seen = now;
+            if result.endpoint.is_some() {
+                entry.endpoint = result.endpoint.clone();
+            }
+        }
+
+        if let Some(endpoint) = &result.endpoint {
+            let entry = self.endpoints.entry(endpoint.clone()).or_default();
+            let first = entry.samples == 0;
+            entry.samples += 1;
+            entry.latency_ms = smooth(entry.latency_ms, latency, first);
+            entry.error_rate = smooth(entry.error_rate, if failed { 1.0 } else { 0.0 }, first);
+            entry.last_seen = now;
+        }
+    }
+
+    pub fn record_help(&mut self, help: Vec<(String, EndpointHelp)>) {
+        for (endpoint, help) in help {
+            if !help.notices.is_empty() {
+                self.notices.insert(endpoint, help.notices);
+            }
+        }
+    }
+
+    /// Folds in history from another instance: per endpoint the entry with
+    /// more samples wins; TLDs and notices only fill gaps.
+    pub fn merge(&mut self, other: ProbeHistory) {
+        for (endpoint, theirs) in other.endpoints {
+            match self.endpoints.get(&endpoint) {
+                Some(ours) if ours.samples >= theirs.samples => {}
+                _ => {
+                    self.endpoints.insert(endpoint, theirs);
+                }
+            }
+        }
+        for (tld, theirs) in other.tlds {
+            self.tlds.entry(tld).or_insert(theirs);
+        }
+        for (endpoint, notices) in other.notices {
+            self.notices.entry(endpoint).or_insert(notices);
+        }
+    }
+
+    /// Forgets endpoints and TLDs without a sample in `max_age`, so a
+    /// registry that moved or a TLD no longer swept stops steering the
+    /// tuning. Entries from older files, without a timestamp, are kept.
+    pub fn expire(&mut self, max_age: Duration) {
+        let cutoff = now_secs().saturating_sub(max_age.as_secs());
+        self.endpoints.retain(|_, e| e.last_seen == 0 || e.last_seen >= cutoff);
+        self.tlds.retain(|_, t| t.last_seen == 0 || t.last_seen >= cutoff);
+        self.notices.retain(|endpoint, _| self.endpoints.contains_key(endpoint));
+    }
+
+    pub fn notices(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.notices
+    }
+
+    /// Orders TLDs for dispatch: priority TLDs first in rank order, then the
+    /// rest fastest first. The rest is interleaved across the endpoints that
+    /// served them last time, so a run of TLDs sharing one slow registry
+    /// can't hold every in-flight slot while other endpoints sit idle.
+    /// TLDs without history sort as if they had the median latency.
+    pub fn order(&self, tlds: Vec<String>, ranker: &TldRanker) -> Vec<String> {
+        let (mut ordered, mut rest): (Vec<_>, Vec<_>) =
+            tlds.into_iter().partition(|tld| ranker.weight(tld).is_some());
+        ordered.sort_by(|a, b| ranker.compare(a, b));
+
+        let mut known: Vec<f64> = self.tlds.values().map(|t| t.latency_ms).collect();
+        if known.is_empty() {
+            ordered.extend(rest);
+            return ordered;
+        }
+        known.sort_by(f64::total_cmp);
+        let median = known[known.len() / 2];
+
+        let latency = |tld: &str| self.tlds.get(tld).map_or(median, |t| t.latency_ms);
+        rest.sort_by(|a, b| latency(a).total_cmp(&latency(b)));
+
+        // Buckets keep first-seen order, so the fastest endpoint leads each round
+        let mut buckets: Vec<(Option<&str>, VecDeque<String>)> = Vec::new();
+        for tld in rest {
+            let endpoint = self.tlds.get(&tld).and_then(|t| t.endpoint.as_deref());
+            // TLDs with no known endpoint aren't assumed to share one
+            match buckets.iter_mut().find(|(e, _)| endpoint.is_some() && *e == endpoint) {
+                Some((_, bucket)) => bucket.push_back(tld),
+                None => buckets.push((endpoint, VecDeque::from([tld]))),
+            }
+        }
+        while !buckets.is_empty() {
+            for (_, bucket) in buckets.iter_mut() {
+                ordered.extend(bucket.pop_front());
+            }
+            buckets.retain(|(_, bucket)| !bucket.is_empty());
+        }
+
+        ordered
+    }
+
+    /// Halves the starting concurrency when endpoints errored a lot last time.
+    pub fn tune(&self, config: &mut ProbeConfig) {
+        let (samples, errors) = self
+            .endpoints
+            .values()
+            .fold((0, 0.0), |(samples, errors), e| {
+                (samples + e.samples, errors + e.error_rate * e.samples as f64)
+            });
+
+        if samples >= MIN_SAMPLES && errors / samples as f64 > HIGH_ERROR_RATE {
+            config.max_concurrent_per_endpoint = (config.max_concurrent_per_endpoint / 2).max(1);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a variant differs from the brand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VariantKind {
+    /// A character left out: `exmple`
+    Omission,
+    /// A character typed twice: `exxample`
+    Repetition,
+    /// Two neighbours swapped: `examlpe`
+    Transposition,
+    /// A key next to the intended one on a QWERTY keyboard: `exsmple`
+    Replacement,
+    /// ASCII lookalikes: `examp1e`, `rn` for `m`
+    Homoglyph,
+    /// A hyphen inserted: `ex-ample`
+    Hyphenation,
+    /// A Latin letter swapped for an identical-looking Cyrillic one
+    Idn,
+}
+
+impl VariantKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            VariantKind::Omission => "omission",
+            VariantKind::Repetition => "repetition",
+            VariantKind::Transposition => "transposition",
+            VariantKind::Replacement => "replacement",
+            VariantKind::Homoglyph => "homoglyph",
+            VariantKind::Hyphenation => "hyphenation",
+            VariantKind::Idn => "idn",
+        }
+    }
+}
+
+/// QWERTY neighbours of each letter and digit.
+fn adjacent_keys(c: char) -> &'static str {
+    match c {
+        'q' => "wa", 'w' => "qeas", 'e' => "wrsd", 'r' => "etdf", 't' => "ryfg",
+        'y' => "tugh", 'u' => "yihj", 'i' => "uojk", 'o' => "ipkl", 'p' => "ol",
+        'a' => "qwsz", 's' => "weadzx", 'd' => "erfsxc", 'f' => "rtdgcv", 'g' => "tyfhvb",
+        'h' => "yugjbn", 'j' => "uihknm", 'k' => "iojlm", 'l' => "opk",
+        'z' => "asx", 'x' => "zsdc", 'c' => "xdfv", 'v' => "cfgb", 'b' => "vghn",
+        'n' => "bhjm", 'm' => "njk",
+        '1' => "2q", '2' => "13w", '3' => "24e", '4' => "35r", '5' => "46t",
+        '6' => "57y", '7' => "68u", '8' => "79i", '9' => "80o", '0' => "9p",
+        _ => "",
+    }
+}
+
+/// ASCII sequences that read alike, both directions listed.
+const HOMOGLYPHS: &[(&str, &str)] = &[
+    ("o", "0"), ("0", "o"), ("l", "1"), ("1", "l"), ("i", "1"), ("i", "l"), ("l", "i"),
+    ("m", "rn"), ("rn", "m"), ("w", "vv"), ("vv", "w"), ("d", "cl"), ("cl", "d"),
+    ("s", "5"), ("e", "3"), ("g", "q"), ("q", "g"), ("u", "v"), ("v", "u"),
+];
+
+/// Cyrillic letters that render like these Latin ones.
+const CYRILLIC: &[(char, char)] = &[
+    ('a', 'а'), ('c', 'с'), ('e', 'е'), ('i', 'і'), ('j', 'ј'), ('o', 'о'),
+    ('p', 'р'), ('s', 'ѕ'), ('x', 'х'), ('y', 'у'),
+];
+
+fn valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.chars().count() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && !label.contains("--")
+}
+
+/// Lookalike and mistyped variants of `brand`, each once (the first kind
+/// to produce it wins), without `brand` itself.
+pub fn variants(brand: &str) -> Vec<(String, VariantKind)> {
+    let brand = brand.trim().to_lowercase();
+    let chars: Vec<char> = brand.chars().collect();
+    let mut found: BTreeMap<String, VariantKind> = BTreeMap::new();
+    let mut add = |name: String, kind: VariantKind| {
+        if name != brand && valid_label(&name) {
+            found.entry(name).or_insert(kind);
+        }
+    };
+    let join = |chars: &[char]| chars.iter().collect::<String>();
+
+    for i in 0..chars.len() {
+        let mut omitted = chars.clone();
+        omitted.remove(i);
+        add(join(&omitted), VariantKind::Omission);
+
+        let mut repeated = chars.clone();
+        repeated.insert(i, chars[i]);
+        add(join(&repeated), VariantKind::Repetition);
+
+        if i + 1 < chars.len() && chars[i] != chars[i + 1] {
+            let mut swapped = chars.clone();
+            swapped.swap(i, i + 1);
+            add(join(&swapped), VariantKind::Transposition);
+        }
+
+        for key in adjacent_keys(chars[i]).chars() {
+            let mut replaced = chars.clone();
+            replaced[i] = key;
+            add(join(&replaced), VariantKind::Replacement);
+        }
+
+        if i > 0 {
+            let mut hyphenated = chars.clone();
+            hyphenated.insert(i, '-');
+            add(join(&hyphenated), VariantKind::Hyphenation);
+        }
+
+        if let Some(&(_, cyrillic)) = CYRILLIC.iter().find(|(latin, _)| *latin == chars[i]) {
+            let mut idn = chars.clone();
+            idn[i] = cyrillic;
+            add(join(&idn), VariantKind::Idn);
+        }
+    }
+
+    for (from, to) in HOMOGLYPHS {
+        for (pos, _) in brand.match_indices(from) {
+            let variant = format!("{}{}{}", &brand[..pos], to, &brand[pos + from.len()..]);
+            add(variant, VariantKind::Homoglyph);
+        }
+    }
+
+    found.into_iter().collect()
+}
@@ -0,0 +1,139 @@
+use minisign_verify::{PublicKey, Signature};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{error::Error, path::Path};
+
+use crate::config::DQ_USER_AGENT;
+
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Lists the SHA-256 of every release asset; signed as `SHA256SUMS.minisig`.
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+/// Minisign public key of the release signer, set when release builds are
+/// made. Without it there is nothing to trust a download by, and
+/// `dq self-update` only checks for releases.
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("DQ_RELEASE_PUBKEY");
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Result<&Asset, String> {
+        self.assets
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| format!("release {} has no {}", self.tag_name, name))
+    }
+}
+
+/// Prebuilt binaries are published as e.g. `dq-x86_64-linux`.
+fn binary_asset_name() -> String {
+    format!("dq-{}-{}{}", std::env::consts::ARCH, std::env::consts::OS, std::env::consts::EXE_SUFFIX)
+}
+
+/// `v0.10.1` as `[0, 10, 1]`; anything after a `-` is ignored.
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+async fn download(client: &Client, asset: &Asset) -> Result<Vec<u8>, Box<dyn Error>> {
+    let response = client.get(&asset.browser_download_url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Checks `checksums` against the release signature by `key`, then
+/// `binary` against its line in `checksums`.
+fn verify(key: &str, checksums: &[u8], signature: &[u8], binary: &[u8], name: &str) -> Result<(), Box<dyn Error>> {
+    let key = PublicKey::from_base64(key)?;
+    let signature = Signature::decode(std::str::from_utf8(signature)?)?;
+    key.verify(checksums, &signature, false)
+        .map_err(|e| format!("{} signature does not verify: {}", CHECKSUMS_ASSET, e))?;
+    let expected = String::from_utf8_lossy(checksums)
+        .lines()
+        .find_map(|line| {
+            let (sum, file) = line.split_once(char::is_whitespace)?;
+            (file.trim().trim_start_matches('*') == name).then(|| sum.to_lowercase())
+        })
+        .ok_or_else(|| format!("{} has no entry for {}", CHECKSUMS_ASSET, name))?;
+    let actual = format!("{:x}", Sha256::digest(binary));
+    if actual != expected {
+        return Err(format!("checksum mismatch for {}: expected {}, got {}", name, expected, actual).into());
+    }
+    Ok(())
+}
+
+/// Writes `binary` next to `exe` and moves it into place, so a failed
+/// write never leaves a half-written dq behind.
+fn replace(exe: &Path, binary: &[u8]) -> std::io::Result<()> {
+    let staged = exe.with_extension("new");
+    std::fs::write(&staged, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    // Windows won't overwrite a running executable, but will rename it
+    #[cfg(windows)]
+    std::fs::rename(exe, exe.with_extension("old"))?;
+    std::fs::rename(&staged, exe)
+}
+
+/// `dq self-update`: installs the latest GitHub release over the running
+/// binary, or with `check_only` just says whether there is one.
+pub fn run(check_only: bool) -> Result<(), Box<dyn Error>> {
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let client = Client::builder().user_agent(DQ_USER_AGENT).build()?;
+        let url = format!(
+            "{}/releases/latest",
+            env!("CARGO_PKG_REPOSITORY").replace("https://github.com/", "https://api.github.com/repos/")
+        );
+        let release: Release = client.get(&url).send().await?.error_for_status()?.json().await?;
+
+        if version_parts(&release.tag_name) <= version_parts(CURRENT_VERSION) {
+            println!("dq {} is up to date (latest release: {})", CURRENT_VERSION, release.tag_name);
+            return Ok(());
+        }
+        if check_only {
+            println!("dq {} is available (installed: {}); run `dq self-update` to install it", release.tag_name, CURRENT_VERSION);
+            return Ok(());
+        }
+
+        // A checksum from the same release proves nothing on its own
+        let Some(key) = RELEASE_PUBLIC_KEY else {
+            return Err(format!(
+                "this build has no release signing key, so {} can't be verified; download it from {}/releases",
+                release.tag_name,
+                env!("CARGO_PKG_REPOSITORY")
+            )
+            .into());
+        };
+
+        let name = binary_asset_name();
+        let binary = download(&client, release.asset(&name)?).await?;
+        let checksums = download(&client, release.asset(CHECKSUMS_ASSET)?).await?;
+        let signature = download(&client, release.asset(&format!("{}.minisig", CHECKSUMS_ASSET))?).await?;
+        verify(key, &checksums, &signature, &binary, &name)?;
+
+        let exe = std::env::current_exe()?;
+        replace(&exe, &binary).map_err(|e| format!("could not replace {}: {}", exe.display(), e))?;
+        println!("Updated dq {} → {} at {}", CURRENT_VERSION, release.tag_name, exe.display());
+        Ok(())
+    })
+}
@@ -0,0 +1,146 @@
+use librdap_storm::EndpointMetrics;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    audit::format_utc,
+    cache::{read_file_locked, update_file_locked},
+    config::Config,
+    instance::state_path,
+};
+
+const USAGE_FILE: &str = "usage.json";
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Requests sent to one RDAP endpoint.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct EndpointUsage {
+    pub requests: u64,
+    /// 429 answers
+    pub rate_limited: u64,
+    pub timeouts: u64,
+    /// 5xx answers and connection failures
+    pub errors: u64,
+}
+
+impl EndpointUsage {
+    fn from_metrics(metrics: &EndpointMetrics) -> Self {
+        Self {
+            requests: metrics.requests,
+            rate_limited: metrics.rate_limited,
+            timeouts: metrics.timeouts,
+            errors: metrics.errors,
+        }
+    }
+
+    fn since(self, earlier: Self) -> Self {
+        Self {
+            requests: self.requests.saturating_sub(earlier.requests),
+            rate_limited: self.rate_limited.saturating_sub(earlier.rate_limited),
+            timeouts: self.timeouts.saturating_sub(earlier.timeouts),
+            errors: self.errors.saturating_sub(earlier.errors),
+        }
+    }
+
+    fn add(&mut self, other: Self) {
+        self.requests += other.requests;
+        self.rate_limited += other.rate_limited;
+        self.timeouts += other.timeouts;
+        self.errors += other.errors;
+    }
+}
+
+/// Everything dq has asked of registries on this machine since `since`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Unix seconds of the first recorded sweep
+    pub since: u64,
+    pub sweeps: u64,
+    /// Domains probed; cached verdicts don't count
+    pub probes: u64,
+    pub endpoints: BTreeMap<String, EndpointUsage>,
+}
+
+impl UsageStats {
+    pub fn load() -> Option<Self> {
+        read_file_locked(&state_path(USAGE_FILE)?)
+    }
+
+    pub fn print(&self) {
+        let requests: u64 = self.endpoints.values().map(|e| e.requests).sum();
+        println!(
+            "Since {} UTC: {} sweeps, {} probes, {} requests to {} RDAP endpoints",
+            format_utc(self.since),
+            self.sweeps,
+            self.probes,
+            requests,
+            self.endpoints.len()
+        );
+        if self.endpoints.is_empty() {
+            return;
+        }
+        let mut endpoints: Vec<_> = self.endpoints.iter().collect();
+        endpoints.sort_by(|a, b| b.1.requests.cmp(&a.1.requests).then_with(|| a.0.cmp(b.0)));
+        println!("\n{:>9} {:>6} {:>8} {:>6}  endpoint", "requests", "429", "timeouts", "errors");
+        for (endpoint, e) in endpoints {
+            println!("{:>9} {:>6} {:>8} {:>6}  {}", e.requests, e.rate_limited, e.timeouts, e.errors, endpoint);
+        }
+    }
+}
+
+/// Adds one prober's activity to the usage file, when `[usage] enabled`.
+/// A prober's counters only grow, so each call adds what is new since the
+/// previous one.
+pub struct UsageRecorder {
+    enabled: bool,
+    reported: Mutex<BTreeMap<String, EndpointUsage>>,
+}
+
+impl UsageRecorder {
+    pub fn new(config: &Config) -> Self {
+        Self { enabled: config.usage.enabled == Some(true), reported: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn record(&self, sweeps: u64, probes: u64, metrics: &[(String, EndpointMetrics)]) {
+        let Some(path) = state_path(USAGE_FILE).filter(|_| self.enabled) else {
+            return;
+        };
+        let mut reported = self.reported.lock().unwrap();
+        let added: Vec<(String, EndpointUsage)> = metrics
+            .iter()
+            .map(|(endpoint, metrics)| {
+                let total = EndpointUsage::from_metrics(metrics);
+                let earlier = reported.insert(endpoint.clone(), total).unwrap_or_default();
+                (endpoint.clone(), total.since(earlier))
+            })
+            .collect();
+        update_file_locked(&path, |stats: &mut UsageStats| {
+            if stats.since == 0 {
+                stats.since = now_secs();
+            }
+            stats.sweeps += sweeps;
+            stats.probes += probes;
+            for (endpoint, usage) in added {
+                stats.endpoints.entry(endpoint).or_default().add(usage);
+            }
+        });
+    }
+
+    #[cfg(feature = "tui")]
+    /// Records the rest of a prober that is being replaced; the next one's
+    /// counters start from zero.
+    pub fn retire(&self, metrics: &[(String, EndpointMetrics)]) {
+        self.record(0, 0, metrics);
+        self.reported.lock().unwrap().clear();
+    }
+}
@@ -0,0 +1,444 @@
+use futures::StreamExt;
+use librdap_storm::{public_suffix, Availability, Prober};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Notify;
+
+use crate::{
+    audit::{self, AuditAction},
+    config::Config,
+    hooks::run_summary_hook,
+    instance::InstanceLock,
+    publish::Publisher,
+    snapshot::{RegisteredEntry, Snapshot},
+    usage::UsageRecorder,
+    watchlist::Watchlist,
+};
+
+pub const DEFAULT_WATCH_INTERVAL_SECS: u64 = 3600;
+/// Changes kept for `status`.
+const RECENT_CHANGES: usize = 50;
+/// Snapshot set the passes keep, for `dq new-registrations`.
+pub const SNAPSHOT_SET: &str = "watchlist";
+/// Exit code of `dq watch check-once` when a verdict changed.
+pub const CHANGED_EXIT: i32 = 3;
+/// Where the watcher leaves its status, for `dq status` once it has stopped.
+const STATUS_FILE: &str = "watch-status.json";
+
+/// A verdict from the watcher: a change found by a pass, or the answer to
+/// a `query` sent over the control socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchEvent {
+    /// Unix seconds
+    pub at: u64,
+    pub domain: String,
+    /// `null` when availability could not be determined
+    pub available: Option<bool>,
+    /// Last verdict the watcher had, `null` on first sight
+    pub previous: Option<bool>,
+    pub explanation: String,
+}
+
+/// Handed to the `[hooks] summary` command after every pass.
+#[derive(Debug, Serialize)]
+struct PassSummary<'a> {
+    /// Unix seconds
+    at: u64,
+    checked: usize,
+    /// Domains found available, changed or not
+    available: usize,
+    /// Domains that couldn't be determined
+    unknown: usize,
+    changes: &'a [&'a WatchEvent],
+}
+
+/// What `dq ctl status` prints.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatcherStatus {
+    pub pid: u32,
+    /// Unix seconds, as are the other timestamps
+    pub started_at: u64,
+    pub interval_secs: u64,
+    pub tlds: Vec<String>,
+    /// Names checked by the latest pass
+    pub names: Vec<String>,
+    pub passes: u64,
+    pub last_pass: Option<u64>,
+    pub next_pass: Option<u64>,
+    /// A pass is running right now
+    pub checking: bool,
+    /// Last determined verdict per domain, `true` when available
+    pub verdicts: BTreeMap<String, bool>,
+    /// Newest last
+    pub recent_changes: VecDeque<WatchEvent>,
+}
+
+impl WatcherStatus {
+    /// What the last watcher saved in the state dir.
+    pub fn load_saved() -> Option<Self> {
+        let content = std::fs::read_to_string(crate::instance::state_path(STATUS_FILE)?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = crate::instance::state_path(STATUS_FILE)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))?;
+        std::fs::write(path, serde_json::to_string(self)?)
+    }
+}
+
+/// A request on the control socket, one line per connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Control {
+    /// Check a name or domain now and reply with its verdicts
+    Query(String),
+    Add { names: Vec<String>, shortlist: bool },
+    Remove(Vec<String>),
+    /// Start the next pass immediately
+    Recheck,
+    Status,
+}
+
+impl Control {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let rest: Vec<String> = words.map(str::to_string).collect();
+        match command {
+            "query" if rest.len() == 1 => Ok(Control::Query(rest[0].clone())),
+            "add" => {
+                let shortlist = rest.iter().any(|w| w == "--shortlist");
+                let names: Vec<String> = rest.into_iter().filter(|w| w != "--shortlist").collect();
+                if names.is_empty() {
+                    return Err("add: no names given".to_string());
+                }
+                Ok(Control::Add { names, shortlist })
+            }
+            "remove" if !rest.is_empty() => Ok(Control::Remove(rest)),
+            "recheck" => Ok(Control::Recheck),
+            "status" => Ok(Control::Status),
+            _ => Err(format!("unknown command `{}` (query, add, remove, recheck, status)", line.trim())),
+        }
+    }
+
+    /// The line `parse` reads back.
+    pub fn to_line(&self) -> String {
+        match self {
+            Control::Query(name) => format!("query {}", name),
+            Control::Add { names, shortlist } => {
+                format!("add {}{}", if *shortlist { "--shortlist " } else { "" }, names.join(" "))
+            }
+            Control::Remove(names) => format!("remove {}", names.join(" ")),
+            Control::Recheck => "recheck".to_string(),
+            Control::Status => "status".to_string(),
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Full domains stay as they are; bare names are tried under every TLD.
+fn domains(names: impl IntoIterator<Item = String>, tlds: &[String]) -> Vec<String> {
+    let mut domains = Vec::new();
+    for name in names {
+        let name = name.trim().trim_end_matches('.').to_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        if name.contains('.') {
+            domains.push(name);
+        } else {
+            domains.extend(tlds.iter().map(|tld| format!("{}.{}", name, tld)));
+        }
+    }
+    domains
+}
+
+/// State shared by the passes and control connections.
+struct Watcher {
+    prober: Prober,
+    tlds: Vec<String>,
+    status: Mutex<WatcherStatus>,
+    recheck: Notify,
+    usage: UsageRecorder,
+    publisher: Option<Publisher>,
+}
+
+impl Watcher {
+    /// Changes the status and saves it for `dq status`.
+    fn update_status(&self, change: impl FnOnce(&mut WatcherStatus)) {
+        let mut status = self.status.lock().unwrap();
+        change(&mut status);
+        if let Err(e) = status.save() {
+            eprintln!("Warning: could not save watcher status: {}", e);
+        }
+    }
+
+    /// Verdicts for `domains`, with the registration details of the taken ones.
+    async fn check(&self, domains: Vec<String>) -> (Vec<WatchEvent>, BTreeMap<String, RegisteredEntry>) {
+        let now = now_secs();
+        let probes = domains.len() as u64;
+        let results: Vec<_> = self.prober.probe_stream_detailed(domains).collect().await;
+        self.usage.record(1, probes, &self.prober.metrics());
+        let mut status = self.status.lock().unwrap();
+        let mut registered = BTreeMap::new();
+        let events = results
+            .into_iter()
+            .map(|(result, record)| {
+                let available = match result.availability {
+                    Availability::Available => Some(true),
+                    Availability::Taken | Availability::Reserved | Availability::Premium => Some(false),
+                    Availability::Unknown { .. } => None,
+                };
+                if result.availability.is_taken() {
+                    let entry = RegisteredEntry {
+                        registrar: record.as_ref().and_then(|r| r.registrar.clone()),
+                        created: record.and_then(|r| r.created),
+                    };
+                    registered.insert(result.domain.clone(), entry);
+                }
+                let previous = match available {
+                    Some(available) => status.verdicts.insert(result.domain.clone(), available),
+                    None => status.verdicts.get(&result.domain).copied(),
+                };
+                WatchEvent { at: now, domain: result.domain, available, previous, explanation: result.explanation }
+            })
+            .collect();
+        (events, registered)
+    }
+
+    /// Replies to one control request, as JSON lines.
+    async fn handle(&self, request: Control) -> Vec<String> {
+        let ok = |detail: String| serde_json::json!({ "ok": true, "detail": detail }).to_string();
+        match request {
+            Control::Query(name) => self
+                .check(domains([name], &self.tlds))
+                .await
+                .0
+                .iter()
+                .filter_map(|event| serde_json::to_string(event).ok())
+                .collect(),
+            Control::Add { names, shortlist } => {
+                let saved = Watchlist::load().and_then(|mut watchlist| {
+                    watchlist.add(&names, shortlist);
+                    watchlist.save().map_err(|e| e.to_string())
+                });
+                if let Err(e) = saved {
+                    return vec![serde_json::json!({ "error": e }).to_string()];
+                }
+                let list = if shortlist { "shortlist" } else { "watchlist" };
+                audit::record(AuditAction::WatchAdd, format!("{} to {}", names.join(", "), list));
+                vec![ok(format!("added {} to the {}, checked next pass", names.join(", "), list))]
+            }
+            Control::Remove(names) => {
+                let saved = Watchlist::load().and_then(|mut watchlist| {
+                    watchlist.remove(&names);
+                    watchlist.save().map_err(|e| e.to_string())
+                });
+                if let Err(e) = saved {
+                    return vec![serde_json::json!({ "error": e }).to_string()];
+                }
+                audit::record(AuditAction::WatchRemove, names.join(", "));
+                vec![ok(format!("removed {}", names.join(", ")))]
+            }
+            Control::Recheck => {
+                self.recheck.notify_one();
+                vec![ok("pass started".to_string())]
+            }
+            Control::Status => {
+                let status = self.status.lock().unwrap().clone();
+                vec![serde_json::to_string(&status).unwrap_or_default()]
+            }
+        }
+    }
+
+    /// Checks the watchlist once, printing (and publishing) what changed.
+    /// Returns how many verdicts changed.
+    async fn pass(&self, config: &Config) -> io::Result<usize> {
+        let interval = config.watch_interval();
+        let watchlist = Watchlist::load().unwrap_or_else(|e| {
+            eprintln!("Warning: {}; checking only the names in the config", e);
+            Watchlist::default()
+        });
+        let names: BTreeSet<String> = watchlist
+            .names
+            .into_iter()
+            .chain(watchlist.shortlist)
+            .chain(config.watch.names.iter().cloned())
+            .collect();
+        self.update_status(|status| {
+            status.checking = true;
+            status.names = names.iter().cloned().collect();
+        });
+
+        let (events, mut registered) = self.check(domains(names, &self.tlds)).await;
+        if events.iter().any(|e| e.available.is_some()) {
+            // Undetermined domains keep their last snapshot entry, so
+            // they don't show up as new once they answer again
+            let previous = Snapshot::latest(SNAPSHOT_SET, 1).pop().unwrap_or_default();
+            for event in events.iter().filter(|e| e.available.is_none()) {
+                if let Some(entry) = previous.registered.get(&event.domain) {
+                    registered.insert(event.domain.clone(), entry.clone());
+                }
+            }
+            let snapshot = Snapshot { at: now_secs(), registered };
+            if let Err(e) = snapshot.save(SNAPSHOT_SET) {
+                eprintln!("Warning: could not save snapshot: {}", e);
+            }
+        }
+        let changed: Vec<&WatchEvent> = events
+            .iter()
+            .filter(|e| e.available.is_some() && e.available != e.previous)
+            .collect();
+        for event in &changed {
+            let line = serde_json::to_string(event)?;
+            if let Some(publisher) = &self.publisher {
+                publisher.send(public_suffix(&event.domain).unwrap_or(""), "watch", line.clone()).await;
+            }
+            println!("{}", line);
+        }
+        io::stdout().flush()?;
+        eprintln!("Checked {} domains, {} changed", events.len(), changed.len());
+        let summary = PassSummary {
+            at: now_secs(),
+            checked: events.len(),
+            available: events.iter().filter(|e| e.available == Some(true)).count(),
+            unknown: events.iter().filter(|e| e.available.is_none()).count(),
+            changes: &changed,
+        };
+        run_summary_hook(config.hooks.summary.as_deref(), &summary).await;
+
+        let count = changed.len();
+        self.update_status(|status| {
+            let now = now_secs();
+            status.checking = false;
+            status.passes += 1;
+            status.last_pass = Some(now);
+            status.next_pass = Some(now + interval.as_secs());
+            status.recent_changes.extend(changed.into_iter().cloned());
+            let excess = status.recent_changes.len().saturating_sub(RECENT_CHANGES);
+            status.recent_changes.drain(..excess);
+        });
+        Ok(count)
+    }
+
+    async fn passes(&self, config: &Config) -> io::Result<()> {
+        let interval = config.watch_interval();
+        loop {
+            self.pass(config).await?;
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = self.recheck.notified() => {}
+            }
+        }
+    }
+}
+
+/// Serves the control socket: `query`, `add`, `remove`, `recheck` and
+/// `status`, so scripts and other dq processes drive the running watcher
+/// instead of probing alongside it.
+#[cfg(unix)]
+async fn serve(lock: &InstanceLock, watcher: Arc<Watcher>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = match crate::instance::listen(lock) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Warning: no control socket ({}), `dq ctl` won't reach this watcher", e);
+            return std::future::pending().await;
+        }
+    };
+    while let Ok((stream, _)) = listener.accept().await {
+        let watcher = watcher.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let Ok(Some(line)) = BufReader::new(reader).lines().next_line().await else {
+                return;
+            };
+            let replies = match Control::parse(&line) {
+                Ok(request) => watcher.handle(request).await,
+                Err(e) => vec![serde_json::json!({ "error": e }).to_string()],
+            };
+            for mut reply in replies {
+                reply.push('\n');
+                if writer.write_all(reply.as_bytes()).await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+async fn serve(_lock: &InstanceLock, _watcher: Arc<Watcher>) {
+    std::future::pending().await
+}
+
+/// Re-checks the watchlist every `[watch] interval_secs`, printing each
+/// changed verdict as an NDJSON `WatchEvent` (and publishing it with
+/// `[publish]`, `{run_id}` being "watch"). The watchlist is reloaded
+/// every pass, so `dq watch add` takes effect without a restart.
+pub async fn run(lock: InstanceLock, prober: Prober, config: &Config, tlds: Vec<String>) -> io::Result<()> {
+    let status = WatcherStatus {
+        pid: std::process::id(),
+        started_at: now_secs(),
+        interval_secs: config.watch_interval().as_secs(),
+        tlds: tlds.clone(),
+        ..WatcherStatus::default()
+    };
+    let publisher = Publisher::connect(&config.publish).await.transpose().map_err(io::Error::other)?;
+    let watcher = Arc::new(Watcher {
+        prober,
+        tlds,
+        status: Mutex::new(status),
+        recheck: Notify::new(),
+        usage: UsageRecorder::new(config),
+        publisher,
+    });
+
+    tokio::select! {
+        result = watcher.passes(config) => result,
+        _ = serve(&lock, watcher.clone()) => Ok(()),
+    }
+}
+
+/// One pass for an external scheduler (cron, a Kubernetes CronJob): the
+/// verdicts of the previous run, daemon or one-shot, are the baseline,
+/// and this pass's become the next one's. Returns how many changed.
+pub async fn check_once(_lock: InstanceLock, prober: Prober, config: &Config, tlds: Vec<String>) -> io::Result<usize> {
+    let saved = WatcherStatus::load_saved().unwrap_or_default();
+    let status = WatcherStatus {
+        pid: std::process::id(),
+        started_at: now_secs(),
+        interval_secs: 0,
+        tlds: tlds.clone(),
+        ..saved
+    };
+    let publisher = Publisher::connect(&config.publish).await.transpose().map_err(io::Error::other)?;
+    let mut watcher = Watcher {
+        prober,
+        tlds,
+        status: Mutex::new(status),
+        recheck: Notify::new(),
+        usage: UsageRecorder::new(config),
+        publisher,
+    };
+    let changed = watcher.pass(config).await?;
+    // Nothing is scheduled; the next pass is up to whoever ran this one
+    watcher.update_status(|status| status.next_pass = None);
+    if let Some(publisher) = watcher.publisher.take() {
+        publisher.finish().await;
+    }
+    Ok(changed)
+}
@@ -0,0 +1,111 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, path::{Path, PathBuf}};
+
+/// Names being monitored plus a shortlist of favourites, kept in the user
+/// state dir and portable between machines via `dq watch export/import`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Watchlist {
+    #[serde(default)]
+    pub names: BTreeSet<String>,
+    #[serde(default)]
+    pub shortlist: BTreeSet<String>,
+}
+
+/// File format for export/import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    Toml,
+    Json,
+}
+
+impl ListFormat {
+    /// Guesses the format from a file extension, defaulting to TOML.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ListFormat::Json,
+            _ => ListFormat::Toml,
+        }
+    }
+}
+
+fn watchlist_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|p| p.join("dq").join("watchlist.json"))
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_lowercase()
+}
+
+impl Watchlist {
+    /// The saved watchlist, empty if there is none yet. A file that can't
+    /// be read or parsed is an error rather than an empty list, so nothing
+    /// saves over it; it is also copied to `watchlist.json.bad`.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = watchlist_path() else {
+            return Ok(Self::default());
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("could not read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&content).map_err(|e| {
+            let backup = path.with_extension("json.bad");
+            let _ = std::fs::copy(&path, &backup);
+            format!(
+                "{} is not a valid watchlist ({}); a copy is saved as {}, fix or remove the original",
+                path.display(),
+                e,
+                backup.display()
+            )
+        })
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = watchlist_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn add(&mut self, names: &[String], shortlist: bool) {
+        let target = if shortlist { &mut self.shortlist } else { &mut self.names };
+        target.extend(names.iter().map(|n| normalize(n)).filter(|n| !n.is_empty()));
+    }
+
+    /// Removes names from both lists.
+    pub fn remove(&mut self, names: &[String]) {
+        for name in names.iter().map(|n| normalize(n)) {
+            self.names.remove(&name);
+            self.shortlist.remove(&name);
+        }
+    }
+
+    /// Adds everything in `other`; returns how many names were new.
+    pub fn merge(&mut self, other: Watchlist) -> usize {
+        let before = self.names.len() + self.shortlist.len();
+        self.names.extend(other.names.iter().map(|n| normalize(n)));
+        self.shortlist.extend(other.shortlist.iter().map(|n| normalize(n)));
+        self.names.len() + self.shortlist.len() - before
+    }
+
+    pub fn export(&self, format: ListFormat) -> Result<String, String> {
+        match format {
+            ListFormat::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+            ListFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn parse(content: &str, format: ListFormat) -> Result<Self, String> {
+        match format {
+            ListFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ListFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use futures::StreamExt;
+use librdap_storm::{Availability, Prober};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    instance::state_path,
+    interrupt::{Interrupt, INTERRUPTED_EXIT},
+    usage::UsageRecorder,
+};
+
+const CHECKPOINT_FILE: &str = "words-checkpoint.json";
+/// Results between checkpoint saves.
+const CHECKPOINT_EVERY: usize = 200;
+
+/// Where the last `dq words` run got to. Running the same list, lengths
+/// and TLDs again carries on from `done`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub list: PathBuf,
+    pub min: usize,
+    pub max: usize,
+    pub tlds: Vec<String>,
+    /// Words in the filtered list, to notice a list that changed since
+    pub total: usize,
+    /// Words whose every domain has been probed
+    pub done: usize,
+}
+
+impl Checkpoint {
+    /// A fresh run over `total` words.
+    pub fn new(list: &Path, (min, max): (usize, usize), tlds: &[String], total: usize) -> Self {
+        Self {
+            list: list.canonicalize().unwrap_or_else(|_| list.to_path_buf()),
+            min,
+            max,
+            tlds: tlds.to_vec(),
+            total,
+            done: 0,
+        }
+    }
+
+    fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(state_path(CHECKPOINT_FILE)?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = state_path(CHECKPOINT_FILE) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    fn clear() {
+        if let Some(path) = state_path(CHECKPOINT_FILE) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    fn same_run(&self, other: &Checkpoint) -> bool {
+        (&self.list, self.min, self.max, &self.tlds, self.total) == (&other.list, other.min, other.max, &other.tlds, other.total)
+    }
+}
+
+/// The words of a list file `min` to `max` letters long, lowercased,
+/// deduplicated and sorted. Entries that aren't plain lowercase letters
+/// (proper nouns, possessives, phrases) are left out.
+pub fn read_words(list: &Path, min: usize, max: usize) -> io::Result<Vec<String>> {
+    let content = std::fs::read_to_string(list)?;
+    let words: BTreeSet<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|word| (min..=max).contains(&word.len()) && word.chars().all(|c| c.is_ascii_lowercase()))
+        .collect();
+    Ok(words.into_iter().map(str::to_string).collect())
+}
+
+/// Printed by `dq words --json` for every domain probed.
+#[derive(Debug, Serialize)]
+struct WordResult {
+    word: String,
+    domain: String,
+    /// `null` when it couldn't be determined
+    available: Option<bool>,
+    explanation: String,
+}
+
+/// Probes `word.tld` for every word and each of the checkpoint's TLDs,
+/// printing the available domains (every verdict with `json`) as they
+/// come in. Resumes an interrupted run with the same arguments unless
+/// `restart`.
+pub async fn run(
+    prober: Prober,
+    words: Vec<String>,
+    mut checkpoint: Checkpoint,
+    restart: bool,
+    json: bool,
+    usage: &UsageRecorder,
+) -> io::Result<()> {
+    if let Some(saved) = Checkpoint::load().filter(|saved| !restart && saved.done > 0 && saved.same_run(&checkpoint)) {
+        checkpoint.done = saved.done;
+        eprintln!("Resuming after word {} of {} ({})", saved.done, words.len(), words[saved.done - 1]);
+    }
+    let tlds = checkpoint.tlds.clone();
+
+    // Words are done in list order once all their TLDs have answered
+    let start = checkpoint.done;
+    let mut pending: Vec<usize> = vec![tlds.len(); words.len()];
+    let mut word_of = HashMap::new();
+    let mut domains = Vec::new();
+    for (i, word) in words.iter().enumerate().skip(start) {
+        for tld in &tlds {
+            let domain = format!("{}.{}", word, tld);
+            word_of.insert(domain.clone(), i);
+            domains.push(domain);
+        }
+    }
+
+    let interrupt = Interrupt::install();
+    let probes = domains.len() as u64;
+    let mut results = prober.probe_stream(interrupt.guard(domains));
+    let (mut seen, mut available) = (0usize, 0usize);
+    while let Some(result) = results.next().await {
+        let Some(&i) = word_of.get(&result.domain) else {
+            continue;
+        };
+        seen += 1;
+        let verdict = match result.availability {
+            Availability::Available => Some(true),
+            Availability::Taken | Availability::Reserved | Availability::Premium => Some(false),
+            Availability::Unknown { .. } => None,
+        };
+        if verdict == Some(true) {
+            available += 1;
+        }
+        if json {
+            let line = WordResult {
+                word: words[i].clone(),
+                domain: result.domain,
+                available: verdict,
+                explanation: result.explanation,
+            };
+            println!("{}", serde_json::to_string(&line)?);
+        } else if verdict == Some(true) {
+            println!("{}", result.domain);
+        }
+        io::stdout().flush()?;
+
+        pending[i] -= 1;
+        while checkpoint.done < words.len() && pending[checkpoint.done] == 0 {
+            checkpoint.done += 1;
+        }
+        if seen % CHECKPOINT_EVERY == 0 {
+            checkpoint.save()?;
+        }
+    }
+    drop(results);
+    usage.record(1, seen as u64, &prober.metrics());
+
+    if checkpoint.done < words.len() {
+        checkpoint.save()?;
+        eprintln!(
+            "{} available; stopped after word {} of {}, run the same command again to resume",
+            available,
+            checkpoint.done,
+            words.len()
+        );
+        if interrupt.is_set() {
+            std::process::exit(INTERRUPTED_EXIT);
+        }
+        return Ok(());
+    }
+    Checkpoint::clear();
+    eprintln!("{} words, {} domains probed: {} available", words.len() - start, probes, available);
+    Ok(())
+}
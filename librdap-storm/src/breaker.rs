@@ -0,0 +1,78 @@
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct Circuit {
+    /// Failures since the last answer
+    failures: u32,
+    /// Set while open; once past, the next request is let through as a trial
+    open_until: Option<Instant>,
+}
+
+/// Per RDAP endpoint: after `threshold` timeouts, connection errors or 5xx
+/// in a row, requests are skipped for `cooldown`. Then a single trial
+/// request is let through; an answer closes the circuit again, another
+/// failure keeps it open for a further `cooldown`.
+pub struct EndpointBreakers {
+    circuits: DashMap<String, Circuit>,
+    threshold: u32,
+    cooldown: Duration,
+}
+
+impl EndpointBreakers {
+    /// A `threshold` of 0 never opens a circuit.
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            circuits: DashMap::new(),
+            threshold,
+            cooldown,
+        }
+    }
+
+    /// Whether a request to `endpoint` may go out now. Letting the trial
+    /// through holds the circuit open for everyone else, so a trial that
+    /// never reports back costs one more `cooldown`, not a stuck endpoint.
+    pub fn allow(&self, endpoint: &str) -> bool {
+        let Some(mut circuit) = self.circuits.get_mut(endpoint) else {
+            return true;
+        };
+        let now = Instant::now();
+        match circuit.open_until {
+            None => true,
+            Some(until) if until > now => false,
+            Some(_) => {
+                circuit.open_until = Some(now + self.cooldown);
+                true
+            }
+        }
+    }
+
+    /// Records how a request to `endpoint` went.
+    pub fn record(&self, endpoint: &str, failed: bool) {
+        if self.threshold == 0 {
+            return;
+        }
+        if !failed {
+            self.circuits.remove(endpoint);
+            return;
+        }
+        let mut circuit = self.circuits.entry(endpoint.to_string()).or_default();
+        circuit.failures += 1;
+        if circuit.failures >= self.threshold || circuit.open_until.is_some() {
+            if circuit.open_until.is_none() {
+                tracing::warn!(endpoint, failures = circuit.failures, "circuit open, skipping endpoint");
+            }
+            circuit.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+
+    /// Endpoints currently being skipped.
+    pub fn open(&self) -> Vec<String> {
+        let now = Instant::now();
+        self.circuits
+            .iter()
+            .filter(|c| c.open_until.is_some_and(|until| until > now))
+            .map(|c| c.key().clone())
+            .collect()
+    }
+}
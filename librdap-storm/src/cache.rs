@@ -0,0 +1,102 @@
+use crate::{
+    record::DomainRecord,
+    types::{Availability, ProbeResult, ProbeSource},
+};
+use dashmap::DashMap;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Entries past which expired ones are swept out, every `SWEEP_EVERY`
+/// inserts.
+const SWEEP_AT: usize = 4096;
+const SWEEP_EVERY: usize = 256;
+/// Most entries kept; past it the oldest go, down to three quarters.
+const MAX_ENTRIES: usize = 65_536;
+
+struct Entry {
+    at: Instant,
+    result: ProbeResult,
+    /// From a detailed probe; plain ones can't stand in for those
+    detailed: bool,
+    record: Option<DomainRecord>,
+}
+
+/// Available/taken verdicts per domain, reused for `ttl`. Unknown results
+/// are never kept, so a flaky endpoint is asked again.
+pub struct ProbeCache {
+    entries: DashMap<String, Entry>,
+    ttl: Duration,
+    inserts: AtomicUsize,
+}
+
+impl ProbeCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            ttl,
+            inserts: AtomicUsize::new(0),
+        }
+    }
+
+    /// The verdict for `domain` if it is fresh, and for `detailed` probes
+    /// came with its record.
+    pub fn get(&self, domain: &str, detailed: bool) -> Option<(ProbeResult, Option<DomainRecord>)> {
+        let entry = self.entries.get(domain)?;
+        let age = entry.at.elapsed();
+        if age >= self.ttl {
+            drop(entry);
+            self.entries.remove(domain);
+            return None;
+        }
+        if detailed && !entry.detailed {
+            return None;
+        }
+        let result = ProbeResult {
+            domain: domain.to_string(),
+            duration: Duration::ZERO,
+            explanation: format!("cached {}s ago: {}", age.as_secs(), entry.result.explanation),
+            source: ProbeSource::Cache,
+            ..entry.result.clone()
+        };
+        Some((result, entry.record.clone()))
+    }
+
+    pub fn insert(&self, result: &ProbeResult, detailed: bool, record: Option<&DomainRecord>) {
+        if matches!(result.availability, Availability::Unknown { .. }) {
+            return;
+        }
+        let inserts = self.inserts.fetch_add(1, Ordering::Relaxed) + 1;
+        let len = self.entries.len();
+        if len >= MAX_ENTRIES || (len >= SWEEP_AT && inserts.is_multiple_of(SWEEP_EVERY)) {
+            self.sweep();
+        }
+        self.entries.insert(
+            result.domain.clone(),
+            Entry {
+                at: Instant::now(),
+                result: result.clone(),
+                detailed,
+                record: record.cloned(),
+            },
+        );
+    }
+
+    /// Drops expired entries, then the oldest while at `MAX_ENTRIES`.
+    fn sweep(&self) {
+        self.entries.retain(|_, e| e.at.elapsed() < self.ttl);
+        let len = self.entries.len();
+        if len < MAX_ENTRIES {
+            return;
+        }
+        let mut times: Vec<Instant> = self.entries.iter().map(|e| e.at).collect();
+        let (_, cutoff, _) = times.select_nth_unstable(len - MAX_ENTRIES * 3 / 4 - 1);
+        let cutoff = *cutoff;
+        self.entries.retain(|_, e| e.at > cutoff);
+    }
+
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,57 @@
+use reqwest::{header::CONTENT_TYPE, Client, Response, StatusCode};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Registered domain used to check that RDAP answers are real.
+pub const CANARY_DOMAIN: &str = "example.com";
+
+/// Domain expected to be registered under `tld`: registries keep `nic.<tld>`
+/// for themselves.
+pub fn tld_canary(tld: &str) -> String {
+    format!("nic.{}", tld)
+}
+
+#[derive(Debug, Error)]
+pub enum CanaryError {
+    #[error(
+        "RDAP requests are being intercepted ({0}); sign in to the network (captive portal?) and retry"
+    )]
+    Intercepted(String),
+    #[error("Proxy {0} is not usable: {1}")]
+    Proxy(String, String),
+}
+
+/// True when a response looks like RDAP rather than a login page.
+pub(crate) fn is_rdap_response(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.contains("json"))
+}
+
+/// Looks up the canary on `endpoint`. A network failure is not treated as
+/// interception; the sweep reports those per domain.
+pub(crate) async fn check_canary(
+    client: &Client,
+    endpoint: &str,
+    timeout: Duration,
+) -> Result<(), CanaryError> {
+    let url = format!("{}/domain/{}", endpoint, CANARY_DOMAIN);
+    let Ok(response) = client.get(&url).timeout(timeout).send().await else {
+        return Ok(());
+    };
+
+    let expected_host = reqwest::Url::parse(endpoint).ok().and_then(|u| u.host_str().map(str::to_string));
+    let host = response.url().host_str().map(str::to_string);
+    if host != expected_host {
+        return Err(CanaryError::Intercepted(format!(
+            "redirected to {}",
+            host.unwrap_or_else(|| response.url().to_string())
+        )));
+    }
+    if response.status() == StatusCode::OK && !is_rdap_response(&response) {
+        return Err(CanaryError::Intercepted("HTML instead of RDAP".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::types::{Availability, Confidence};
+use futures::future::BoxFuture;
+use std::{fmt, sync::Arc};
+
+/// A source of verdicts outside the crate, such as a registrar's API or an
+/// internal EPP gateway, added to a probe with `CheckStep::Custom`.
+pub trait Checker: Send + Sync {
+    /// Names the checker in explanations, e.g. "epp-gateway".
+    fn name(&self) -> &str;
+
+    /// Availability of `domain`, in ASCII (punycode) form. `Unknown` hands
+    /// the domain on to the next step of the chain.
+    fn check<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Availability>;
+
+    /// How far its verdicts can be trusted.
+    fn confidence(&self) -> Confidence {
+        Confidence::Medium
+    }
+}
+
+/// One step of `ProbeConfig::chain`. Steps are asked in order until one
+/// comes back with anything but `Unknown`.
+#[derive(Clone)]
+pub enum CheckStep {
+    /// NS lookup; a delegated domain is taken, anything else goes on
+    Dns,
+    /// The registry's RDAP endpoints, with canary check, confirmation and
+    /// retries as configured
+    Rdap,
+    /// The TLD's WHOIS server; skipped with `whois_fallback` off or once
+    /// port 43 turned out to be blocked
+    Whois,
+    Custom(Arc<dyn Checker>),
+}
+
+impl CheckStep {
+    /// The chain used while `ProbeConfig::chain` is empty.
+    pub fn default_chain(dns_first: bool) -> Vec<CheckStep> {
+        let mut chain = Vec::new();
+        if dns_first {
+            chain.push(CheckStep::Dns);
+        }
+        chain.extend([CheckStep::Rdap, CheckStep::Whois]);
+        chain
+    }
+}
+
+impl fmt::Debug for CheckStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStep::Dns => write!(f, "Dns"),
+            CheckStep::Rdap => write!(f, "Rdap"),
+            CheckStep::Whois => write!(f, "Whois"),
+            CheckStep::Custom(checker) => write!(f, "Custom({})", checker.name()),
+        }
+    }
+}
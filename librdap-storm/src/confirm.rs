@@ -0,0 +1,50 @@
+use crate::{
+    dns::is_delegated,
+    resolver::Resolver,
+    types::{Availability, ProbeSource},
+    whois::WhoisClient,
+};
+use std::time::Duration;
+
+/// Re-checks an RDAP "available" verdict through another backend: WHOIS
+/// where a server is known, then DNS. WHOIS may confirm it, or overrule it
+/// as taken, reserved or premium. DNS can only overrule it: a delegated or
+/// resolving name is registered, but a miss (or a timeout) proves nothing.
+/// Returns the verdict with how it was confirmed or overruled, and by which
+/// backend; `None` when neither could tell.
+pub(crate) async fn confirm_available(
+    whois: Option<&WhoisClient>,
+    resolver: &Resolver,
+    domain: &str,
+    timeout: Duration,
+) -> Option<(Availability, String, ProbeSource)> {
+    if let Some(whois) = whois {
+        let answer = whois.check(domain, timeout).await;
+        match answer.availability {
+            Availability::Available => {
+                return Some((Availability::Available, format!("confirmed: {}", answer.explanation), ProbeSource::Whois))
+            }
+            held @ (Availability::Taken | Availability::Reserved | Availability::Premium) => {
+                return Some((held, format!("overruled: {}", answer.explanation), ProbeSource::Whois))
+            }
+            Availability::Unknown { .. } => {}
+        }
+    }
+
+    let overruled = match is_delegated(resolver, domain, timeout).await {
+        Some(true) => "overruled: domain is delegated in DNS (NS records)",
+        Some(false) => return None,
+        None if resolves(resolver, domain, timeout).await => "overruled: domain resolves in DNS",
+        None => return None,
+    };
+    Some((Availability::Taken, overruled.to_string(), ProbeSource::Dns))
+}
+
+/// True when the domain has address records. Only registered domains can
+/// resolve; a miss proves nothing (parked names often have no A record).
+pub(crate) async fn resolves(resolver: &Resolver, domain: &str, timeout: Duration) -> bool {
+    match tokio::time::timeout(timeout, resolver.lookup(domain, 0)).await {
+        Ok(Ok(addrs)) => !addrs.is_empty(),
+        _ => false,
+    }
+}
@@ -0,0 +1,145 @@
+use crate::types::{Availability, Confidence, ProbeResult, ProbeSource};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS results (
+    domain TEXT NOT NULL,
+    checked_at INTEGER NOT NULL,
+    availability TEXT NOT NULL,
+    reason TEXT,
+    endpoint TEXT,
+    explanation TEXT NOT NULL,
+    source TEXT NOT NULL,
+    confidence TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS results_domain ON results (domain, checked_at);
+";
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Every probe result, kept in an SQLite file across runs. Only definite
+/// verdicts are replayed; unknowns are kept for the record. Queries run on
+/// the blocking pool, since another dq process can hold the file for up to
+/// the busy timeout.
+pub struct ResultDb {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl ResultDb {
+    /// Opens or creates the file, dropping rows older than `max_age`, which
+    /// would never be replayed.
+    pub fn open(path: &Path, max_age: Option<Duration>) -> rusqlite::Result<Self> {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let conn = Connection::open(path)?;
+        // Other dq processes may be writing the same file
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        if let Some(age) = max_age {
+            let pruned = conn.execute(
+                "DELETE FROM results WHERE checked_at < ?1",
+                params![now_secs().saturating_sub(age.as_secs()) as i64],
+            )?;
+            tracing::debug!(pruned, "old results dropped from result db");
+        }
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// The newest verdict for `domain`, if it is no older than `max_age`.
+    pub async fn lookup(&self, domain: &str, max_age: Option<Duration>) -> Option<ProbeResult> {
+        let conn = Arc::clone(&self.conn);
+        let domain = domain.to_string();
+        tokio::task::spawn_blocking(move || Self::lookup_blocking(&conn.lock().unwrap(), &domain, max_age))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    fn lookup_blocking(conn: &Connection, domain: &str, max_age: Option<Duration>) -> Option<ProbeResult> {
+        let now = now_secs();
+        let since = max_age.map_or(0, |age| now.saturating_sub(age.as_secs()));
+        let row = conn
+            .query_row(
+                "SELECT checked_at, availability, endpoint, explanation, confidence FROM results
+                 WHERE domain = ?1 AND availability != 'unknown' AND checked_at >= ?2
+                 ORDER BY checked_at DESC LIMIT 1",
+                params![domain, since as i64],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                },
+            )
+            .optional();
+        let (checked_at, availability, endpoint, explanation, confidence) = match row {
+            Ok(row) => row?,
+            Err(e) => {
+                tracing::warn!(error = %e, "result db lookup failed");
+                return None;
+            }
+        };
+        let availability = match availability.as_str() {
+            "available" => Availability::Available,
+            "reserved" => Availability::Reserved,
+            "premium" => Availability::Premium,
+            _ => Availability::Taken,
+        };
+        Some(ProbeResult {
+            domain: domain.to_string(),
+            availability,
+            duration: Duration::ZERO,
+            headers: Vec::new(),
+            endpoint,
+            explanation: format!("recorded {}s ago: {}", now.saturating_sub(checked_at as u64), explanation),
+            source: ProbeSource::Cache,
+            confidence: Confidence::from_name(&confidence).unwrap_or(Confidence::Medium),
+            raw: None,
+        })
+    }
+
+    pub async fn record(&self, result: &ProbeResult) {
+        let (availability, reason) = match &result.availability {
+            Availability::Available => ("available", None),
+            Availability::Taken => ("taken", None),
+            Availability::Reserved => ("reserved", None),
+            Availability::Premium => ("premium", None),
+            Availability::Unknown { reason } => ("unknown", Some(reason.to_string())),
+        };
+        let conn = Arc::clone(&self.conn);
+        let row = (
+            result.domain.clone(),
+            result.endpoint.clone(),
+            result.explanation.clone(),
+            result.source.name(),
+            result.confidence.name(),
+        );
+        let inserted = tokio::task::spawn_blocking(move || {
+            let (domain, endpoint, explanation, source, confidence) = row;
+            conn.lock().unwrap().execute(
+                "INSERT INTO results (domain, checked_at, availability, reason, endpoint, explanation, source, confidence)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![domain, now_secs() as i64, availability, reason, endpoint, explanation, source, confidence],
+            )
+        })
+        .await;
+        if let Ok(Err(e)) = inserted {
+            tracing::warn!(error = %e, "could not record result");
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::types::to_ascii_domain;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+/// The inputs `dedupe_domains` merged, filled in as its iterator is drawn
+/// from. Complete once the iterator is exhausted.
+#[derive(Debug, Clone, Default)]
+pub struct MergedInputs(Arc<Mutex<BTreeMap<String, Vec<String>>>>);
+
+impl MergedInputs {
+    /// Every input of each domain given more than once, first one first,
+    /// keyed by the domain's ASCII (punycode) form.
+    pub fn get(&self) -> BTreeMap<String, Vec<String>> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Each registrable domain once, compared in its ASCII form, so case, a
+/// trailing dot and IDN vs punycode spellings (`münchen.de`,
+/// `xn--mnchen-3ya.de`) don't make it probed twice. The first spelling is
+/// kept, lowercased and without the trailing dot. Lazy, so `domains` is
+/// only drawn from as probes are started.
+pub fn dedupe_domains<I: IntoIterator<Item = String>>(domains: I) -> (impl Iterator<Item = String>, MergedInputs) {
+    let merged = MergedInputs::default();
+    let inputs = merged.clone();
+    let mut first = HashMap::new();
+    let unique = domains.into_iter().filter_map(move |input| {
+        let domain = input.trim_end_matches('.').to_lowercase();
+        let key = to_ascii_domain(&domain).unwrap_or_else(|_| domain.clone());
+        match first.get(&key) {
+            None => {
+                first.insert(key, input);
+                Some(domain)
+            }
+            Some(original) => {
+                let mut merged = inputs.0.lock().unwrap();
+                merged.entry(key).or_insert_with(|| vec![original.clone()]).push(input);
+                None
+            }
+        }
+    });
+    (unique, merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_case_dot_and_idn_spellings() {
+        let inputs = ["München.de", "foo.com", "xn--mnchen-3ya.de", "FOO.com.", "bar.com"];
+        let (unique, merged) = dedupe_domains(inputs.map(String::from));
+        assert_eq!(unique.collect::<Vec<_>>(), ["münchen.de", "foo.com", "bar.com"]);
+        let merged = merged.get();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["xn--mnchen-3ya.de"], ["München.de", "xn--mnchen-3ya.de"]);
+        assert_eq!(merged["foo.com"], ["foo.com", "FOO.com."]);
+    }
+}
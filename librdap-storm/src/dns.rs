@@ -0,0 +1,89 @@
+use crate::resolver::Resolver;
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::net::UdpSocket;
+
+const DNS_PORT: u16 = 53;
+const TYPE_NS: u16 = 2;
+const TYPE_DS: u16 = 43;
+const CLASS_IN: u16 = 1;
+pub(crate) const RCODE_NXDOMAIN: u8 = 3;
+
+/// First `nameserver` from /etc/resolv.conf. Other platforms have no such
+/// file, and callers fall back to address lookups.
+fn system_resolver() -> Option<SocketAddr> {
+    let conf = std::fs::read_to_string("/etc/resolv.conf").ok()?;
+    conf.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        (words.next()? == "nameserver").then_some(())?;
+        let ip: IpAddr = words.next()?.parse().ok()?;
+        Some(SocketAddr::new(ip, DNS_PORT))
+    })
+}
+
+fn query(id: u16, domain: &str, record_type: u16) -> Option<Vec<u8>> {
+    let mut packet = Vec::with_capacity(32 + domain.len());
+    packet.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired; one question
+    packet.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    for label in domain.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return None;
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&record_type.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Some(packet)
+}
+
+/// Asks the resolver for the domain's records of `record_type`; the
+/// response code and how many answers came back.
+async fn ask(resolver: &Resolver, domain: &str, record_type: u16, timeout: Duration) -> Option<(u8, u16)> {
+    if !resolver.is_system() {
+        return resolver.records(domain, record_type, timeout).await;
+    }
+    let resolver = system_resolver()?;
+    let id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u16)
+        .unwrap_or(0);
+    let query = query(id, domain, record_type)?;
+
+    let bind: SocketAddr = if resolver.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+    let socket = UdpSocket::bind(bind).await.ok()?;
+    socket.connect(resolver).await.ok()?;
+    socket.send(&query).await.ok()?;
+
+    let mut response = [0u8; 512];
+    let len = tokio::time::timeout(timeout, socket.recv(&mut response)).await.ok()?.ok()?;
+    if len < 12 || response[..2] != id.to_be_bytes() || response[2] & 0x80 == 0 {
+        return None;
+    }
+    Some((response[3] & 0x0f, u16::from_be_bytes([response[6], response[7]])))
+}
+
+/// Asks the resolver for the domain's NS records. `Some(true)` when
+/// it is delegated (so registered), `Some(false)` on NXDOMAIN or an empty
+/// answer, `None` when DNS couldn't tell.
+pub(crate) async fn is_delegated(resolver: &Resolver, domain: &str, timeout: Duration) -> Option<bool> {
+    match ask(resolver, domain, TYPE_NS, timeout).await? {
+        (0, answers) => Some(answers > 0),
+        (RCODE_NXDOMAIN, _) => Some(false),
+        // SERVFAIL, REFUSED, ...: a lame delegation looks the same as a broken resolver
+        _ => None,
+    }
+}
+
+/// Whether the parent zone has DS records for the domain, which is what
+/// makes its delegation DNSSEC-signed. `None` when DNS couldn't tell.
+pub(crate) async fn is_signed(resolver: &Resolver, domain: &str, timeout: Duration) -> Option<bool> {
+    match ask(resolver, domain, TYPE_DS, timeout).await? {
+        (0, answers) => Some(answers > 0),
+        _ => None,
+    }
+}
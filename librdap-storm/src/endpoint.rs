@@ -1,9 +1,13 @@
+use crate::{psl::public_suffix, types::BootstrapState};
 use dashmap::DashMap;
 use reqwest::Client;
-use serde::Deserialize;
+use std::{path::Path, sync::Mutex, time::Duration};
 use thiserror::Error;
 
 const IANA_BOOTSTRAP_URL: &str = "https://data.iana.org/rdap/dns.json";
+/// Endpoints for popular TLDs, compiled in so probing works when IANA
+/// can't be reached. Refresh it from the live file now and then.
+const BUNDLED_BOOTSTRAP: &str = include_str!("bootstrap.json");
 
 #[derive(Debug, Error)]
 pub enum EndpointError {
@@ -13,55 +17,150 @@ pub enum EndpointError {
     NoEndpoint(String),
     #[error("Invalid domain format: {0}")]
     InvalidDomain(String),
+    #[error("Invalid RDAP bootstrap document: {0}")]
+    InvalidBootstrap(#[from] serde_json::Error),
 }
 
-#[derive(Debug, Deserialize)]
-struct IanaBootstrap {
-    services: Vec<(Vec<String>, Vec<String>)>,
+/// The `services` of an IANA bootstrap document: TLDs and their base URLs.
+type Services = Vec<(Vec<String>, Vec<String>)>;
+
+fn bootstrap_services(json: &str) -> Result<Services, serde_json::Error> {
+    let mut document: serde_json::Value = serde_json::from_str(json)?;
+    serde_json::from_value(document.get_mut("services").map(serde_json::Value::take).unwrap_or_default())
 }
 
 pub struct EndpointRegistry {
-    endpoints: DashMap<String, String>,
+    /// Base URLs per suffix, in the order IANA lists them
+    endpoints: DashMap<String, Vec<String>>,
+    /// Caller-set endpoints, which win over the bootstrap's
+    custom: DashMap<String, Vec<String>>,
     bootstrapped: std::sync::atomic::AtomicBool,
+    state: Mutex<BootstrapState>,
 }
 
 impl EndpointRegistry {
     pub fn new() -> Self {
         Self {
             endpoints: DashMap::new(),
+            custom: DashMap::new(),
             bootstrapped: std::sync::atomic::AtomicBool::new(false),
+            state: Mutex::new(BootstrapState::NotStarted),
         }
     }
 
+    /// Fetches the live bootstrap, falling back to the bundled snapshot
+    /// when IANA can't be reached. Errors only if neither is usable.
     pub async fn bootstrap(&self, client: &Client) -> Result<(), EndpointError> {
         if self.bootstrapped.load(std::sync::atomic::Ordering::Relaxed) {
             return Ok(());
         }
 
-        let resp: IanaBootstrap = client
-            .get(IANA_BOOTSTRAP_URL)
-            .send()
-            .await?
-            .json()
-            .await?;
-
-        for (tlds, urls) in resp.services {
-            if let Some(url) = urls.first() {
-                let base_url = url.trim_end_matches('/').to_string();
-                for tld in tlds {
-                    self.endpoints.insert(tld.to_lowercase(), base_url.clone());
-                }
+        match self.fetch_and_install(client).await {
+            Ok(_) => Ok(()),
+            Err(e) => self.load_bundled().map_err(|_| e),
+        }
+    }
+
+    /// Installs the snapshot compiled into the library.
+    pub fn load_bundled(&self) -> Result<(), EndpointError> {
+        let services = bootstrap_services(BUNDLED_BOOTSTRAP)?;
+        self.install(services);
+        tracing::warn!(endpoints = self.endpoints.len(), "using the bundled RDAP bootstrap");
+        self.set_state(BootstrapState::Bundled(self.endpoints.len()));
+        Ok(())
+    }
+
+    /// Downloads and installs the bootstrap, returning the document so the
+    /// caller can cache it.
+    #[tracing::instrument(name = "bootstrap", skip_all)]
+    pub async fn fetch_and_install(&self, client: &Client) -> Result<String, EndpointError> {
+        self.set_state(BootstrapState::InProgress);
+
+        let start = std::time::Instant::now();
+        let fetched = match fetch_bootstrap_json(client).await {
+            Ok(json) => bootstrap_services(&json)
+                .map(|services| (json, services))
+                .map_err(EndpointError::from),
+            Err(e) => Err(e),
+        };
+        let (json, services) = match fetched {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                tracing::warn!(error = %e, "RDAP bootstrap fetch failed");
+                self.set_state(BootstrapState::Failed(e.to_string()));
+                return Err(e);
+            }
+        };
+
+        self.install(services);
+        tracing::info!(
+            endpoints = self.endpoints.len(),
+            latency_ms = start.elapsed().as_millis() as u64,
+            "RDAP bootstrap fetched"
+        );
+        Ok(json)
+    }
+
+    pub fn is_bootstrapped(&self) -> bool {
+        self.bootstrapped.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Populates the registry from a bootstrap document (e.g. a cached
+    /// copy of `fetch_bootstrap_json`) instead of fetching it.
+    pub fn load_bootstrap(&self, json: &str) -> Result<(), EndpointError> {
+        let services = bootstrap_services(json)?;
+        self.install(services);
+        Ok(())
+    }
+
+    fn install(&self, services: Services) {
+        for (tlds, urls) in services {
+            let base_urls: Vec<String> = urls.iter().map(|url| url.trim_end_matches('/').to_string()).collect();
+            if base_urls.is_empty() {
+                continue;
+            }
+            for tld in tlds {
+                self.endpoints.insert(tld.to_lowercase(), base_urls.clone());
             }
         }
 
         self.bootstrapped.store(true, std::sync::atomic::Ordering::Relaxed);
-        Ok(())
+        self.set_state(BootstrapState::Ready(self.endpoints.len()));
+    }
+
+    pub fn state(&self) -> BootstrapState {
+        self.state.lock().unwrap().clone()
+    }
+
+    fn set_state(&self, state: BootstrapState) {
+        *self.state.lock().unwrap() = state;
+    }
+
+    /// The RDAP base URL for a public suffix. A multi-label suffix such as
+    /// `co.uk` uses its own bootstrap entry if there is one, else that of
+    /// the nearest parent, down to the TLD.
+    pub fn get_endpoint(&self, suffix: &str) -> Option<String> {
+        self.get_endpoints(suffix).into_iter().next()
     }
 
-    pub fn get_endpoint(&self, tld: &str) -> Option<String> {
-        self.endpoints.get(&tld.to_lowercase()).map(|v| v.clone())
+    /// Every RDAP base URL for a public suffix, preferred first; empty if
+    /// there is none. Later ones are alternates for when the first fails.
+    pub fn get_endpoints(&self, suffix: &str) -> Vec<String> {
+        let suffix = suffix.to_lowercase();
+        std::iter::once(suffix.as_str())
+            .chain(suffix.match_indices('.').map(|(i, _)| &suffix[i + 1..]))
+            .find_map(|candidate| {
+                self.custom.get(candidate).or_else(|| self.endpoints.get(candidate)).map(|v| v.clone())
+            })
+            .unwrap_or_default()
     }
 
+    /// Sends lookups for `suffix` to `urls` instead of what the bootstrap
+    /// says, e.g. a private mirror. Kept across bootstrap refreshes.
+    pub fn set_endpoints(&self, suffix: &str, urls: &[&str]) {
+        let urls: Vec<String> = urls.iter().map(|url| url.trim_end_matches('/').to_string()).collect();
+        self.custom.insert(suffix.trim_start_matches('.').to_lowercase(), urls);
+    }
 }
 
 impl Default for EndpointRegistry {
@@ -70,11 +169,39 @@ impl Default for EndpointRegistry {
     }
 }
 
+/// Fetches the raw IANA RDAP bootstrap document, for callers that cache it.
+pub async fn fetch_bootstrap_json(client: &Client) -> Result<String, EndpointError> {
+    Ok(client
+        .get(IANA_BOOTSTRAP_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// A cached bootstrap document and its age.
+pub(crate) fn read_bootstrap_cache(path: &Path) -> Option<(String, Duration)> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let json = std::fs::read_to_string(path).ok()?;
+    Some((json, modified.elapsed().unwrap_or_default()))
+}
+
+/// Writes through a temporary file and a rename, so another process never
+/// reads half a document.
+pub(crate) fn write_bootstrap_cache(path: &Path, json: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(tmp, path)
+}
+
+/// The domain's public suffix, so `foo.co.uk` yields `co.uk` rather than
+/// `uk`.
 pub fn extract_tld(domain: &str) -> Result<String, EndpointError> {
-    domain
-        .rsplit('.')
-        .next()
-        .filter(|s| !s.is_empty())
+    public_suffix(domain)
         .map(|s| s.to_lowercase())
         .ok_or_else(|| EndpointError::InvalidDomain(domain.to_string()))
 }
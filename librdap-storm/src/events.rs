@@ -0,0 +1,22 @@
+use crate::types::Availability;
+use std::time::Duration;
+
+/// Progress of a probe, as received from `Prober::subscribe`. Domains are
+/// as passed to the prober. An endpoint's queue depth is its `Queued` events
+/// less its `Started` ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeEvent {
+    /// Waiting for one of `endpoint`'s `max_concurrent_per_endpoint` slots
+    Queued { domain: String, endpoint: String },
+    /// Holds a slot; the request goes out once the rate limit allows
+    Started { domain: String, endpoint: String },
+    /// `endpoint` answered 429; `wait` is its `Retry-After`
+    RateLimited {
+        domain: String,
+        endpoint: String,
+        wait: Option<Duration>,
+    },
+    /// RDAP gave no answer, asking WHOIS
+    FallbackToWhois { domain: String },
+    Finished { domain: String, availability: Availability },
+}
@@ -0,0 +1,93 @@
+use crate::{
+    psl::public_suffix,
+    types::{to_ascii_domain, Availability, ProbeError, ProbeResult, ProbeSource},
+};
+use futures::{
+    stream::{self, Stream, StreamExt},
+    Future,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Failures a suffix needs before they count as systematic; a lone one
+/// is just a failed probe.
+const MIN_GROUP: usize = 2;
+
+/// Per suffix: whether it got an answer, and the failures held until then.
+type Groups<T> = Arc<Mutex<HashMap<String, (bool, Vec<T>)>>>;
+
+/// How a result failed, with the domain taken out of the reason so the
+/// failures of one suffix compare equal. `None` for verdicts, and for
+/// unknowns a retry can't help (maintenance, invalid names).
+fn failure(result: &ProbeResult) -> Option<(Option<&str>, ProbeSource, &'static str, String)> {
+    match &result.availability {
+        Availability::Unknown { reason } if !matches!(reason, ProbeError::Maintenance | ProbeError::InvalidName) => {
+            let ascii = to_ascii_domain(&result.domain).unwrap_or_else(|_| result.domain.clone());
+            let message = reason.to_string().replace(&ascii, "").replace(&result.domain, "");
+            Some((result.endpoint.as_deref(), result.source, reason.kind(), message))
+        }
+        _ => None,
+    }
+}
+
+/// `results` with failures held back per public suffix until the suffix
+/// gets an answer, when they come out as they are. Once `results` ends, a
+/// suffix whose every result failed the same way has them passed through
+/// `retry`; any other held failures come out unchanged. Unless `enabled`,
+/// `results` as they are.
+pub(crate) fn group_retried<'a, T, F, Fut>(
+    results: impl Stream<Item = T> + 'a,
+    result: fn(&T) -> &ProbeResult,
+    retry: F,
+    concurrency: usize,
+    enabled: bool,
+) -> impl Stream<Item = T> + 'a
+where
+    T: 'a,
+    F: FnMut(T) -> Fut + 'a,
+    Fut: Future<Output = T> + 'a,
+{
+    let groups: Groups<T> = Arc::default();
+
+    let passed = {
+        let groups = Arc::clone(&groups);
+        results.flat_map(move |item| {
+            let found = result(&item);
+            let suffix = public_suffix(&found.domain).unwrap_or_default().to_string();
+            let mut groups = groups.lock().unwrap();
+            let (answered, held) = groups.entry(suffix).or_default();
+            let out = if *answered || !enabled {
+                vec![item]
+            } else if failure(found).is_some() {
+                held.push(item);
+                Vec::new()
+            } else {
+                *answered = true;
+                let mut out = std::mem::take(held);
+                out.push(item);
+                out
+            };
+            stream::iter(out)
+        })
+    };
+
+    // Boxed so the stream stays `Unpin`, as `buffer_unordered` ones are
+    let held = stream::once(Box::pin(async move {
+        let groups = std::mem::take(&mut *groups.lock().unwrap());
+        let (mut systematic, mut kept) = (Vec::new(), Vec::new());
+        for (_, (_, held)) in groups {
+            let first = held.first().and_then(|item| failure(result(item)));
+            if held.len() >= MIN_GROUP && held.iter().all(|item| failure(result(item)) == first) {
+                systematic.extend(held);
+            } else {
+                kept.extend(held);
+            }
+        }
+        stream::iter(kept).chain(stream::iter(systematic).map(retry).buffer_unordered(concurrency))
+    }))
+    .flatten();
+
+    passed.chain(held)
+}
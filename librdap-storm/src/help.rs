@@ -0,0 +1,95 @@
+use reqwest::Client;
+use serde_json::Value;
+use std::time::Duration;
+
+/// What an endpoint's RDAP `/help` response says about using it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointHelp {
+    /// Notices as `title: description`, in response order.
+    pub notices: Vec<String>,
+    /// Query limit stated in the notices, if one could be recognized.
+    pub limit: Option<DocumentedLimit>,
+}
+
+/// A limit of `requests` per `period`, e.g. "60 queries per minute".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentedLimit {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+impl DocumentedLimit {
+    pub fn per_second(&self) -> f64 {
+        self.requests as f64 / self.period.as_secs_f64()
+    }
+}
+
+/// Fetches `{endpoint}/help`. Endpoints without a usable help resource
+/// yield an empty `EndpointHelp`.
+pub async fn fetch_help(client: &Client, endpoint: &str, timeout: Duration) -> EndpointHelp {
+    let url = format!("{}/help", endpoint);
+    let response = async {
+        client
+            .get(&url)
+            .timeout(timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await
+    };
+
+    let Ok(help) = response.await else {
+        return EndpointHelp::default();
+    };
+
+    let notices: Vec<String> = help
+        .get("notices")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice)
+        .iter()
+        .map(|n| {
+            let description = n.get("description").and_then(Value::as_array).map_or(&[][..], Vec::as_slice);
+            let text = description.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" ");
+            match n.get("title").and_then(Value::as_str).map(str::to_string) {
+                Some(title) if !text.is_empty() => format!("{}: {}", title, text),
+                Some(title) => title,
+                None => text,
+            }
+        })
+        .filter(|n| !n.trim().is_empty())
+        .collect();
+
+    let limit = notices.iter().find_map(|n| parse_limit(n));
+    EndpointHelp { notices, limit }
+}
+
+/// Finds phrases like "10 queries per second" or "1000 requests an hour".
+fn parse_limit(text: &str) -> Option<DocumentedLimit> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_ascii_alphanumeric())
+                .replace(',', "")
+                .to_lowercase()
+        })
+        .collect();
+
+    words.iter().enumerate().find_map(|(i, word)| {
+        let requests: u32 = word.parse().ok().filter(|n| *n > 0)?;
+        let rest = words.get(i + 1..(i + 6).min(words.len()))?;
+        if !rest.iter().any(|w| {
+            w.starts_with("quer") || w.starts_with("request") || w.starts_with("lookup")
+        }) {
+            return None;
+        }
+        let period = rest.iter().find_map(|w| match w.as_str() {
+            "second" | "sec" | "s" => Some(Duration::from_secs(1)),
+            "minute" | "min" => Some(Duration::from_secs(60)),
+            "hour" | "hr" => Some(Duration::from_secs(3600)),
+            "day" => Some(Duration::from_secs(86400)),
+            _ => None,
+        })?;
+        Some(DocumentedLimit { requests, period })
+    })
+}
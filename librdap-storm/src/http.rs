@@ -1,9 +1,44 @@
-use reqwest::Client;
-use std::time::Duration;
+use crate::resolver::Resolver;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client,
+};
+use std::{sync::Arc, time::Duration};
 
-pub fn create_http_pool(timeout: Duration) -> Client {
-    Client::builder()
+/// Sent with every request unless `ProbeConfig::user_agent` says otherwise.
+pub const DEFAULT_USER_AGENT: &str = concat!("librdap-storm/", env!("CARGO_PKG_VERSION"));
+
+/// The shared client. `headers` go out with every request; ones that
+/// aren't valid HTTP are skipped with a warning. With `proxy` every
+/// request goes through it. Host names are looked up by
+/// `resolver`.
+pub(crate) fn create_http_pool(
+    timeout: Duration,
+    user_agent: &str,
+    headers: &[(String, String)],
+    proxy: Option<reqwest::Proxy>,
+    resolver: &Resolver,
+) -> Client {
+    let mut default_headers = HeaderMap::new();
+    for (name, value) in headers {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            (Ok(name), Ok(value)) => {
+                default_headers.append(name, value);
+            }
+            _ => tracing::warn!(header = %name, "invalid HTTP header, not sent"),
+        }
+    }
+    let mut builder = Client::builder();
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy);
+    }
+    if !resolver.is_system() {
+        builder = builder.dns_resolver(Arc::new(resolver.clone()));
+    }
+    builder
         .timeout(timeout)
+        .user_agent(user_agent)
+        .default_headers(default_headers)
         .pool_max_idle_per_host(100)
         .pool_idle_timeout(Duration::from_secs(90))
         .tcp_keepalive(Duration::from_secs(60))
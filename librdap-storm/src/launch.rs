@@ -0,0 +1,112 @@
+use crate::maintenance::parse_date;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Where a TLD is in its launch. Before general availability a name the
+/// registry reports as free is usually limited to trademark holders
+/// (sunrise) or sold at launch prices (landrush, early access).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum LaunchPhase {
+    /// Delegated or announced, but no phase has opened yet
+    PreLaunch,
+    Sunrise,
+    Landrush,
+    EarlyAccess,
+    GeneralAvailability,
+}
+
+impl LaunchPhase {
+    pub fn name(self) -> &'static str {
+        match self {
+            LaunchPhase::PreLaunch => "pre_launch",
+            LaunchPhase::Sunrise => "sunrise",
+            LaunchPhase::Landrush => "landrush",
+            LaunchPhase::EarlyAccess => "early_access",
+            LaunchPhase::GeneralAvailability => "general_availability",
+        }
+    }
+}
+
+impl fmt::Display for LaunchPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LaunchPhase::PreLaunch => "pre-launch",
+            LaunchPhase::Sunrise => "sunrise",
+            LaunchPhase::Landrush => "landrush",
+            LaunchPhase::EarlyAccess => "early access",
+            LaunchPhase::GeneralAvailability => "general availability",
+        })
+    }
+}
+
+/// Published start dates (`YYYY-MM-DD`, UTC) of a TLD's launch phases.
+/// Phases the registry doesn't run, or hasn't dated yet, are left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(deny_unknown_fields))]
+pub struct LaunchSchedule {
+    pub tld: String,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub sunrise: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub landrush: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub early_access: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub general_availability: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub note: Option<String>,
+}
+
+impl LaunchSchedule {
+    /// The phases with a date, in the order they open.
+    fn dated(&self) -> impl Iterator<Item = (LaunchPhase, &str)> {
+        [
+            (LaunchPhase::Sunrise, &self.sunrise),
+            (LaunchPhase::Landrush, &self.landrush),
+            (LaunchPhase::EarlyAccess, &self.early_access),
+            (LaunchPhase::GeneralAvailability, &self.general_availability),
+        ]
+        .into_iter()
+        .filter_map(|(phase, date)| date.as_deref().map(|date| (phase, date)))
+    }
+
+    /// Describes what is wrong with the schedule, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut previous: Option<(LaunchPhase, i64)> = None;
+        for (phase, date) in self.dated() {
+            let Some(day) = parse_date(date) else {
+                return Err(format!("{}: expected YYYY-MM-DD, got \"{}\"", phase.name(), date));
+            };
+            if let Some((before, _)) = previous.filter(|(_, earlier)| *earlier > day) {
+                return Err(format!("{}: {} is before {} opens", phase.name(), date, before.name()));
+            }
+            previous = Some((phase, day));
+        }
+        if previous.is_none() {
+            return Err("general_availability: no phase has a date".to_string());
+        }
+        Ok(())
+    }
+
+    /// Covers the public suffix `suffix` or its TLD.
+    pub fn covers(&self, suffix: &str) -> bool {
+        let tld = suffix.rsplit('.').next().unwrap_or(suffix);
+        self.tld.eq_ignore_ascii_case(suffix) || self.tld.eq_ignore_ascii_case(tld)
+    }
+
+    /// The phase open at `now` (Unix seconds), with the date the next one
+    /// opens if that is known.
+    pub fn phase_at(&self, now: u64) -> (LaunchPhase, Option<&str>) {
+        let today = (now / 86400) as i64;
+        let mut current = LaunchPhase::PreLaunch;
+        for (phase, date) in self.dated() {
+            if parse_date(date).is_some_and(|day| day > today) {
+                return (current, Some(date));
+            }
+            current = phase;
+        }
+        (current, None)
+    }
+}
@@ -1,15 +1,62 @@
+mod breaker;
+mod cache;
+mod canary;
+mod checker;
+mod confirm;
+#[cfg(feature = "sqlite-cache")]
+mod db;
+mod dedupe;
+mod dns;
 mod endpoint;
+mod events;
+mod group;
+mod help;
 mod http;
+mod launch;
+mod maintenance;
+mod metrics;
 mod prober;
+mod proxy;
+pub mod psl;
 mod ratelimit;
 mod rdap;
+mod record;
+mod requeue;
+mod resolver;
+mod retry;
+pub mod restrictions;
+mod syntax;
 pub mod tlds;
 mod types;
 mod whois;
 
-pub use prober::Prober;
-pub use types::{Availability, ProbeConfig, ProbeResult};
-pub use tlds::{expand_tlds, fetch_iana_tlds};
+pub use canary::{tld_canary, CanaryError, CANARY_DOMAIN};
+pub use checker::{CheckStep, Checker};
+pub use dedupe::{dedupe_domains, MergedInputs};
+pub use endpoint::{fetch_bootstrap_json, EndpointError, EndpointRegistry};
+pub use events::ProbeEvent;
+pub use help::{DocumentedLimit, EndpointHelp};
+pub use http::DEFAULT_USER_AGENT;
+pub use launch::{LaunchPhase, LaunchSchedule};
+pub use maintenance::{builtin_windows, MaintenanceWindow, MAINTENANCE_REASON};
+pub use metrics::EndpointMetrics;
+pub use prober::{Prober, ProberBuilder};
+pub use proxy::{Proxy, ProxyError};
+pub use psl::{public_suffix, PublicSuffixList};
+pub use record::{DomainRecord, RdapEntity, LOCK_STATUSES};
+pub use resolver::{DnsResolver, ResolverError};
+pub use retry::RetryOn;
+pub use types::{
+    to_ascii_domain, Availability, BootstrapState, Confidence, ProbeConfig, ProbeError, ProbeResult, ProbeSource,
+    DIAGNOSTIC_HEADERS,
+};
+pub use restrictions::{
+    is_registrable, restriction_for, EntityType, Registrant, Requirement, TldRestriction,
+};
+pub use syntax::{check_name, name_rule_for, NameRule, INVALID_NAME_REASON};
+pub use whois::parse_whois;
+pub use tlds::{expand_tlds, fetch_iana_tlds, TldRanker, DEFAULT_PRIORITY_TLDS};
+pub use tokio_util::sync::CancellationToken;
 
 use futures::StreamExt;
 
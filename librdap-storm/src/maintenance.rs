@@ -0,0 +1,118 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How `ProbeError::Maintenance`, answers lost to a maintenance window,
+/// displays.
+pub const MAINTENANCE_REASON: &str = "Registry maintenance";
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// A window in which the registries of `tlds` are down for maintenance.
+/// Weekly with `day`, one-off with `date`, daily with neither.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(deny_unknown_fields))]
+pub struct MaintenanceWindow {
+    pub tlds: Vec<String>,
+    /// Three-letter weekday, e.g. `"sun"`
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub day: Option<String>,
+    /// `YYYY-MM-DD`
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub date: Option<String>,
+    /// UTC start time, `HH:MM`
+    pub start: String,
+    pub minutes: u32,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub note: Option<String>,
+}
+
+/// Weekly windows the registries publish. Registries announce one-off
+/// work on their own sites; add it through `ProbeConfig::maintenance`.
+pub fn builtin_windows() -> Vec<MaintenanceWindow> {
+    let weekly = |tlds: &[&str], day: &str, start: &str, minutes, note: &str| MaintenanceWindow {
+        tlds: tlds.iter().map(|t| t.to_string()).collect(),
+        day: Some(day.to_string()),
+        date: None,
+        start: start.to_string(),
+        minutes,
+        note: Some(note.to_string()),
+    };
+    vec![
+        weekly(&["br"], "sun", "03:00", 120, "Registro.br weekly maintenance"),
+        weekly(&["au"], "sun", "00:00", 60, "auDA registry weekly maintenance"),
+        weekly(&["cl"], "sun", "05:00", 60, "NIC Chile weekly maintenance"),
+    ]
+}
+
+/// Days since the Unix epoch of a proleptic Gregorian date.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+pub(crate) fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    ((1..=12).contains(&month) && (1..=31).contains(&day)).then(|| days_from_civil(year, month, day))
+}
+
+/// Minutes after midnight of `HH:MM`.
+fn parse_time(time: &str) -> Option<i64> {
+    let (hours, minutes) = time.split_once(':')?;
+    let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+    ((0..24).contains(&hours) && (0..60).contains(&minutes)).then_some(hours * 60 + minutes)
+}
+
+impl MaintenanceWindow {
+    /// Describes what is wrong with the window, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tlds.is_empty() {
+            return Err("tlds: no TLDs given".to_string());
+        }
+        if parse_time(&self.start).is_none() {
+            return Err(format!("start: expected HH:MM, got \"{}\"", self.start));
+        }
+        if self.minutes == 0 {
+            return Err("minutes: must be greater than 0".to_string());
+        }
+        match (&self.day, &self.date) {
+            (Some(_), Some(_)) => Err("day: cannot be combined with date".to_string()),
+            (Some(day), None) if !WEEKDAYS.contains(&day.to_lowercase().as_str()) => {
+                Err(format!("day: expected one of {}, got \"{}\"", WEEKDAYS.join(", "), day))
+            }
+            (None, Some(date)) if parse_date(date).is_none() => {
+                Err(format!("date: expected YYYY-MM-DD, got \"{}\"", date))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Covers the public suffix `suffix` or its TLD.
+    pub fn covers(&self, suffix: &str) -> bool {
+        let tld = suffix.rsplit('.').next().unwrap_or(suffix);
+        self.tlds.iter().any(|t| t.eq_ignore_ascii_case(suffix) || t.eq_ignore_ascii_case(tld))
+    }
+
+    /// When the window containing `now` (Unix seconds) ends, if it does.
+    pub fn active_until(&self, now: u64) -> Option<u64> {
+        let now = now as i64;
+        let start = parse_time(&self.start)? * 60;
+        let today = now.div_euclid(86400);
+        // A window that crosses midnight started the day before
+        (0..=self.minutes as i64 / 1440 + 1).find_map(|back| {
+            let day = today - back;
+            let applies = match (&self.day, &self.date) {
+                // 1970-01-01 was a Thursday
+                (Some(weekday), _) => WEEKDAYS[(day + 3).rem_euclid(7) as usize].eq_ignore_ascii_case(weekday),
+                (None, Some(date)) => parse_date(date) == Some(day),
+                (None, None) => true,
+            };
+            let (from, until) = (day * 86400 + start, day * 86400 + start + self.minutes as i64 * 60);
+            (applies && (from..until).contains(&now)).then_some(until as u64)
+        })
+    }
+}
@@ -0,0 +1,78 @@
+use crate::retry::RetryOn;
+use dashmap::DashMap;
+use std::time::Duration;
+
+/// Latencies kept per endpoint for the percentiles; past this the oldest
+/// are overwritten.
+const MAX_LATENCY_SAMPLES: usize = 4096;
+
+/// Domain queries sent to one RDAP endpoint by a prober, as returned by
+/// `Prober::metrics`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointMetrics {
+    pub requests: u64,
+    /// 200 answers
+    pub ok: u64,
+    /// 404 answers
+    pub not_found: u64,
+    /// 429 answers
+    pub rate_limited: u64,
+    pub timeouts: u64,
+    /// 5xx answers and connection failures
+    pub errors: u64,
+    pub p50: Option<Duration>,
+    pub p95: Option<Duration>,
+    pub p99: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Counters {
+    metrics: EndpointMetrics,
+    latencies: Vec<Duration>,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    endpoints: DashMap<String, Counters>,
+}
+
+impl Metrics {
+    /// Counts a request to `endpoint`; `status` is unset when no answer came.
+    pub fn record(&self, endpoint: &str, status: Option<u16>, failure: Option<RetryOn>, latency: Duration) {
+        let mut counters = self.endpoints.entry(endpoint.to_string()).or_default();
+        let metrics = &mut counters.metrics;
+        metrics.requests += 1;
+        match (status, failure) {
+            (Some(200), _) => metrics.ok += 1,
+            (Some(404), _) => metrics.not_found += 1,
+            (Some(429), _) => metrics.rate_limited += 1,
+            (_, Some(RetryOn::Timeout)) => metrics.timeouts += 1,
+            (_, Some(RetryOn::ServerError | RetryOn::Connection)) => metrics.errors += 1,
+            _ => {}
+        }
+
+        let slot = (metrics.requests - 1) as usize % MAX_LATENCY_SAMPLES;
+        match counters.latencies.get_mut(slot) {
+            Some(sample) => *sample = latency,
+            None => counters.latencies.push(latency),
+        }
+    }
+
+    /// Every endpoint asked so far, sorted.
+    pub fn snapshot(&self) -> Vec<(String, EndpointMetrics)> {
+        let mut snapshot: Vec<_> = self
+            .endpoints
+            .iter()
+            .map(|entry| {
+                let mut latencies = entry.latencies.clone();
+                latencies.sort();
+                // Nearest rank
+                let percentile = |p: usize| latencies.get((latencies.len() * p).div_ceil(100).saturating_sub(1)).copied();
+                let metrics = EndpointMetrics { p50: percentile(50), p95: percentile(95), p99: percentile(99), ..entry.metrics.clone() };
+                (entry.key().clone(), metrics)
+            })
+            .collect();
+        snapshot.sort_by(|a, b| a.0.cmp(&b.0));
+        snapshot
+    }
+}
@@ -1,19 +1,107 @@
 use crate::{
-    endpoint::{extract_tld, EndpointRegistry},
+    breaker::EndpointBreakers,
+    cache::ProbeCache,
+    canary::{check_canary, tld_canary, CanaryError, CANARY_DOMAIN},
+    checker::{CheckStep, Checker},
+    confirm::{confirm_available, resolves},
+    dedupe::dedupe_domains,
+    dns::{is_delegated, is_signed},
+    events::ProbeEvent,
+    group::group_retried,
+    endpoint::{
+        extract_tld, fetch_bootstrap_json, read_bootstrap_cache, write_bootstrap_cache, EndpointError,
+        EndpointRegistry,
+    },
+    help::{fetch_help, EndpointHelp},
     http::create_http_pool,
+    launch::LaunchPhase,
+    metrics::{EndpointMetrics, Metrics},
+    proxy::Proxy,
+    psl::public_suffix,
     ratelimit::EndpointRateLimiters,
-    rdap::check_rdap,
-    types::{Availability, ProbeConfig, ProbeResult},
-    whois::check_whois,
+    rdap::{check_rdap, fetch_record, RdapResponse},
+    record::DomainRecord,
+    requeue::{requeuing, Queued},
+    resolver::Resolver,
+    retry::{backoff, RetryOn},
+    syntax::check_name,
+    types::{
+        to_ascii_domain, Availability, BootstrapState, Confidence, ProbeConfig, ProbeError, ProbeResult, ProbeSource,
+    },
+    whois::{WhoisAnswer, WhoisClient},
 };
-use futures::stream::{self, Stream, StreamExt};
+#[cfg(feature = "sqlite-cache")]
+use crate::db::ResultDb;
+use dashmap::DashMap;
+use futures::stream::{Stream, StreamExt};
 use reqwest::Client;
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tokio::sync::{broadcast, OnceCell, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+/// `ProbeEvent`s a subscriber may fall behind by before missing some.
+const EVENT_BUFFER: usize = 4096;
+
+/// What one step of the probe chain made of a domain.
+struct StepAnswer {
+    availability: Availability,
+    explanation: String,
+    source: ProbeSource,
+    /// Instead of what `source` implies
+    confidence: Option<Confidence>,
+    headers: Vec<(String, String)>,
+    endpoint: Option<String>,
+    record: Option<DomainRecord>,
+    raw: Option<String>,
+    /// A 429 sent the domain to the back of the queue
+    requeued: bool,
+}
+
+impl StepAnswer {
+    fn new(availability: Availability, explanation: String, source: ProbeSource) -> Self {
+        Self {
+            availability,
+            explanation,
+            source,
+            confidence: None,
+            headers: Vec::new(),
+            endpoint: None,
+            record: None,
+            raw: None,
+            requeued: false,
+        }
+    }
+}
 
 pub struct Prober {
     client: Client,
     registry: Arc<EndpointRegistry>,
     rate_limiters: Arc<EndpointRateLimiters>,
+    /// Per endpoint: `max_concurrent_per_endpoint` request slots, so a slow
+    /// registry ties up only its own share of the sweep
+    in_flight: Arc<DashMap<String, Arc<Semaphore>>>,
+    breakers: Arc<EndpointBreakers>,
+    metrics: Arc<Metrics>,
+    /// Recent verdicts, when `cache_ttl` is set
+    cache: Option<Arc<ProbeCache>>,
+    #[cfg(feature = "sqlite-cache")]
+    db: Option<Arc<ResultDb>>,
+    whois: Arc<WhoisClient>,
+    help: Arc<DashMap<String, Arc<OnceCell<EndpointHelp>>>>,
+    /// Per endpoint: whether its canary lookup came back as registered
+    reliable: Arc<DashMap<String, Arc<OnceCell<bool>>>>,
+    /// Per domain: times it has been requeued on 429 so far
+    requeues: Arc<DashMap<String, u32>>,
+    /// A background refresh of the cached bootstrap has been started
+    refreshing: Arc<AtomicBool>,
+    events: broadcast::Sender<ProbeEvent>,
+    resolver: Resolver,
     config: ProbeConfig,
 }
 
@@ -23,76 +111,742 @@ impl Prober {
     }
 
     pub fn with_config(config: ProbeConfig) -> Self {
-        let client = create_http_pool(config.timeout);
-        Self {
-            client,
-            registry: Arc::new(EndpointRegistry::new()),
-            rate_limiters: Arc::new(EndpointRateLimiters::new(config.max_rate_per_endpoint)),
-            config,
+        Self::builder().config(config).build()
+    }
+
+    /// For embedders bringing their own HTTP client, endpoint registry or
+    /// rate limits.
+    pub fn builder() -> ProberBuilder {
+        ProberBuilder::default()
+    }
+
+    pub async fn ensure_bootstrapped(&self) -> Result<(), EndpointError> {
+        if self.registry.is_bootstrapped() {
+            return Ok(());
+        }
+        let Some(path) = &self.config.bootstrap_cache else {
+            return self.registry.bootstrap(&self.client).await;
+        };
+
+        if let Some((json, age)) = read_bootstrap_cache(path) {
+            if self.registry.load_bootstrap(&json).is_ok() {
+                tracing::debug!(path = %path.display(), age_secs = age.as_secs(), "cached RDAP bootstrap loaded");
+                if age >= self.config.bootstrap_ttl {
+                    self.refresh_bootstrap_cache();
+                }
+                return Ok(());
+            }
         }
+        match self.registry.fetch_and_install(&self.client).await {
+            Ok(json) => {
+                let _ = write_bootstrap_cache(path, &json);
+                Ok(())
+            }
+            // Only live documents are cached, so the next run tries IANA again
+            Err(e) => self.registry.load_bundled().map_err(|_| e),
+        }
+    }
+
+    /// Downloads a new bootstrap behind a stale cached one, once per
+    /// prober. Probes keep using the stale copy meanwhile; a failed
+    /// download leaves it in place for the next run to retry.
+    fn refresh_bootstrap_cache(&self) {
+        let Some(path) = self.config.bootstrap_cache.clone() else {
+            return;
+        };
+        if self.refreshing.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let (client, registry) = (self.client.clone(), Arc::clone(&self.registry));
+        tokio::spawn(async move {
+            if let Ok(json) = fetch_bootstrap_json(&client).await {
+                if registry.load_bootstrap(&json).is_ok() {
+                    let _ = write_bootstrap_cache(&path, &json);
+                }
+            }
+        });
     }
 
-    pub async fn ensure_bootstrapped(&self) -> Result<(), crate::endpoint::EndpointError> {
-        self.registry.bootstrap(&self.client).await
+    /// Uses a previously fetched bootstrap document instead of fetching one.
+    pub fn seed_bootstrap(&self, json: &str) -> Result<(), EndpointError> {
+        self.registry.load_bootstrap(json)
+    }
+
+    pub fn bootstrap_state(&self) -> BootstrapState {
+        self.registry.state()
+    }
+
+    /// The launch phase of `suffix` while it isn't in general availability,
+    /// with the date the next phase opens if known. `None` for TLDs
+    /// without a schedule in `ProbeConfig::launches`.
+    pub fn launch_phase(&self, suffix: &str) -> Option<(LaunchPhase, Option<String>)> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let schedule = self.config.launches.iter().rev().find(|schedule| schedule.covers(suffix))?;
+        match schedule.phase_at(now) {
+            (LaunchPhase::GeneralAvailability, _) => None,
+            (phase, next) => Some((phase, next.map(str::to_string))),
+        }
+    }
+
+    /// False once the WHOIS fallback has been disabled because outbound
+    /// port 43 appears to be blocked.
+    pub fn whois_available(&self) -> bool {
+        !self.whois.is_disabled()
+    }
+
+    fn use_whois(&self) -> bool {
+        self.config.whois_fallback && self.whois_available()
+    }
+
+    /// Checks that RDAP traffic isn't being intercepted (e.g. by a captive
+    /// portal answering every lookup with a login page) before a sweep, so
+    /// callers can abort instead of reporting every domain as taken. A
+    /// `proxy` that can't be reached or logged into fails it too.
+    pub async fn check_network(&self) -> Result<(), CanaryError> {
+        if let Some(proxy) = &self.config.proxy {
+            let checked = tokio::time::timeout(self.config.timeout, proxy.check(&self.resolver)).await;
+            if let Err(e) = checked.unwrap_or_else(|_| Err(std::io::ErrorKind::TimedOut.into())) {
+                return Err(CanaryError::Proxy(proxy.to_string(), e.to_string()));
+            }
+        }
+        match self.ensure_bootstrapped().await {
+            Err(EndpointError::FetchError(e)) if e.is_decode() || e.is_redirect() => {
+                return Err(CanaryError::Intercepted("IANA bootstrap is not JSON".to_string()));
+            }
+            Err(EndpointError::InvalidBootstrap(_)) => {
+                return Err(CanaryError::Intercepted("IANA bootstrap is not JSON".to_string()));
+            }
+            _ => {}
+        }
+
+        let endpoint = extract_tld(CANARY_DOMAIN)
+            .ok()
+            .and_then(|tld| self.registry.get_endpoint(&tld));
+        match endpoint {
+            Some(endpoint) => check_canary(&self.client, &endpoint, self.config.timeout).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Events of every probe from now on, this prober's clones included.
+    /// A subscriber more than a few thousand events behind misses the
+    /// oldest (`RecvError::Lagged`) rather than slowing the probes down.
+    pub fn subscribe(&self) -> broadcast::Receiver<ProbeEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: impl FnOnce() -> ProbeEvent) {
+        // Nobody listening is the common case; skip building the event
+        if self.events.receiver_count() > 0 {
+            let _ = self.events.send(event());
+        }
+    }
+
+    /// Endpoints skipped for now after failing `breaker_threshold` times in
+    /// a row, sorted.
+    pub fn open_circuits(&self) -> Vec<String> {
+        let mut endpoints = self.breakers.open();
+        endpoints.sort();
+        endpoints
+    }
+
+    /// Per RDAP endpoint, the domain queries this prober and its clones
+    /// have sent so far, sorted by endpoint. Canary and `/help` requests
+    /// aren't counted.
+    pub fn metrics(&self) -> Vec<(String, EndpointMetrics)> {
+        self.metrics.snapshot()
+    }
+
+    /// Forgets the verdicts kept under `cache_ttl`, so the next probe of
+    /// each domain goes out again.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Endpoints whose canary lookup reported a registered domain as
+    /// available, sorted.
+    pub fn unreliable_endpoints(&self) -> Vec<String> {
+        let mut endpoints: Vec<String> = self
+            .reliable
+            .iter()
+            .filter(|entry| entry.value().get() == Some(&false))
+            .map(|entry| entry.key().clone())
+            .collect();
+        endpoints.sort();
+        endpoints
+    }
+
+    /// Looks up `tld`'s canary on `endpoint` once per endpoint.
+    async fn endpoint_reliable(&self, endpoint: &str, tld: &str) -> bool {
+        let cell = self
+            .reliable
+            .entry(endpoint.to_string())
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .clone();
+
+        *cell
+            .get_or_init(|| async {
+                self.rate_limiters.acquire(endpoint).await;
+                let canary = check_rdap(
+                    &self.client,
+                    endpoint,
+                    &tld_canary(tld),
+                    self.config.timeout,
+                    &[],
+                    false,
+                    false,
+                )
+                .await;
+                !matches!(canary.availability, Availability::Available)
+            })
+            .await
+    }
+
+    /// `/help` responses fetched so far (only with `discover_limits`),
+    /// sorted by endpoint.
+    pub fn endpoint_help(&self) -> Vec<(String, EndpointHelp)> {
+        let mut help: Vec<_> = self
+            .help
+            .iter()
+            .filter_map(|entry| entry.value().get().map(|h| (entry.key().clone(), h.clone())))
+            .collect();
+        help.sort_by(|a, b| a.0.cmp(&b.0));
+        help
+    }
+
+    /// Fetches an endpoint's help once and seeds its rate limiter from it.
+    async fn discover_limits(&self, endpoint: &str) {
+        let cell = self
+            .help
+            .entry(endpoint.to_string())
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .clone();
+
+        cell.get_or_init(|| async {
+            self.rate_limiters.acquire(endpoint).await;
+            let help = fetch_help(&self.client, endpoint, self.config.timeout).await;
+            if let Some(limit) = help.limit {
+                self.rate_limiters.limit(endpoint, limit.per_second());
+            }
+            help
+        })
+        .await;
     }
 
     pub async fn probe_one(&self, domain: &str) -> ProbeResult {
+        self.probe(domain, false).await.0
+    }
+
+    /// Like `probe_one`, but also parses the RDAP (or fallback WHOIS) answer
+    /// for a taken domain into a `DomainRecord`. The record is `None` when
+    /// neither was asked or the answer couldn't be parsed (and, with
+    /// `ProbeConfig::check_dnssec`, DNS had nothing to add either).
+    pub async fn probe_one_detailed(&self, domain: &str) -> (ProbeResult, Option<DomainRecord>) {
+        self.probe(domain, true).await
+    }
+
+    async fn probe(&self, input: &str, detailed: bool) -> (ProbeResult, Option<DomainRecord>) {
+        loop {
+            match self.probe_queued(input, detailed).await {
+                Queued::Done(found) => return found,
+                // Nothing else is queued behind a single probe
+                Queued::Requeued(_) => continue,
+            }
+        }
+    }
+
+    #[tracing::instrument(name = "probe", skip(self, input, detailed), fields(domain = input, tld = tracing::field::Empty))]
+    async fn probe_queued(&self, input: &str, detailed: bool) -> Queued<(ProbeResult, Option<DomainRecord>)> {
+        let cached = self.cache.as_ref().and_then(|cache| cache.get(input, detailed));
+        #[cfg(feature = "sqlite-cache")]
+        let cached = match (cached, self.db.as_ref()) {
+            // Registration records aren't kept, so detailed probes go out
+            (None, Some(db)) if !detailed => {
+                db.lookup(input, self.config.result_db_max_age).await.map(|result| (result, None))
+            }
+            (cached, _) => cached,
+        };
+        let (result, record) = match cached {
+            Some(hit) => {
+                tracing::debug!("cache hit");
+                hit
+            }
+            None => {
+                let (result, mut record) = match self.probe_inner(input, detailed, None).await {
+                    Queued::Done(found) => found,
+                    Queued::Requeued(domain) => return Queued::Requeued(domain),
+                };
+                if detailed && self.config.check_dnssec && result.availability.is_taken() {
+                    self.check_dnssec(input, &mut record).await;
+                }
+                if let Some(cache) = &self.cache {
+                    cache.insert(&result, detailed, record.as_ref());
+                }
+                #[cfg(feature = "sqlite-cache")]
+                if let Some(db) = &self.db {
+                    db.record(&result).await;
+                }
+                (result, record)
+            }
+        };
+        tracing::debug!(
+            availability = ?result.availability,
+            endpoint = result.endpoint.as_deref(),
+            latency_ms = result.duration.as_millis() as u64,
+            "probe finished"
+        );
+        self.emit(|| ProbeEvent::Finished {
+            domain: input.to_string(),
+            availability: result.availability.clone(),
+        });
+        Queued::Done((result, record))
+    }
+
+    /// `retrying` is a failure of a group retry: the chain is then RDAP
+    /// without the endpoint that failed, then WHOIS.
+    async fn probe_inner(
+        &self,
+        input: &str,
+        detailed: bool,
+        retrying: Option<&ProbeResult>,
+    ) -> Queued<(ProbeResult, Option<DomainRecord>)> {
         let start = Instant::now();
-        
-        if let Err(e) = self.ensure_bootstrapped().await {
-            return ProbeResult {
-                domain: domain.to_string(),
-                availability: Availability::Unknown { reason: format!("Bootstrap failed: {}", e) },
-                duration: start.elapsed(),
-            };
+        let result = |availability: Availability, explanation, headers, endpoint, source: ProbeSource| ProbeResult {
+            domain: input.to_string(),
+            confidence: source.confidence(&availability),
+            availability,
+            duration: start.elapsed(),
+            headers,
+            endpoint,
+            explanation,
+            source,
+            raw: None,
+        };
+
+        // RDAP and WHOIS only speak punycode; the result keeps what was asked
+        let domain = match to_ascii_domain(input) {
+            Ok(ascii) => ascii,
+            Err(reason) => {
+                let unknown = Availability::Unknown { reason: ProbeError::InvalidName };
+                return Queued::Done((result(unknown, reason, Vec::new(), None, ProbeSource::Rdap), None));
+            }
+        };
+        let domain = domain.as_str();
+
+        // Names the registry would refuse are settled without asking it
+        if let Err(problem) = check_name(domain) {
+            let unknown = Availability::Unknown { reason: ProbeError::InvalidName };
+            let explanation = format!("Not probed: {}", problem);
+            return Queued::Done((result(unknown, explanation, Vec::new(), None, ProbeSource::Rdap), None));
         }
 
-        let tld = match extract_tld(domain) {
-            Ok(t) => t,
+        let suffix = match extract_tld(domain) {
+            Ok(s) => s,
             Err(e) => {
-                return ProbeResult {
-                    domain: domain.to_string(),
-                    availability: Availability::Unknown { reason: e.to_string() },
-                    duration: start.elapsed(),
-                };
+                let unknown = Availability::Unknown { reason: ProbeError::InvalidName };
+                return Queued::Done((result(unknown, e.to_string(), Vec::new(), None, ProbeSource::Rdap), None));
             }
         };
+        tracing::Span::current().record("tld", suffix.as_str());
+
+        let maintenance = self.maintenance_until(&suffix);
+        if let (Some(until), true) = (maintenance, self.config.defer_during_maintenance) {
+            let unknown = Availability::Unknown { reason: ProbeError::Maintenance };
+            let explanation = format!("Deferred: .{} registry maintenance until {} UTC", suffix, format_hhmm(until));
+            return Queued::Done((result(unknown, explanation, Vec::new(), None, ProbeSource::Rdap), None));
+        }
 
-        let endpoint = match self.registry.get_endpoint(&tld) {
-            Some(e) => e,
+        let default_chain;
+        let chain = if retrying.is_some() {
+            default_chain = vec![CheckStep::Rdap, CheckStep::Whois];
+            &default_chain
+        } else if self.config.chain.is_empty() {
+            default_chain = CheckStep::default_chain(self.config.dns_first);
+            &default_chain
+        } else {
+            &self.config.chain
+        };
+
+        // Steps are asked until one decides. Explanations add up; the
+        // first endpoint and record found are kept, the last raw answer
+        let mut explanations = Vec::new();
+        let (mut headers, mut endpoint, mut record, mut raw) = (Vec::new(), None, None, None);
+        let mut last: Option<StepAnswer> = None;
+        for step in chain {
+            let mut answer = match step {
+                CheckStep::Dns => self.dns_step(domain).await,
+                CheckStep::Rdap => {
+                    let skip = retrying.and_then(|failed| failed.endpoint.as_deref());
+                    let answer = self.rdap_step(domain, input, &suffix, detailed, skip).await;
+                    if answer.requeued {
+                        return Queued::Requeued(input.to_string());
+                    }
+                    answer
+                }
+                CheckStep::Whois if self.use_whois() => {
+                    let answer = self.whois_fallback(domain, input, detailed).await;
+                    StepAnswer {
+                        raw: self.kept_raw(answer.raw),
+                        record: answer.record,
+                        ..StepAnswer::new(answer.availability, answer.explanation, ProbeSource::Whois)
+                    }
+                }
+                CheckStep::Whois => continue,
+                CheckStep::Custom(checker) => Self::custom_step(checker.as_ref(), domain).await,
+            };
+            if !answer.explanation.is_empty() {
+                explanations.push(std::mem::take(&mut answer.explanation));
+            }
+            headers.append(&mut answer.headers);
+            endpoint = endpoint.or(answer.endpoint.take());
+            record = record.or(answer.record.take());
+            raw = answer.raw.take().or(raw);
+            let decided = !answer.availability.is_unknown();
+            last = Some(answer);
+            if decided {
+                break;
+            }
+        }
+        let Some(last) = last else {
+            let reason = ProbeError::NoEndpoint("No step of the probe chain could be asked".to_string());
+            let explanation = reason.to_string();
+            return Queued::Done((result(Availability::Unknown { reason }, explanation, Vec::new(), None, ProbeSource::Rdap), None));
+        };
+
+        let mut explanation = explanations.join("; ");
+        if let (true, Availability::Unknown { reason }) = (explanation.is_empty(), &last.availability) {
+            explanation = reason.to_string();
+        }
+        let availability = match (last.availability, maintenance) {
+            (Availability::Unknown { .. }, Some(until)) => {
+                explanation = format!(
+                    "{}; .{} registry maintenance until {} UTC",
+                    explanation,
+                    suffix,
+                    format_hhmm(until)
+                );
+                Availability::Unknown { reason: ProbeError::Maintenance }
+            }
+            (availability, _) => availability,
+        };
+
+        if let Some(record) = record.as_mut().filter(|_| self.config.follow_referrals) {
+            if let Some(url) = record.referral.clone() {
+                // Registrar servers get their own limiter, keyed by origin
+                if let Ok(parsed) = reqwest::Url::parse(&url) {
+                    self.rate_limiters.acquire(&parsed.origin().ascii_serialization()).await;
+                }
+                if let Some(registrar) = fetch_record(&self.client, &url, self.config.timeout).await {
+                    record.merge_referral(registrar);
+                }
+            }
+        }
+
+        if let (Availability::Available, Some((phase, next))) = (&availability, self.launch_phase(&suffix)) {
+            explanation = match next {
+                Some(date) => format!("{}; .{} is in {} until {}, not open to everyone yet", explanation, suffix, phase, date),
+                None => format!("{}; .{} is in {}, not open to everyone yet", explanation, suffix, phase),
+            };
+        }
+
+        let mut found = result(availability, explanation, headers, endpoint, last.source);
+        if let Some(confidence) = last.confidence.filter(|_| !found.availability.is_unknown()) {
+            found.confidence = confidence;
+        }
+        found.raw = raw;
+        Queued::Done((found, record))
+    }
+
+    /// NS lookup, or an address lookup when the resolver can't be asked
+    /// directly. Only ever says taken; an address alone is weaker evidence
+    /// than a delegation.
+    async fn dns_step(&self, domain: &str) -> StepAnswer {
+        let taken = match is_delegated(&self.resolver, domain, self.config.timeout).await {
+            Some(delegated) => delegated.then_some(("domain is delegated in DNS (NS records)", Confidence::High)),
+            None => resolves(&self.resolver, domain, self.config.timeout)
+                .await
+                .then_some(("domain resolves in DNS", Confidence::Medium)),
+        };
+        match taken {
+            Some((explanation, confidence)) => StepAnswer {
+                confidence: Some(confidence),
+                ..StepAnswer::new(Availability::Taken, explanation.to_string(), ProbeSource::Dns)
+            },
             None => {
-                if self.config.whois_fallback {
-                    let availability = check_whois(domain, self.config.timeout).await;
-                    return ProbeResult {
-                        domain: domain.to_string(),
-                        availability,
-                        duration: start.elapsed(),
-                    };
+                let reason = ProbeError::AmbiguousResponse("Not delegated in DNS".to_string());
+                StepAnswer::new(Availability::Unknown { reason }, String::new(), ProbeSource::Dns)
+            }
+        }
+    }
+
+    /// The registry's RDAP answer: endpoints in turn (but `skip`) and
+    /// retries, then the canary check and confirmation of "available". A 429
+    /// pauses the endpoint and, while `rate_limit_requeues` allows, answers
+    /// `requeued` so the domain goes to the back of the queue.
+    async fn rdap_step(&self, domain: &str, input: &str, suffix: &str, detailed: bool, skip: Option<&str>) -> StepAnswer {
+        let unknown = |reason: ProbeError| {
+            let explanation = reason.to_string();
+            StepAnswer::new(Availability::Unknown { reason }, explanation, ProbeSource::Rdap)
+        };
+        if self.ensure_bootstrapped().await.is_err() {
+            // The failure itself is reported once via `bootstrap_state()`
+            return unknown(ProbeError::Bootstrap("RDAP bootstrap unavailable".to_string()));
+        }
+        let mut endpoints = self.registry.get_endpoints(suffix);
+        if let Some(skip) = skip {
+            endpoints.retain(|endpoint| endpoint != skip);
+            if endpoints.is_empty() {
+                return unknown(ProbeError::NoEndpoint(format!("No other RDAP endpoint for .{}", suffix)));
+            }
+        }
+        if endpoints.is_empty() {
+            return unknown(ProbeError::NoEndpoint(format!("No RDAP endpoint for .{}", suffix)));
+        }
+        // Canaries sit directly under the TLD, whichever namespace the domain is in
+        let tld = suffix.rsplit('.').next().unwrap_or(suffix);
+
+        if self.config.discover_limits {
+            self.discover_limits(&endpoints[0]).await;
+        }
+
+        let requeues = self.requeues.get(domain).map_or(0, |n| *n);
+        let mut attempt = 0;
+        let (endpoint, rdap) = loop {
+            let (endpoint, rdap) = self.query_endpoints(&endpoints, domain, input, detailed).await;
+            let rate_limited = rdap.failure == Some(RetryOn::RateLimited);
+            if rate_limited {
+                self.emit(|| ProbeEvent::RateLimited {
+                    domain: input.to_string(),
+                    endpoint: endpoint.clone(),
+                    wait: rdap.retry_after,
+                });
+            }
+            let requeue = rate_limited && requeues < self.config.rate_limit_requeues;
+            let wait = match rdap.retry_after {
+                Some(wait) => Some(wait),
+                None if requeue => Some(backoff(self.config.backoff_base, requeues)),
+                None => None,
+            };
+            if let Some(wait) = wait {
+                // Everyone waits, not just this probe
+                self.rate_limiters.pause(&endpoint, wait.min(self.config.max_retry_after));
+            }
+            if requeue && wait.is_some_and(|wait| wait <= self.config.max_retry_after) {
+                self.requeues.insert(domain.to_string(), requeues + 1);
+                return StepAnswer { requeued: true, ..unknown(ProbeError::RateLimited) };
+            }
+            if attempt >= self.config.retries || !self.should_retry(rdap.failure) {
+                break (endpoint, rdap);
+            }
+            tokio::time::sleep(backoff(self.config.backoff_base, attempt)).await;
+            attempt += 1;
+        };
+        self.requeues.remove(domain);
+        let mut explanation = rdap.explanation;
+        let mut source = ProbeSource::Rdap;
+        let mut confidence = None;
+        if requeues > 0 {
+            explanation = format!("{} (after {} requeues on 429)", explanation, requeues);
+        }
+        if attempt > 0 {
+            explanation = format!("{} (after {} retries)", explanation, attempt);
+        }
+        let availability = match rdap.availability {
+            Availability::Available
+                if self.config.canary_check
+                    && domain != tld_canary(tld)
+                    && !self.endpoint_reliable(&endpoint, tld).await =>
+            {
+                let reason = ProbeError::AmbiguousResponse(format!("Unreliable endpoint: {} reported available", tld_canary(tld)));
+                explanation = format!("{}, but {} also reported available there", explanation, tld_canary(tld));
+                Availability::Unknown { reason }
+            }
+            Availability::Available if self.config.confirm_available => {
+                let whois = self.use_whois().then_some(self.whois.as_ref());
+                match confirm_available(whois, &self.resolver, domain, self.config.timeout).await {
+                    Some((availability, confirmation, backend)) => {
+                        explanation = format!("{}; {}", explanation, confirmation);
+                        // A confirmation leaves the registry's word standing
+                        if !availability.is_available() {
+                            source = backend;
+                        }
+                        availability
+                    }
+                    None => {
+                        explanation = format!("{}; unconfirmed: neither WHOIS nor DNS could tell", explanation);
+                        confidence = Some(Confidence::Medium);
+                        Availability::Available
+                    }
                 }
-                return ProbeResult {
-                    domain: domain.to_string(),
-                    availability: Availability::Unknown { 
-                        reason: format!("No RDAP endpoint for .{}", tld) 
-                    },
-                    duration: start.elapsed(),
-                };
             }
+            availability => availability,
         };
 
-        self.rate_limiters.acquire(&endpoint).await;
+        StepAnswer {
+            headers: rdap.headers,
+            endpoint: Some(endpoint),
+            record: rdap.record,
+            raw: rdap.raw,
+            confidence,
+            ..StepAnswer::new(availability, explanation, source)
+        }
+    }
 
-        let availability = check_rdap(&self.client, &endpoint, domain, self.config.timeout).await;
+    async fn custom_step(checker: &dyn Checker, domain: &str) -> StepAnswer {
+        let availability = checker.check(domain).await;
+        let verdict = match &availability {
+            Availability::Available => "available".to_string(),
+            Availability::Taken => "taken".to_string(),
+            Availability::Reserved => "reserved".to_string(),
+            Availability::Premium => "premium".to_string(),
+            Availability::Unknown { reason } => reason.to_string(),
+        };
+        StepAnswer {
+            confidence: Some(checker.confidence()),
+            ..StepAnswer::new(availability.clone(), format!("{}: {}", checker.name(), verdict), ProbeSource::Custom)
+        }
+    }
 
-        let availability = if matches!(availability, Availability::Unknown { .. }) && self.config.whois_fallback {
-            check_whois(domain, self.config.timeout).await
-        } else {
-            availability
+    /// Fills in `dnssec` from a DS lookup when the registry's answer didn't say.
+    async fn check_dnssec(&self, input: &str, record: &mut Option<DomainRecord>) {
+        if record.as_ref().is_some_and(|r| r.dnssec.is_some()) {
+            return;
+        }
+        let Ok(domain) = to_ascii_domain(input) else {
+            return;
         };
+        if let Some(signed) = is_signed(&self.resolver, &domain, self.config.timeout).await {
+            record.get_or_insert_with(DomainRecord::default).dnssec = Some(signed);
+        }
+    }
 
-        ProbeResult {
-            domain: domain.to_string(),
-            availability,
-            duration: start.elapsed(),
+    /// `raw` if results are to carry the registry's answer.
+    fn kept_raw(&self, raw: Option<String>) -> Option<String> {
+        raw.filter(|_| self.config.capture_raw)
+    }
+
+    /// End of the maintenance window `suffix`'s registry is in, if any.
+    fn maintenance_until(&self, suffix: &str) -> Option<u64> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.config
+            .maintenance
+            .iter()
+            .filter(|window| window.covers(suffix))
+            .find_map(|window| window.active_until(now))
+    }
+
+    /// Asks the base URLs in turn, moving on while one answers 5xx or can't
+    /// be reached. Endpoints with an open circuit are passed over. Returns
+    /// the last one asked with its answer. `input` is the domain as asked,
+    /// for events.
+    async fn query_endpoints(
+        &self,
+        endpoints: &[String],
+        domain: &str,
+        input: &str,
+        detailed: bool,
+    ) -> (String, RdapResponse) {
+        let mut failed = Vec::new();
+        let mut last: Option<(String, RdapResponse)> = None;
+        for endpoint in endpoints {
+            let slots = self.endpoint_slots(endpoint);
+            // Wait for a slot before a rate-limit token, so tokens aren't
+            // spent by probes that can't send yet. The circuit is checked
+            // last: it may have opened while we waited
+            self.emit(|| ProbeEvent::Queued { domain: input.to_string(), endpoint: endpoint.clone() });
+            let _permit = slots.acquire().await.expect("endpoint semaphore is never closed");
+            self.emit(|| ProbeEvent::Started { domain: input.to_string(), endpoint: endpoint.clone() });
+            if !self.breakers.allow(endpoint) {
+                tracing::debug!(endpoint, "circuit open, endpoint skipped");
+                continue;
+            }
+            if let Some((previous, _)) = last.take() {
+                failed.push(previous);
+            }
+            self.rate_limiters.acquire(endpoint).await;
+            let sent = Instant::now();
+            let rdap = check_rdap(
+                &self.client,
+                endpoint,
+                domain,
+                self.config.timeout,
+                &self.config.capture_headers,
+                detailed,
+                self.config.capture_raw,
+            )
+            .await;
+            self.metrics.record(endpoint, rdap.status, rdap.failure, sent.elapsed());
+            self.breakers.record(
+                endpoint,
+                matches!(rdap.failure, Some(RetryOn::Timeout | RetryOn::ServerError | RetryOn::Connection)),
+            );
+            let done = !rdap.server_failed();
+            last = Some((endpoint.clone(), rdap));
+            if done {
+                break;
+            }
+        }
+
+        let Some((endpoint, mut rdap)) = last else {
+            // Straight on to WHOIS rather than waiting out another timeout
+            let reason = ProbeError::NoEndpoint("RDAP endpoint failing, skipped".to_string());
+            let rdap = RdapResponse {
+                availability: Availability::Unknown { reason },
+                headers: Vec::new(),
+                explanation: format!("RDAP skipped: {} kept failing (circuit open)", endpoints.join(", ")),
+                record: None,
+                failure: None,
+                retry_after: None,
+                status: None,
+                raw: None,
+            };
+            return (endpoints[0].clone(), rdap);
+        };
+        if !failed.is_empty() {
+            rdap.explanation = format!("{} (after {} failed)", rdap.explanation, failed.join(", "));
+        }
+        (endpoint, rdap)
+    }
+
+    fn endpoint_slots(&self, endpoint: &str) -> Arc<Semaphore> {
+        self.in_flight
+            .entry(endpoint.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.config.max_concurrent_per_endpoint.max(1) as usize)))
+            .clone()
+    }
+
+    fn should_retry(&self, failure: Option<RetryOn>) -> bool {
+        failure.is_some_and(|failure| self.config.retry_on.contains(&failure))
+    }
+
+    /// WHOIS lookup; detailed probes also collect the registration record,
+    /// following thin registries' referral to the registrar. Transient
+    /// failures are retried like RDAP ones.
+    async fn whois_fallback(&self, domain: &str, input: &str, detailed: bool) -> WhoisAnswer {
+        self.emit(|| ProbeEvent::FallbackToWhois { domain: input.to_string() });
+        let mut attempt = 0;
+        loop {
+            let mut answer = if detailed {
+                self.whois.check_detailed(domain, self.config.timeout).await
+            } else {
+                self.whois.check(domain, self.config.timeout).await
+            };
+            if attempt >= self.config.retries || !self.should_retry(answer.failure) {
+                if attempt > 0 {
+                    answer.explanation = format!("{} (after {} retries)", answer.explanation, attempt);
+                }
+                return answer;
+            }
+            tokio::time::sleep(backoff(self.config.backoff_base, attempt)).await;
+            attempt += 1;
         }
     }
 
@@ -100,28 +854,223 @@ impl Prober {
     where
         I: IntoIterator<Item = String> + 'static,
     {
-        let domains: Vec<String> = domains.into_iter().collect();
-        
-        stream::iter(domains)
-            .map(move |domain| async move {
-                self.probe_one(&domain).await
-            })
-            .buffer_unordered(self.config.max_concurrent_per_endpoint as usize * 10)
+        self.probe_stream_cancellable(domains, CancellationToken::new())
+    }
+
+    /// `probe_stream` that ends as soon as `cancel` is cancelled. Probes in
+    /// flight are dropped mid-request and queued ones never start, so they
+    /// stop taking up rate-limit budget and concurrency slots.
+    pub fn probe_stream_cancellable<I>(&self, domains: I, cancel: CancellationToken) -> impl Stream<Item = ProbeResult> + '_
+    where
+        I: IntoIterator<Item = String> + 'static,
+    {
+        // The same registrable domain is only probed once per stream
+        // (`dedupe_domains`)
+        let concurrency = self.config.max_concurrent_per_endpoint as usize * 10;
+        let probe = move |domain: String| async move { self.probe_queued(&domain, false).await.map(|(result, _)| result) };
+        let results = requeuing(dedupe_domains(domains).0, probe, concurrency);
+        let retry = move |failed| async move { self.group_retry(failed, false).await.0 };
+        group_retried(results, |result| result, retry, concurrency, self.config.group_retry)
+            .take_until(Box::pin(cancel.cancelled_owned()))
+    }
+
+    /// `probe_stream` that owns its prober, so it can be moved into
+    /// `tokio::spawn` without cloning the `Prober`.
+    pub fn probe_stream_owned<I>(self: Arc<Self>, domains: I) -> impl Stream<Item = ProbeResult> + Send + 'static
+    where
+        I: IntoIterator<Item = String> + 'static,
+        I::IntoIter: Send,
+    {
+        let concurrency = self.config.max_concurrent_per_endpoint as usize * 10;
+        let enabled = self.config.group_retry;
+        let prober = Arc::clone(&self);
+        let probe = move |domain: String| {
+            let prober = Arc::clone(&self);
+            async move { prober.probe_queued(&domain, false).await.map(|(result, _)| result) }
+        };
+        let results = requeuing(dedupe_domains(domains).0, probe, concurrency);
+        let retry = move |failed| {
+            let prober = Arc::clone(&prober);
+            async move { prober.group_retry(failed, false).await.0 }
+        };
+        group_retried(results, |result| result, retry, concurrency, enabled)
+    }
+
+    /// `probe_stream` with `probe_one_detailed`, for callers that want the
+    /// registrar and dates of taken domains.
+    pub fn probe_stream_detailed<I>(&self, domains: I) -> impl Stream<Item = (ProbeResult, Option<DomainRecord>)> + '_
+    where
+        I: IntoIterator<Item = String> + 'static,
+    {
+        let concurrency = self.config.max_concurrent_per_endpoint as usize * 10;
+        let probe = move |domain: String| async move { self.probe_queued(&domain, true).await };
+        let results = requeuing(dedupe_domains(domains).0, probe, concurrency);
+        let retry = move |(failed, _)| async move { self.group_retry(failed, true).await };
+        group_retried(results, |(result, _)| result, retry, concurrency, self.config.group_retry)
+    }
+
+    /// Probes a domain again once every probe of its suffix in a stream
+    /// failed the same way, via the suffix's other RDAP base URLs or WHOIS.
+    /// The explanation says which answered; if neither did, the first
+    /// failure stands.
+    async fn group_retry(&self, failed: ProbeResult, detailed: bool) -> (ProbeResult, Option<DomainRecord>) {
+        let (mut found, record) = loop {
+            // The held-back failures are all that is left of the stream
+            if let Queued::Done(found) = self.probe_inner(&failed.domain, detailed, Some(&failed)).await {
+                break found;
+            }
+        };
+        let suffix = public_suffix(&failed.domain).unwrap_or_default().to_string();
+        if found.availability.is_unknown() {
+            let mut failed = failed;
+            failed.explanation = format!("{}; retried after every .{} probe failed: {}", failed.explanation, suffix, found.explanation);
+            return (failed, None);
+        }
+        let via = match found.source {
+            ProbeSource::Rdap => found.endpoint.clone().unwrap_or_default(),
+            source => source.name().to_uppercase(),
+        };
+        found.explanation = format!("{}; retried via {} after every .{} probe failed", found.explanation, via, suffix);
+        found.duration += failed.duration;
+        tracing::debug!(domain = %found.domain, via, "group retry answered");
+        if let Some(cache) = &self.cache {
+            cache.insert(&found, detailed, record.as_ref());
+        }
+        #[cfg(feature = "sqlite-cache")]
+        if let Some(db) = &self.db {
+            db.record(&found).await;
+        }
+        (found, record)
     }
 }
 
+fn format_hhmm(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 3600 % 24, secs / 60 % 60)
+}
+
 impl Default for Prober {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Builds a `Prober` from parts the caller already has. Anything not set
+/// is created from the `ProbeConfig`, as `Prober::with_config` does.
+#[derive(Default)]
+pub struct ProberBuilder {
+    config: ProbeConfig,
+    client: Option<Client>,
+    registry: Option<Arc<EndpointRegistry>>,
+    max_rate_per_endpoint: Option<u32>,
+    endpoint_rates: Vec<(String, f64)>,
+}
+
+impl ProberBuilder {
+    pub fn config(mut self, config: ProbeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sends RDAP and bootstrap requests with `client`, middleware and
+    /// all. The config's `user_agent`, `extra_headers`, `proxy` and
+    /// `resolver` are then up to that client (WHOIS and the DNS checks
+    /// still use them); `timeout` is still set on each request.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Looks endpoints up in `registry`, which may be shared with other
+    /// probers or hold `EndpointRegistry::set_endpoints` entries. It is
+    /// bootstrapped on first use unless it already is.
+    pub fn registry(mut self, registry: Arc<EndpointRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Requests per second to any one endpoint, instead of the config's
+    /// `max_rate_per_endpoint`.
+    pub fn max_rate_per_endpoint(mut self, per_second: u32) -> Self {
+        self.max_rate_per_endpoint = Some(per_second.max(1));
+        self
+    }
+
+    /// A lower rate for one RDAP base URL, e.g. a registry with a
+    /// documented limit. Rates at or above the default are ignored.
+    pub fn endpoint_rate(mut self, endpoint: &str, per_second: f64) -> Self {
+        self.endpoint_rates.push((endpoint.to_string(), per_second));
+        self
+    }
+
+    pub fn build(self) -> Prober {
+        let config = self.config;
+        let resolver = Resolver::new(&config.resolver);
+        let client = self.client.unwrap_or_else(|| {
+            create_http_pool(
+                config.timeout,
+                &config.user_agent,
+                &config.extra_headers,
+                config.proxy.as_ref().map(Proxy::for_http),
+                &resolver,
+            )
+        });
+        let rate_limiters = EndpointRateLimiters::new(self.max_rate_per_endpoint.unwrap_or(config.max_rate_per_endpoint));
+        for (endpoint, per_second) in &self.endpoint_rates {
+            rate_limiters.limit(endpoint.trim_end_matches('/'), *per_second);
+        }
+        Prober {
+            client,
+            registry: self.registry.unwrap_or_default(),
+            rate_limiters: Arc::new(rate_limiters),
+            in_flight: Arc::new(DashMap::new()),
+            breakers: Arc::new(EndpointBreakers::new(config.breaker_threshold, config.breaker_cooldown)),
+            metrics: Arc::new(Metrics::default()),
+            cache: config.cache_ttl.map(|ttl| Arc::new(ProbeCache::new(ttl))),
+            #[cfg(feature = "sqlite-cache")]
+            db: config.result_db.as_deref().and_then(|path| match ResultDb::open(path, config.result_db_max_age) {
+                Ok(db) => Some(Arc::new(db)),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "result db unavailable, not recording");
+                    None
+                }
+            }),
+            whois: Arc::new(WhoisClient::new(
+                config.whois_rate_per_server,
+                config.whois_max_connections_per_server,
+                &config.whois_servers,
+                config.proxy.clone(),
+                resolver.clone(),
+            )),
+            help: Arc::new(DashMap::new()),
+            reliable: Arc::new(DashMap::new()),
+            requeues: Arc::new(DashMap::new()),
+            refreshing: Arc::new(AtomicBool::new(false)),
+            events: broadcast::channel(EVENT_BUFFER).0,
+            resolver,
+            config,
+        }
+    }
+}
+
 impl Clone for Prober {
     fn clone(&self) -> Self {
         Self {
             client: self.client.clone(),
             registry: Arc::clone(&self.registry),
             rate_limiters: Arc::clone(&self.rate_limiters),
+            in_flight: Arc::clone(&self.in_flight),
+            breakers: Arc::clone(&self.breakers),
+            metrics: Arc::clone(&self.metrics),
+            cache: self.cache.clone(),
+            #[cfg(feature = "sqlite-cache")]
+            db: self.db.clone(),
+            whois: Arc::clone(&self.whois),
+            help: Arc::clone(&self.help),
+            reliable: Arc::clone(&self.reliable),
+            requeues: Arc::clone(&self.requeues),
+            refreshing: Arc::clone(&self.refreshing),
+            events: self.events.clone(),
+            resolver: self.resolver.clone(),
             config: self.config.clone(),
         }
     }
@@ -0,0 +1,319 @@
+use crate::resolver::Resolver;
+use reqwest::Url;
+use std::{
+    fmt, io,
+    net::IpAddr,
+    str::FromStr,
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// Longest request or response head read from a client or HTTP proxy.
+const MAX_HEAD: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ProxyError {
+    #[error("invalid proxy URL \"{0}\": {1}")]
+    InvalidUrl(String, String),
+    #[error("unsupported proxy scheme {0}://, expected http, socks5 or socks5h")]
+    UnsupportedScheme(String),
+    /// SOCKS5 sends host names, user names and passwords with a one-byte
+    /// length
+    #[error("{0} is longer than the 255 bytes SOCKS5 allows")]
+    TooLong(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProxyKind {
+    /// `CONNECT` tunnels through an HTTP proxy
+    Http,
+    /// SOCKS5; with `remote_dns` (`socks5h://`) host names are resolved
+    /// by the proxy, as Tor requires
+    Socks5 { remote_dns: bool },
+}
+
+/// Where RDAP and WHOIS connections go through (`ProbeConfig::proxy`):
+/// `http://[user:pass@]host:port`, `socks5://…` or `socks5h://…`.
+#[derive(Clone)]
+pub struct Proxy {
+    kind: ProxyKind,
+    host: String,
+    port: u16,
+    credentials: Option<(String, String)>,
+    /// What reqwest makes of the URL; it speaks SOCKS5 itself
+    http: reqwest::Proxy,
+}
+
+impl FromStr for Proxy {
+    type Err = ProxyError;
+
+    fn from_str(url: &str) -> Result<Self, ProxyError> {
+        let parsed = Url::parse(url.trim()).map_err(|e| ProxyError::InvalidUrl(url.to_string(), e.to_string()))?;
+        let (kind, default_port) = match parsed.scheme() {
+            "http" => (ProxyKind::Http, 80),
+            "socks5" => (ProxyKind::Socks5 { remote_dns: false }, 1080),
+            "socks5h" => (ProxyKind::Socks5 { remote_dns: true }, 1080),
+            scheme => return Err(ProxyError::UnsupportedScheme(scheme.to_string())),
+        };
+        let host = parsed
+            .host_str()
+            .filter(|h| !h.is_empty())
+            .ok_or_else(|| ProxyError::InvalidUrl(url.to_string(), "no host".to_string()))?;
+        let credentials = (!parsed.username().is_empty()).then(|| {
+            (decode(parsed.username()), parsed.password().map(decode).unwrap_or_default())
+        });
+        if let (ProxyKind::Socks5 { .. }, Some((user, password))) = (kind, &credentials) {
+            short_enough(user, "proxy user name")?;
+            short_enough(password, "proxy password")?;
+        }
+        let http = reqwest::Proxy::all(parsed.as_str()).map_err(|e| ProxyError::InvalidUrl(url.to_string(), e.to_string()))?;
+        Ok(Self {
+            kind,
+            host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            port: parsed.port().unwrap_or(default_port),
+            credentials,
+            http,
+        })
+    }
+}
+
+/// The URL without the credentials.
+impl fmt::Display for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scheme = match self.kind {
+            ProxyKind::Http => "http",
+            ProxyKind::Socks5 { remote_dns: false } => "socks5",
+            ProxyKind::Socks5 { remote_dns: true } => "socks5h",
+        };
+        write!(f, "{}://{}:{}", scheme, bracketed(&self.host), self.port)
+    }
+}
+
+impl PartialEq for Proxy {
+    fn eq(&self, other: &Self) -> bool {
+        (self.kind, &self.host, self.port, &self.credentials) == (other.kind, &other.host, other.port, &other.credentials)
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Proxy({})", self)
+    }
+}
+
+impl Proxy {
+    /// Opens a TCP connection to `host:port` through the proxy. Host names
+    /// the proxy doesn't resolve itself are looked up with `resolver`.
+    pub(crate) async fn connect(&self, host: &str, port: u16, resolver: &Resolver) -> io::Result<TcpStream> {
+        let mut stream = resolver.connect(&self.host, self.port).await?;
+        match self.kind {
+            ProxyKind::Http => self.http_connect(&mut stream, host, port).await?,
+            ProxyKind::Socks5 { remote_dns } => self.socks5_connect(&mut stream, host, port, remote_dns, resolver).await?,
+        }
+        Ok(stream)
+    }
+
+    /// Reaches the proxy and, for SOCKS, logs in, without connecting
+    /// anywhere through it.
+    pub(crate) async fn check(&self, resolver: &Resolver) -> io::Result<()> {
+        let mut stream = resolver.connect(&self.host, self.port).await?;
+        if let ProxyKind::Socks5 { .. } = self.kind {
+            self.socks5_greet(&mut stream).await?;
+        }
+        Ok(())
+    }
+
+    /// The same proxy for the HTTP client, checked when the URL was parsed.
+    pub(crate) fn for_http(&self) -> reqwest::Proxy {
+        self.http.clone()
+    }
+
+    async fn http_connect(&self, stream: &mut TcpStream, host: &str, port: u16) -> io::Result<()> {
+        let target = format!("{}:{}", bracketed(host), port);
+        let mut request = format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n", target, target);
+        if let Some((user, password)) = &self.credentials {
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", base64(format!("{}:{}", user, password).as_bytes())));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        let (head, _) = read_head(stream).await?;
+        let status = head.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some("200") => Ok(()),
+            Some("407") => Err(io::Error::new(io::ErrorKind::PermissionDenied, "proxy authentication required")),
+            _ => Err(io::Error::new(io::ErrorKind::ConnectionRefused, format!("proxy refused the tunnel: {}", status))),
+        }
+    }
+
+    async fn socks5_greet(&self, stream: &mut TcpStream) -> io::Result<()> {
+        // Greeting: no authentication, or user/password when we have them
+        let methods: &[u8] = if self.credentials.is_some() { &[0x00, 0x02] } else { &[0x00] };
+        let mut greeting = vec![5, methods.len() as u8];
+        greeting.extend_from_slice(methods);
+        stream.write_all(&greeting).await?;
+        let mut choice = [0; 2];
+        stream.read_exact(&mut choice).await?;
+        match (choice, &self.credentials) {
+            ([5, 0x00], _) => {}
+            ([5, 0x02], Some((user, password))) => {
+                let mut auth = vec![1, user.len() as u8];
+                auth.extend_from_slice(user.as_bytes());
+                auth.push(password.len() as u8);
+                auth.extend_from_slice(password.as_bytes());
+                stream.write_all(&auth).await?;
+                let mut status = [0; 2];
+                stream.read_exact(&mut status).await?;
+                if status[1] != 0 {
+                    return Err(io::Error::new(io::ErrorKind::PermissionDenied, "SOCKS5 authentication failed"));
+                }
+            }
+            ([5, _], _) => return Err(io::Error::new(io::ErrorKind::PermissionDenied, "SOCKS5 proxy wants authentication")),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "not a SOCKS5 proxy")),
+        }
+        Ok(())
+    }
+
+    async fn socks5_connect(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+        remote_dns: bool,
+        resolver: &Resolver,
+    ) -> io::Result<()> {
+        let ip = match host.parse::<IpAddr>() {
+            Ok(ip) => Some(ip),
+            Err(_) if remote_dns => {
+                short_enough(host, "host name").map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                None
+            }
+            Err(_) => Some(
+                resolver
+                    .lookup(host, port)
+                    .await?
+                    .first()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} has no address", host)))?
+                    .ip(),
+            ),
+        };
+        self.socks5_greet(stream).await?;
+        let mut request = vec![5, 1, 0];
+        match ip {
+            Some(IpAddr::V4(ip)) => {
+                request.push(1);
+                request.extend_from_slice(&ip.octets());
+            }
+            Some(IpAddr::V6(ip)) => {
+                request.push(4);
+                request.extend_from_slice(&ip.octets());
+            }
+            None => {
+                request.extend_from_slice(&[3, host.len() as u8]);
+                request.extend_from_slice(host.as_bytes());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await?;
+
+        let mut reply = [0; 4];
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0 {
+            let (kind, reason) = match reply[1] {
+                2 => (io::ErrorKind::PermissionDenied, "not allowed by the proxy's rules"),
+                3 => (io::ErrorKind::NetworkUnreachable, "network unreachable"),
+                4 => (io::ErrorKind::HostUnreachable, "host unreachable"),
+                5 => (io::ErrorKind::ConnectionRefused, "connection refused"),
+                6 => (io::ErrorKind::TimedOut, "TTL expired"),
+                _ => (io::ErrorKind::Other, "proxy failure"),
+            };
+            return Err(io::Error::new(kind, format!("SOCKS5: {}", reason)));
+        }
+        // The address the proxy bound, which nobody needs
+        let bound = match reply[3] {
+            1 => 4,
+            4 => 16,
+            3 => stream.read_u8().await? as usize,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "bad SOCKS5 reply")),
+        };
+        let mut rest = vec![0; bound + 2];
+        stream.read_exact(&mut rest).await.map(|_| ())
+    }
+}
+
+/// Reads up to the blank line ending an HTTP head; returns the head and
+/// whatever was read past it.
+async fn read_head(stream: &mut TcpStream) -> io::Result<(String, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            buf.truncate(end);
+            return Ok((String::from_utf8_lossy(&buf).into_owned(), rest));
+        }
+        if buf.len() > MAX_HEAD {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "HTTP head too long"));
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+fn short_enough(value: &str, what: &'static str) -> Result<(), ProxyError> {
+    if value.len() > u8::MAX as usize {
+        return Err(ProxyError::TooLong(what));
+    }
+    Ok(())
+}
+
+fn bracketed(host: &str) -> String {
+    if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+/// Undoes the percent-encoding of a URL's user info.
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%').then(|| s.get(i + 1..i + 3)).flatten().and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
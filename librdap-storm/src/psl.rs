@@ -0,0 +1,105 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+/// The ICANN section of the Public Suffix List, refreshed with
+/// `scripts/update-psl.sh`.
+const BUILTIN_RULES: &str = include_str!("public_suffix_list.dat");
+
+/// Public suffix rules (https://publicsuffix.org/list/): exact suffixes,
+/// `*.` wildcards and `!` exceptions.
+#[derive(Debug, Clone, Default)]
+pub struct PublicSuffixList {
+    rules: HashSet<String>,
+    wildcards: HashSet<String>,
+    exceptions: HashSet<String>,
+}
+
+impl PublicSuffixList {
+    /// Parses the list format: one rule per line, `//` comments, and only
+    /// the first word of a line counts. The private section of a full
+    /// `public_suffix_list.dat` is skipped, since its suffixes aren't
+    /// registries; internationalized rules are kept in their ASCII form.
+    pub fn parse(text: &str) -> Self {
+        let mut list = Self::default();
+        for line in text.lines() {
+            if line.contains("===BEGIN PRIVATE DOMAINS===") {
+                break;
+            }
+            let Some(rule) = line.split_whitespace().next() else {
+                continue;
+            };
+            if rule.starts_with("//") {
+                continue;
+            }
+            let mut rule = rule.to_lowercase();
+            if !rule.is_ascii() {
+                // Domains are probed in their ASCII form
+                let name = rule.trim_start_matches(['!', '*', '.']);
+                let Ok(ascii) = idna::domain_to_ascii(name) else {
+                    continue;
+                };
+                rule = format!("{}{}", &rule[..rule.len() - name.len()], ascii);
+            }
+            if let Some(exception) = rule.strip_prefix('!') {
+                list.exceptions.insert(exception.to_string());
+            } else if let Some(parent) = rule.strip_prefix("*.") {
+                list.wildcards.insert(parent.to_string());
+            } else {
+                list.rules.insert(rule);
+            }
+        }
+        list
+    }
+
+    /// The bundled list, parsed once.
+    pub fn builtin() -> &'static Self {
+        static LIST: OnceLock<PublicSuffixList> = OnceLock::new();
+        LIST.get_or_init(|| Self::parse(BUILTIN_RULES))
+    }
+
+    /// The public suffix of `domain`, e.g. `co.uk` for `foo.co.uk`: the
+    /// longest matching rule, with exceptions beating wildcards. Domains
+    /// no rule covers fall back to their last label.
+    pub fn suffix<'a>(&self, domain: &'a str) -> Option<&'a str> {
+        let domain = domain.trim_end_matches('.');
+        if domain.is_empty() || domain.split('.').any(str::is_empty) {
+            return None;
+        }
+        let lower = domain.to_lowercase();
+
+        // Every suffix of the domain, longest first, as byte offsets
+        let starts: Vec<usize> = std::iter::once(0)
+            .chain(lower.match_indices('.').map(|(i, _)| i + 1))
+            .collect();
+        for (n, &start) in starts.iter().enumerate() {
+            let candidate = &lower[start..];
+            if self.exceptions.contains(candidate) {
+                // `!www.ck`: the suffix is the exception minus its first label
+                return starts.get(n + 1).map(|&s| &domain[s..]);
+            }
+            if self.rules.contains(candidate) {
+                return Some(&domain[start..]);
+            }
+            if let Some(&parent) = starts.get(n + 1) {
+                if self.wildcards.contains(&lower[parent..]) {
+                    return Some(&domain[start..]);
+                }
+            }
+        }
+        starts.last().map(|&s| &domain[s..])
+    }
+
+    /// Splits a domain into the label registered under its public suffix
+    /// (and anything left of it) and the suffix: `("foo", "co.uk")`. `None`
+    /// when the domain is itself a public suffix.
+    pub fn split<'a>(&self, domain: &'a str) -> Option<(&'a str, &'a str)> {
+        let domain = domain.trim_end_matches('.');
+        let suffix = self.suffix(domain)?;
+        let name = domain.strip_suffix(suffix)?.strip_suffix('.')?;
+        (!name.is_empty()).then_some((name, suffix))
+    }
+}
+
+/// The public suffix of `domain` under the built-in list.
+pub fn public_suffix(domain: &str) -> Option<&str> {
+    PublicSuffixList::builtin().suffix(domain)
+}
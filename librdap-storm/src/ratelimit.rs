@@ -4,12 +4,18 @@ use governor::{
     state::{InMemoryState, NotKeyed},
     Quota, RateLimiter,
 };
-use std::{num::NonZeroU32, sync::Arc};
+use std::{
+    num::NonZeroU32,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 type Limiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
 
 pub struct EndpointRateLimiters {
     limiters: DashMap<String, Arc<Limiter>>,
+    /// Endpoints that asked us to back off, e.g. with `Retry-After`
+    paused_until: DashMap<String, Instant>,
     default_rate: u32,
 }
 
@@ -17,13 +23,39 @@ impl EndpointRateLimiters {
     pub fn new(default_rate_per_second: u32) -> Self {
         Self {
             limiters: DashMap::new(),
+            paused_until: DashMap::new(),
             default_rate: default_rate_per_second,
         }
     }
 
     pub async fn acquire(&self, endpoint: &str) {
+        let start = Instant::now();
+        // A pause may be extended while we sleep through it
+        loop {
+            let until = self.paused_until.get(endpoint).map(|u| *u);
+            match until {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until.into()).await,
+                _ => break,
+            }
+        }
         let limiter = self.get_or_create(endpoint);
         limiter.until_ready().await;
+
+        let waited = start.elapsed();
+        if waited >= Duration::from_millis(1) {
+            tracing::trace!(endpoint, waited_ms = waited.as_millis() as u64, "rate limit wait");
+        }
+    }
+
+    /// Holds every request to `endpoint` for `duration`; an existing
+    /// longer pause is kept.
+    pub fn pause(&self, endpoint: &str, duration: Duration) {
+        tracing::debug!(endpoint, pause_ms = duration.as_millis() as u64, "pausing endpoint");
+        let until = Instant::now() + duration;
+        let mut entry = self.paused_until.entry(endpoint.to_string()).or_insert(until);
+        if *entry < until {
+            *entry = until;
+        }
     }
 
     fn get_or_create(&self, endpoint: &str) -> Arc<Limiter> {
@@ -36,4 +68,14 @@ impl EndpointRateLimiters {
             .clone()
     }
 
+    /// Slows an endpoint below the default rate, e.g. to a documented limit.
+    /// Rates at or above the default are ignored.
+    pub fn limit(&self, endpoint: &str, per_second: f64) {
+        if per_second <= 0.0 || per_second >= self.default_rate as f64 {
+            return;
+        }
+        if let Some(quota) = Quota::with_period(Duration::from_secs_f64(1.0 / per_second)) {
+            self.limiters.insert(endpoint.to_string(), Arc::new(RateLimiter::direct(quota)));
+        }
+    }
 }
@@ -1,33 +1,218 @@
-use crate::types::Availability;
-use reqwest::{Client, StatusCode};
-use std::time::Duration;
+use crate::{
+    canary::is_rdap_response, maintenance::days_from_civil, record::DomainRecord, retry::RetryOn,
+    types::{Availability, ProbeError},
+};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Client, StatusCode,
+};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::Empty;
 
+pub struct RdapResponse {
+    pub availability: Availability,
+    pub headers: Vec<(String, String)>,
+    /// e.g. "RDAP 404 from rdap.verisign.com"
+    pub explanation: String,
+    /// Parsed body of a 200 answer, when requested
+    pub record: Option<DomainRecord>,
+    /// What went wrong, when it may go right on another attempt
+    pub failure: Option<RetryOn>,
+    /// `Retry-After` of a 429 or 503 answer
+    pub retry_after: Option<Duration>,
+    /// HTTP status, when an answer came
+    pub status: Option<u16>,
+    /// Response body, when asked to keep it
+    pub raw: Option<String>,
+}
+
+impl RdapResponse {
+    /// The server failed (5xx) or couldn't be reached, so an alternate
+    /// base URL may do better.
+    pub fn server_failed(&self) -> bool {
+        matches!(self.failure, Some(RetryOn::ServerError | RetryOn::Connection))
+    }
+}
+
+#[tracing::instrument(
+    name = "rdap",
+    skip(client, timeout, capture_headers, parse_record, capture_raw),
+    fields(status = Empty, latency_ms = Empty)
+)]
 pub async fn check_rdap(
     client: &Client,
     endpoint: &str,
     domain: &str,
     timeout: Duration,
-) -> Availability {
+    capture_headers: &[String],
+    parse_record: bool,
+    capture_raw: bool,
+) -> RdapResponse {
     let url = format!("{}/domain/{}", endpoint, domain);
     
+    let start = Instant::now();
     let result = tokio::time::timeout(timeout, client.get(&url).send()).await;
+    let span = tracing::Span::current();
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
     
-    match result {
-        Ok(Ok(response)) => match response.status() {
-            StatusCode::NOT_FOUND => Availability::Available,
-            StatusCode::OK => Availability::Taken,
-            StatusCode::TOO_MANY_REQUESTS => {
-                Availability::Unknown { reason: "Rate limited".to_string() }
+    let host = reqwest::Url::parse(endpoint)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| endpoint.to_string());
+
+    let mut headers = Vec::new();
+    let mut explanation = None;
+    let mut record = None;
+    let failure;
+    let mut retry_after = None;
+    let mut status = None;
+    let mut raw = None;
+    let availability = match result {
+        Ok(Ok(response)) => {
+            span.record("status", response.status().as_u16());
+            status = Some(response.status().as_u16());
+            headers = select_headers(response.headers(), capture_headers);
+            if matches!(response.status(), StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE) {
+                retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_retry_after);
             }
-            status => Availability::Unknown {
-                reason: format!("HTTP {}", status.as_u16()),
-            },
-        },
-        Ok(Err(e)) => Availability::Unknown {
-            reason: format!("Request failed: {}", e),
-        },
-        Err(_) => Availability::Unknown {
-            reason: "Timeout".to_string(),
-        },
+            failure = match response.status() {
+                status if status.is_server_error() => Some(RetryOn::ServerError),
+                StatusCode::TOO_MANY_REQUESTS => Some(RetryOn::RateLimited),
+                _ => None,
+            };
+            explanation = Some(format!("RDAP {} from {}", response.status().as_u16(), host));
+            let mut availability = match response.status() {
+                StatusCode::NOT_FOUND => Availability::Available,
+                StatusCode::OK if !is_rdap_response(&response) => Availability::Unknown {
+                    reason: ProbeError::AmbiguousResponse("Non-RDAP response (captive portal?)".to_string()),
+                },
+                StatusCode::OK => Availability::Taken,
+                StatusCode::TOO_MANY_REQUESTS => {
+                    Availability::Unknown { reason: ProbeError::RateLimited }
+                }
+                status => Availability::Unknown {
+                    reason: ProbeError::TransportError(format!("HTTP {}", status.as_u16())),
+                },
+            };
+            if availability.is_taken() || capture_raw {
+                let body = tokio::time::timeout(timeout, response.bytes()).await.ok().and_then(Result::ok);
+                if capture_raw {
+                    raw = body
+                        .as_ref()
+                        .filter(|body| !body.is_empty())
+                        .map(|body| String::from_utf8_lossy(body).into_owned());
+                }
+                let parsed = body
+                    .filter(|_| availability.is_taken())
+                    .and_then(|body| DomainRecord::from_json(&body).ok());
+                if let Some(held) = parsed.as_ref().and_then(unregistered_status) {
+                    let marked = if held.is_reserved() { "reserved" } else { "premium" };
+                    explanation = explanation.map(|e| format!("{}, status marks it {}", e, marked));
+                    availability = held;
+                }
+                record = parsed.filter(|_| parse_record);
+            }
+            availability
+        }
+        Ok(Err(e)) => {
+            failure = if e.is_timeout() {
+                Some(RetryOn::Timeout)
+            } else if e.is_connect() || e.is_request() {
+                Some(RetryOn::Connection)
+            } else {
+                None
+            };
+            let reason = if e.is_timeout() {
+                ProbeError::Timeout
+            } else {
+                ProbeError::TransportError(format!("Request failed: {}", e))
+            };
+            Availability::Unknown { reason }
+        }
+        Err(_) => {
+            failure = Some(RetryOn::Timeout);
+            Availability::Unknown { reason: ProbeError::Timeout }
+        }
+    };
+
+    let explanation = explanation.unwrap_or_else(|| match &availability {
+        Availability::Unknown { reason } => format!("RDAP query to {} failed: {}", host, reason),
+        _ => format!("RDAP answer from {}", host),
+    });
+    tracing::debug!(?availability, ?failure, ?retry_after, "{}", explanation);
+
+    RdapResponse { availability, headers, explanation, record, failure, retry_after, status, raw }
+}
+
+/// Some registries answer for names they hold back or sell at a premium,
+/// saying so in the record's status values.
+fn unregistered_status(record: &DomainRecord) -> Option<Availability> {
+    record.statuses.iter().find_map(|status| {
+        let status = status.to_lowercase();
+        if status.contains("reserved") {
+            Some(Availability::Reserved)
+        } else if status.contains("premium") {
+            Some(Availability::Premium)
+        } else {
+            None
+        }
+    })
+}
+
+/// `Retry-After` as delay seconds or an HTTP date (`Wed, 21 Oct 2015
+/// 07:28:00 GMT`); a date in the past means no wait.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| m == month)? as i64 + 1;
+    let mut hms = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (h, m, s) = (hms.next()??, hms.next()??, hms.next()??);
+    let at = days_from_civil(year.parse().ok()?, month, day.parse().ok()?) * 86400 + h * 3600 + m * 60 + s;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    Some(Duration::from_secs(at.saturating_sub(now).max(0) as u64))
+}
+
+/// Fetches a domain record from a full RDAP URL, such as a registry's
+/// referral to the registrar. Any failure yields `None`.
+pub async fn fetch_record(client: &Client, url: &str, timeout: Duration) -> Option<DomainRecord> {
+    let response = tokio::time::timeout(timeout, client.get(url).send()).await.ok()?.ok()?;
+    if response.status() != StatusCode::OK || !is_rdap_response(&response) {
+        return None;
     }
+    let body = tokio::time::timeout(timeout, response.bytes()).await.ok()?.ok()?;
+    DomainRecord::from_json(&body).ok()
+}
+
+/// Picks the headers named in `wanted` (case-insensitive; a trailing `*`
+/// matches by prefix) out of a response.
+fn select_headers(headers: &HeaderMap, wanted: &[String]) -> Vec<(String, String)> {
+    if wanted.is_empty() {
+        return Vec::new();
+    }
+
+    headers
+        .iter()
+        .filter(|(name, _)| {
+            let name = name.as_str();
+            wanted.iter().any(|w| match w.strip_suffix('*') {
+                Some(prefix) => name.starts_with(&prefix.to_lowercase()),
+                None => name.eq_ignore_ascii_case(w),
+            })
+        })
+        .filter_map(|(name, value)| {
+            value.to_str().ok().map(|v| (name.as_str().to_string(), v.to_string()))
+        })
+        .collect()
 }
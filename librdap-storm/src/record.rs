@@ -0,0 +1,314 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Registration details from an RDAP domain response (RFC 9083). Dates are
+/// kept as the RFC 3339 strings the server sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct DomainRecord {
+    /// Domain name as the registry spells it
+    pub ldh_name: Option<String>,
+    /// Name of the entity with the `registrar` role
+    pub registrar: Option<String>,
+    pub created: Option<String>,
+    pub expires: Option<String>,
+    pub updated: Option<String>,
+    /// EPP-style statuses, e.g. "client transfer prohibited"
+    pub statuses: Vec<String>,
+    pub nameservers: Vec<String>,
+    /// Every entity, nested ones included, in document order
+    pub entities: Vec<RdapEntity>,
+    /// The registrar's RDAP URL for this domain, from the registry's `links`
+    pub referral: Option<String>,
+    /// Whether the delegation is DNSSEC-signed: RDAP `secureDNS`, WHOIS
+    /// `DNSSEC:`, or a DS lookup with `ProbeConfig::check_dnssec`
+    pub dnssec: Option<bool>,
+}
+
+/// EPP statuses (RFC 5731) that lock a domain against transfer, update or
+/// deletion, as `DomainRecord::locks` names them.
+pub const LOCK_STATUSES: &[&str] = &[
+    "clientTransferProhibited",
+    "serverTransferProhibited",
+    "clientUpdateProhibited",
+    "serverUpdateProhibited",
+    "clientDeleteProhibited",
+    "serverDeleteProhibited",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct RdapEntity {
+    pub handle: Option<String>,
+    pub roles: Vec<String>,
+    /// vCard `fn`, when the registry didn't redact it
+    pub name: Option<String>,
+}
+
+/// The string at `key` of a JSON object.
+fn text(value: &Value, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+/// The array at `key` of a JSON object; empty when absent.
+fn list<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value.get(key).and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+/// The strings in the array at `key` of a JSON object.
+fn texts(value: &Value, key: &str) -> Vec<String> {
+    list(value, key).iter().filter_map(|v| v.as_str().map(str::to_string)).collect()
+}
+
+/// Whether `secureDNS` says the delegation is signed. Some registries list
+/// the DS or key data without `delegationSigned`.
+fn signed(secure_dns: &Value) -> Option<bool> {
+    let listed = !list(secure_dns, "dsData").is_empty() || !list(secure_dns, "keyData").is_empty();
+    secure_dns.get("delegationSigned").and_then(Value::as_bool).or(listed.then_some(true))
+}
+
+/// The `fn` property of a jCard: `["vcard", [["fn", {}, "text", "Name"], ...]]`.
+fn vcard_name(vcard: &Value) -> Option<String> {
+    vcard
+        .get(1)?
+        .as_array()?
+        .iter()
+        .find(|prop| prop.get(0).and_then(|p| p.as_str()) == Some("fn"))?
+        .get(3)?
+        .as_str()
+        .map(str::to_string)
+        .filter(|name| !name.trim().is_empty())
+}
+
+fn flatten_entities(raw: &[Value], out: &mut Vec<RdapEntity>) {
+    for entity in raw {
+        out.push(RdapEntity {
+            handle: text(entity, "handle"),
+            roles: texts(entity, "roles"),
+            name: entity.get("vcardArray").and_then(vcard_name),
+        });
+        flatten_entities(list(entity, "entities"), out);
+    }
+}
+
+impl DomainRecord {
+    /// Parses an RDAP domain object; fields the server left out stay empty.
+    pub fn from_json(body: &[u8]) -> Result<Self, serde_json::Error> {
+        let raw = Value::Object(serde_json::from_slice::<Map<String, Value>>(body)?);
+
+        let event = |action: &str| {
+            list(&raw, "events")
+                .iter()
+                .find(|e| e.get("eventAction").and_then(Value::as_str).is_some_and(|a| a.eq_ignore_ascii_case(action)))
+                .and_then(|e| text(e, "eventDate"))
+        };
+        let created = event("registration");
+        let expires = event("expiration");
+        let updated = event("last changed");
+
+        let mut entities = Vec::new();
+        flatten_entities(list(&raw, "entities"), &mut entities);
+        let registrar = entities
+            .iter()
+            .find(|e| e.roles.iter().any(|r| r == "registrar"))
+            .and_then(|e| e.name.clone().or_else(|| e.handle.clone()));
+
+        // RFC 9083 referrals: rel "related" with an RDAP media type
+        let referral = list(&raw, "links")
+            .iter()
+            .find(|l| {
+                l.get("rel").and_then(Value::as_str) == Some("related")
+                    && l.get("type").and_then(Value::as_str).is_some_and(|t| t.contains("rdap+json"))
+            })
+            .and_then(|l| text(l, "href"));
+
+        Ok(DomainRecord {
+            ldh_name: text(&raw, "ldhName"),
+            registrar,
+            created,
+            expires,
+            updated,
+            statuses: texts(&raw, "status"),
+            nameservers: list(&raw, "nameservers")
+                .iter()
+                .filter_map(|ns| text(ns, "ldhName").map(|n| n.to_lowercase()))
+                .collect(),
+            entities,
+            referral,
+            dnssec: raw.get("secureDNS").and_then(signed),
+        })
+    }
+
+    /// The `LOCK_STATUSES` the domain has, whether the server wrote them
+    /// RDAP-style ("client transfer prohibited") or as EPP codes.
+    pub fn locks(&self) -> Vec<&'static str> {
+        let held: Vec<String> = self.statuses.iter().map(|s| s.replace([' ', '_'], "").to_lowercase()).collect();
+        LOCK_STATUSES
+            .iter()
+            .copied()
+            .filter(|lock| held.contains(&lock.to_lowercase()))
+            .collect()
+    }
+
+    /// Transfers to another registrar are blocked, by the registrar or the
+    /// registry.
+    pub fn transfer_locked(&self) -> bool {
+        self.locks().iter().any(|lock| lock.ends_with("TransferProhibited"))
+    }
+
+    /// Registry lock: the registry itself blocks transfer, update and
+    /// deletion, lifted only through its out-of-band procedure.
+    pub fn registry_locked(&self) -> bool {
+        let locks = self.locks();
+        ["serverTransferProhibited", "serverUpdateProhibited", "serverDeleteProhibited"]
+            .iter()
+            .all(|lock| locks.contains(lock))
+    }
+
+    /// Fills fields the registry left empty from the registrar's record.
+    /// Registry values win where both have one.
+    pub fn merge_referral(&mut self, registrar: DomainRecord) {
+        self.registrar = self.registrar.take().or(registrar.registrar);
+        self.created = self.created.take().or(registrar.created);
+        self.expires = self.expires.take().or(registrar.expires);
+        self.updated = self.updated.take().or(registrar.updated);
+        self.dnssec = self.dnssec.or(registrar.dnssec);
+        for status in registrar.statuses {
+            if !self.statuses.contains(&status) {
+                self.statuses.push(status);
+            }
+        }
+        if self.nameservers.is_empty() {
+            self.nameservers = registrar.nameservers;
+        }
+        for entity in registrar.entities {
+            if !self.entities.contains(&entity) {
+                self.entities.push(entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verisign's answer for google.com, notices and remarks cut
+    const VERISIGN: &str = r#"{
+        "objectClassName": "domain",
+        "handle": "2138514_DOMAIN_COM-VRSN",
+        "ldhName": "GOOGLE.COM",
+        "links": [
+            {"value": "https://rdap.verisign.com/com/v1/domain/GOOGLE.COM", "rel": "self", "href": "https://rdap.verisign.com/com/v1/domain/GOOGLE.COM", "type": "application/rdap+json"},
+            {"value": "https://rdap.markmonitor.com/rdap/domain/GOOGLE.COM", "rel": "related", "href": "https://rdap.markmonitor.com/rdap/domain/GOOGLE.COM", "type": "application/rdap+json"}
+        ],
+        "status": ["client delete prohibited", "client transfer prohibited", "client update prohibited", "server delete prohibited", "server transfer prohibited", "server update prohibited"],
+        "entities": [{
+            "objectClassName": "entity",
+            "handle": "292",
+            "roles": ["registrar"],
+            "publicIds": [{"type": "IANA Registrar ID", "identifier": "292"}],
+            "vcardArray": ["vcard", [["version", {}, "text", "4.0"], ["fn", {}, "text", "MarkMonitor Inc."]]],
+            "entities": [{
+                "objectClassName": "entity",
+                "roles": ["abuse"],
+                "vcardArray": ["vcard", [["version", {}, "text", "4.0"], ["fn", {}, "text", ""], ["tel", {"type": "voice"}, "uri", "tel:+1.2086851750"], ["email", {}, "text", "abusecomplaints@markmonitor.com"]]]
+            }]
+        }],
+        "events": [
+            {"eventAction": "registration", "eventDate": "1997-09-15T04:00:00Z"},
+            {"eventAction": "expiration", "eventDate": "2028-09-14T04:00:00Z"},
+            {"eventAction": "last changed", "eventDate": "2019-09-09T15:39:04Z"},
+            {"eventAction": "last update of RDAP database", "eventDate": "2024-05-01T12:00:00Z"}
+        ],
+        "secureDNS": {"delegationSigned": false},
+        "nameservers": [
+            {"objectClassName": "nameserver", "ldhName": "NS1.GOOGLE.COM"},
+            {"objectClassName": "nameserver", "ldhName": "NS2.GOOGLE.COM"}
+        ],
+        "rdapConformance": ["rdap_level_0", "icann_rdap_technical_implementation_guide_0", "icann_rdap_response_profile_0"]
+    }"#;
+
+    // A ccTLD registry that names the registrar by handle only and lists
+    // DS data without `delegationSigned`
+    const CCTLD: &str = r#"{
+        "objectClassName": "domain",
+        "ldhName": "example.se",
+        "status": ["active"],
+        "entities": [
+            {"objectClassName": "entity", "handle": "registrar-42", "roles": ["registrar"]},
+            {"objectClassName": "entity", "handle": "REDACTED", "roles": ["registrant"]}
+        ],
+        "events": [{"eventAction": "registration", "eventDate": "2003-02-11"}],
+        "secureDNS": {"dsData": [{"keyTag": 12345, "algorithm": 13, "digestType": 2, "digest": "ABCDEF"}]},
+        "nameservers": [{"ldhName": "ns.example.se"}]
+    }"#;
+
+    #[test]
+    fn parses_registry_answer() {
+        let record = DomainRecord::from_json(VERISIGN.as_bytes()).unwrap();
+        assert_eq!(record.ldh_name.as_deref(), Some("GOOGLE.COM"));
+        assert_eq!(record.registrar.as_deref(), Some("MarkMonitor Inc."));
+        assert_eq!(record.created.as_deref(), Some("1997-09-15T04:00:00Z"));
+        assert_eq!(record.expires.as_deref(), Some("2028-09-14T04:00:00Z"));
+        assert_eq!(record.updated.as_deref(), Some("2019-09-09T15:39:04Z"));
+        assert_eq!(record.nameservers, ["ns1.google.com", "ns2.google.com"]);
+        assert_eq!(record.referral.as_deref(), Some("https://rdap.markmonitor.com/rdap/domain/GOOGLE.COM"));
+        assert_eq!(record.dnssec, Some(false));
+        // The nested abuse contact is kept, its blank name dropped
+        assert_eq!(record.entities.len(), 2);
+        assert_eq!(record.entities[1].roles, ["abuse"]);
+        assert_eq!(record.entities[1].name, None);
+        assert_eq!(record.locks().len(), LOCK_STATUSES.len());
+        assert!(record.transfer_locked());
+        assert!(record.registry_locked());
+    }
+
+    #[test]
+    fn falls_back_to_handles_and_ds_data() {
+        let record = DomainRecord::from_json(CCTLD.as_bytes()).unwrap();
+        assert_eq!(record.registrar.as_deref(), Some("registrar-42"));
+        assert_eq!(record.created.as_deref(), Some("2003-02-11"));
+        assert_eq!(record.expires, None);
+        assert_eq!(record.referral, None);
+        assert_eq!(record.dnssec, Some(true));
+        assert!(record.locks().is_empty());
+        assert!(!record.transfer_locked());
+    }
+
+    #[test]
+    fn reads_epp_status_codes() {
+        let record = DomainRecord {
+            statuses: vec!["clientTransferProhibited".to_string(), "server_update_prohibited".to_string()],
+            ..DomainRecord::default()
+        };
+        assert_eq!(record.locks(), ["clientTransferProhibited", "serverUpdateProhibited"]);
+        assert!(record.transfer_locked());
+        assert!(!record.registry_locked());
+    }
+
+    #[test]
+    fn registry_values_win_over_referral() {
+        let mut record = DomainRecord::from_json(CCTLD.as_bytes()).unwrap();
+        let registrar = DomainRecord {
+            registrar: Some("Example Registrar AB".to_string()),
+            created: Some("2003-02-10".to_string()),
+            expires: Some("2030-02-11".to_string()),
+            statuses: vec!["active".to_string(), "clientTransferProhibited".to_string()],
+            nameservers: vec!["ns1.other.se".to_string()],
+            ..DomainRecord::default()
+        };
+        record.merge_referral(registrar);
+        assert_eq!(record.registrar.as_deref(), Some("registrar-42"));
+        assert_eq!(record.created.as_deref(), Some("2003-02-11"));
+        assert_eq!(record.expires.as_deref(), Some("2030-02-11"));
+        assert_eq!(record.statuses, ["active", "clientTransferProhibited"]);
+        assert_eq!(record.nameservers, ["ns.example.se"]);
+    }
+
+    #[test]
+    fn rejects_non_json() {
+        assert!(DomainRecord::from_json(b"<html>Login required</html>").is_err());
+    }
+}
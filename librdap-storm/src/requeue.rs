@@ -0,0 +1,81 @@
+use futures::{
+    future,
+    stream::{self, Stream, StreamExt},
+    Future,
+};
+use std::sync::{Arc, Mutex};
+
+/// A probe's outcome: its answer, or its domain sent to the back of the
+/// queue after a 429 (`ProbeConfig::rate_limit_requeues`).
+pub(crate) enum Queued<T> {
+    Done(T),
+    Requeued(String),
+}
+
+impl<T> Queued<T> {
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> Queued<U> {
+        match self {
+            Queued::Done(item) => Queued::Done(f(item)),
+            Queued::Requeued(domain) => Queued::Requeued(domain),
+        }
+    }
+}
+
+type Queue = Arc<Mutex<Vec<String>>>;
+
+/// Runs `probe` over `domains`, `concurrency` at a time. A requeued domain
+/// gives up its slot instead of waiting in it; once the rest have been
+/// probed the requeued ones go again, round after round until none are left.
+pub(crate) fn requeuing<'a, T, F, Fut>(
+    domains: impl Iterator<Item = String> + 'a,
+    probe: F,
+    concurrency: usize,
+) -> impl Stream<Item = T> + 'a
+where
+    T: 'a,
+    F: Fn(String) -> Fut + Clone + 'a,
+    Fut: Future<Output = Queued<T>> + 'a,
+{
+    let queue = Queue::default();
+    let first = round(domains, probe.clone(), concurrency, Arc::clone(&queue));
+    // Boxed so the stream stays `Unpin`, as `buffer_unordered` ones are
+    let later = stream::unfold(queue, move |queue| {
+        let probe = probe.clone();
+        Box::pin(async move {
+            let requeued = std::mem::take(&mut *queue.lock().unwrap());
+            if requeued.is_empty() {
+                return None;
+            }
+            let next = round(requeued.into_iter(), probe, concurrency, Arc::clone(&queue));
+            Some((next, queue))
+        })
+    })
+    .flatten();
+    first.chain(later)
+}
+
+fn round<'a, T, F, Fut>(
+    domains: impl Iterator<Item = String> + 'a,
+    probe: F,
+    concurrency: usize,
+    queue: Queue,
+) -> impl Stream<Item = T> + 'a
+where
+    T: 'a,
+    F: Fn(String) -> Fut + 'a,
+    Fut: Future<Output = Queued<T>> + 'a,
+{
+    stream::iter(domains)
+        .map(probe)
+        .buffer_unordered(concurrency)
+        .filter_map(move |probed| {
+            let done = match probed {
+                Queued::Done(item) => Some(item),
+                Queued::Requeued(domain) => {
+                    queue.lock().unwrap().push(domain);
+                    None
+                }
+            };
+            future::ready(done)
+        })
+}
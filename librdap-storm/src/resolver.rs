@@ -0,0 +1,181 @@
+use crate::dns::RCODE_NXDOMAIN;
+use hickory_resolver::{
+    config::{NameServerConfig, NameServerConfigGroup, ResolverConfig},
+    name_server::TokioConnectionProvider,
+    proto::{rr::RecordType, xfer::Protocol},
+    TokioResolver,
+};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    Url,
+};
+use std::{
+    fmt, io,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::net::TcpStream;
+
+const DNS_PORT: u16 = 53;
+
+#[derive(Debug, Error)]
+#[error("invalid resolver \"{0}\": {1}")]
+pub struct ResolverError(String, String);
+
+/// Where host names are looked up (`ProbeConfig::resolver`): `system`,
+/// plain DNS servers (`1.1.1.1, 9.9.9.9:53`), DNS-over-HTTPS at an address
+/// (`https://1.1.1.1/dns-query`), or the DoH service of `cloudflare`,
+/// `google` or `quad9`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DnsResolver {
+    #[default]
+    System,
+    /// Plain DNS, over UDP and TCP for long answers
+    Servers(Vec<SocketAddr>),
+    /// DNS-over-HTTPS; the server's certificate has to cover its address
+    Https { server: SocketAddr, path: String },
+    Cloudflare,
+    Google,
+    Quad9,
+}
+
+impl FromStr for DnsResolver {
+    type Err = ResolverError;
+
+    fn from_str(spec: &str) -> Result<Self, ResolverError> {
+        let invalid = |reason: &str| ResolverError(spec.to_string(), reason.to_string());
+        match spec.trim() {
+            "system" => return Ok(DnsResolver::System),
+            "cloudflare" => return Ok(DnsResolver::Cloudflare),
+            "google" => return Ok(DnsResolver::Google),
+            "quad9" => return Ok(DnsResolver::Quad9),
+            url if url.starts_with("https://") => {
+                let parsed = Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+                let host = parsed.host_str().unwrap_or_default();
+                let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() else {
+                    return Err(invalid("name the DoH server by IP address, or use cloudflare, google or quad9"));
+                };
+                let server = SocketAddr::new(ip, parsed.port().unwrap_or(443));
+                return Ok(DnsResolver::Https { server, path: parsed.path().to_string() });
+            }
+            _ => {}
+        }
+        let servers = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|server| !server.is_empty())
+            .map(|server| {
+                server
+                    .parse::<SocketAddr>()
+                    .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, DNS_PORT)))
+                    .map_err(|_| invalid("expected system, cloudflare, google, quad9, an https:// URL or IP addresses"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if servers.is_empty() {
+            return Err(invalid("no DNS server"));
+        }
+        Ok(DnsResolver::Servers(servers))
+    }
+}
+
+impl fmt::Display for DnsResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnsResolver::System => f.write_str("system"),
+            DnsResolver::Servers(servers) => {
+                let servers: Vec<String> = servers.iter().map(SocketAddr::to_string).collect();
+                f.write_str(&servers.join(", "))
+            }
+            DnsResolver::Https { server, path } => write!(f, "https://{}{}", server, path),
+            DnsResolver::Cloudflare => f.write_str("cloudflare"),
+            DnsResolver::Google => f.write_str("google"),
+            DnsResolver::Quad9 => f.write_str("quad9"),
+        }
+    }
+}
+
+/// Looks up host names and DNS records as `DnsResolver` says; `None` inside
+/// for the system's own lookups. Shared by the HTTP client, WHOIS and the
+/// DNS checks.
+#[derive(Clone, Default)]
+pub(crate) struct Resolver(Option<TokioResolver>);
+
+impl Resolver {
+    pub(crate) fn new(resolver: &DnsResolver) -> Self {
+        let config = match resolver {
+            DnsResolver::System => return Self(None),
+            DnsResolver::Servers(servers) => {
+                let mut group = NameServerConfigGroup::new();
+                for server in servers {
+                    group.push(NameServerConfig::new(*server, Protocol::Udp));
+                    group.push(NameServerConfig::new(*server, Protocol::Tcp));
+                }
+                ResolverConfig::from_parts(None, Vec::new(), group)
+            }
+            DnsResolver::Https { server, path } => {
+                let mut config = NameServerConfig::new(*server, Protocol::Https);
+                config.tls_dns_name = Some(server.ip().to_string());
+                config.http_endpoint = Some(path.clone());
+                ResolverConfig::from_parts(None, Vec::new(), vec![config])
+            }
+            DnsResolver::Cloudflare => ResolverConfig::cloudflare_https(),
+            DnsResolver::Google => ResolverConfig::google_https(),
+            DnsResolver::Quad9 => ResolverConfig::quad9_https(),
+        };
+        Self(Some(TokioResolver::builder_with_config(config, TokioConnectionProvider::default()).build()))
+    }
+
+    /// Whether lookups are left to the system.
+    pub(crate) fn is_system(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Addresses of `host`, which may be an IP address already.
+    pub(crate) async fn lookup(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let Some(resolver) = &self.0 else {
+            return Ok(tokio::net::lookup_host((host, port)).await?.collect());
+        };
+        if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            return Ok(vec![SocketAddr::new(ip, port)]);
+        }
+        let found = resolver.lookup_ip(host).await.map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+        Ok(found.iter().map(|ip| SocketAddr::new(ip, port)).collect())
+    }
+
+    /// A TCP connection to `host:port`, trying its addresses in turn.
+    pub(crate) async fn connect(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        if self.is_system() {
+            return TcpStream::connect((host, port)).await;
+        }
+        TcpStream::connect(&self.lookup(host, port).await?[..]).await
+    }
+
+    /// The response code and number of answers for `domain`'s records of
+    /// `record_type`, as `dns` reads them off the wire; `None` when it
+    /// couldn't tell, or the resolver is the system's.
+    pub(crate) async fn records(&self, domain: &str, record_type: u16, timeout: Duration) -> Option<(u8, u16)> {
+        let resolver = self.0.as_ref()?;
+        let record_type = RecordType::from(record_type);
+        match tokio::time::timeout(timeout, resolver.lookup(domain, record_type)).await.ok()? {
+            Ok(lookup) => {
+                let answers = lookup.record_iter().filter(|r| r.record_type() == record_type).count();
+                Some((0, answers as u16))
+            }
+            Err(e) if e.is_nx_domain() => Some((RCODE_NXDOMAIN, 0)),
+            Err(e) if e.is_no_records_found() => Some((0, 0)),
+            Err(_) => None,
+        }
+    }
+}
+
+impl Resolve for Resolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
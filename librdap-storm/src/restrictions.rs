@@ -0,0 +1,161 @@
+use std::fmt;
+
+/// Kind of organization some sponsored/regulated TLDs are limited to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityType {
+    Bank,
+    Insurance,
+    Pharmacy,
+    Government,
+    Military,
+    Education,
+    Intergovernmental,
+    Aviation,
+    Cooperative,
+    Museum,
+    Postal,
+    Realtor,
+    Accountant,
+}
+
+impl EntityType {
+    pub const ALL: &'static [EntityType] = &[
+        EntityType::Bank,
+        EntityType::Insurance,
+        EntityType::Pharmacy,
+        EntityType::Government,
+        EntityType::Military,
+        EntityType::Education,
+        EntityType::Intergovernmental,
+        EntityType::Aviation,
+        EntityType::Cooperative,
+        EntityType::Museum,
+        EntityType::Postal,
+        EntityType::Realtor,
+        EntityType::Accountant,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EntityType::Bank => "bank",
+            EntityType::Insurance => "insurance",
+            EntityType::Pharmacy => "pharmacy",
+            EntityType::Government => "government",
+            EntityType::Military => "military",
+            EntityType::Education => "education",
+            EntityType::Intergovernmental => "intergovernmental",
+            EntityType::Aviation => "aviation",
+            EntityType::Cooperative => "cooperative",
+            EntityType::Museum => "museum",
+            EntityType::Postal => "postal",
+            EntityType::Realtor => "realtor",
+            EntityType::Accountant => "accountant",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|e| e.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// What a registrant must satisfy to register under a TLD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    /// Residence or registered presence in a country (ISO 3166 alpha-2,
+    /// lowercase) or `"eu"` for any EU/EEA member state.
+    LocalPresence(&'static str),
+    /// Verified membership of a regulated sector.
+    Entity(EntityType),
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Requirement::LocalPresence(country) => {
+                write!(f, "local presence: {}", country.to_uppercase())
+            }
+            Requirement::Entity(entity) => write!(f, "{} only", entity.name()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TldRestriction {
+    pub tld: &'static str,
+    pub requirement: Requirement,
+    pub note: &'static str,
+}
+
+use EntityType::*;
+use Requirement::{Entity, LocalPresence};
+
+/// Bundled eligibility rules for TLDs that are not open to everyone. Not
+/// exhaustive; registries change policies, so treat this as a warning aid.
+const RESTRICTIONS: &[TldRestriction] = &[
+    TldRestriction { tld: "gov", requirement: Entity(Government), note: "US government entities" },
+    TldRestriction { tld: "mil", requirement: Entity(Military), note: "US military" },
+    TldRestriction { tld: "edu", requirement: Entity(Education), note: "Accredited US post-secondary institutions" },
+    TldRestriction { tld: "int", requirement: Entity(Intergovernmental), note: "Treaty-based international organizations" },
+    TldRestriction { tld: "bank", requirement: Entity(Bank), note: "Verified banks and banking associations" },
+    TldRestriction { tld: "insurance", requirement: Entity(Insurance), note: "Licensed insurers" },
+    TldRestriction { tld: "pharmacy", requirement: Entity(Pharmacy), note: "Accredited pharmacies" },
+    TldRestriction { tld: "aero", requirement: Entity(Aviation), note: "Aviation community" },
+    TldRestriction { tld: "coop", requirement: Entity(Cooperative), note: "Cooperatives" },
+    TldRestriction { tld: "museum", requirement: Entity(Museum), note: "Museums" },
+    TldRestriction { tld: "post", requirement: Entity(Postal), note: "Postal operators" },
+    TldRestriction { tld: "realtor", requirement: Entity(Realtor), note: "Members of the National Association of Realtors" },
+    TldRestriction { tld: "cpa", requirement: Entity(Accountant), note: "Licensed CPAs" },
+    TldRestriction { tld: "us", requirement: LocalPresence("us"), note: "US nexus required" },
+    TldRestriction { tld: "nyc", requirement: LocalPresence("us"), note: "New York City address required" },
+    TldRestriction { tld: "ca", requirement: LocalPresence("ca"), note: "Canadian Presence Requirements" },
+    TldRestriction { tld: "quebec", requirement: LocalPresence("ca"), note: "Connection to Québec required" },
+    TldRestriction { tld: "au", requirement: LocalPresence("au"), note: "Australian presence required" },
+    TldRestriction { tld: "eu", requirement: LocalPresence("eu"), note: "EU/EEA residence or establishment" },
+    TldRestriction { tld: "fr", requirement: LocalPresence("eu"), note: "EU/EEA residence or establishment" },
+    TldRestriction { tld: "it", requirement: LocalPresence("eu"), note: "EU/EEA residence or establishment" },
+    TldRestriction { tld: "no", requirement: LocalPresence("no"), note: "Norwegian organization number or residence" },
+    TldRestriction { tld: "jp", requirement: LocalPresence("jp"), note: "Japanese address required" },
+    TldRestriction { tld: "kr", requirement: LocalPresence("kr"), note: "Korean presence required" },
+    TldRestriction { tld: "sg", requirement: LocalPresence("sg"), note: "Singapore address for admin contact" },
+    TldRestriction { tld: "my", requirement: LocalPresence("my"), note: "Malaysian presence required" },
+];
+
+/// Returns the bundled restriction for a TLD, if it has one.
+pub fn restriction_for(tld: &str) -> Option<&'static TldRestriction> {
+    let tld = tld.trim_start_matches('.');
+    RESTRICTIONS.iter().find(|r| r.tld.eq_ignore_ascii_case(tld))
+}
+
+/// EU and EEA member states, which satisfy `LocalPresence("eu")`.
+const EU_EEA: &[&str] = &[
+    "at", "be", "bg", "hr", "cy", "cz", "dk", "ee", "fi", "fr", "de", "gr", "hu", "ie", "it",
+    "lv", "lt", "lu", "mt", "nl", "pl", "pt", "ro", "sk", "si", "es", "se", "is", "li", "no",
+];
+
+/// Who is registering, used to decide which restricted TLDs are reachable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registrant {
+    /// ISO 3166 alpha-2 country of residence or establishment.
+    pub country: Option<String>,
+    pub entity_types: Vec<EntityType>,
+}
+
+impl TldRestriction {
+    pub fn is_met_by(&self, registrant: &Registrant) -> bool {
+        match self.requirement {
+            Requirement::LocalPresence(required) => match registrant.country.as_deref() {
+                Some(country) if required == "eu" => {
+                    EU_EEA.iter().any(|c| c.eq_ignore_ascii_case(country))
+                }
+                Some(country) => required.eq_ignore_ascii_case(country),
+                None => false,
+            },
+            Requirement::Entity(entity) => registrant.entity_types.contains(&entity),
+        }
+    }
+}
+
+/// True when the TLD is unrestricted or its restriction is met by `registrant`.
+pub fn is_registrable(tld: &str, registrant: &Registrant) -> bool {
+    restriction_for(tld).is_none_or(|r| r.is_met_by(registrant))
+}
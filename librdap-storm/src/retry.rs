@@ -0,0 +1,35 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// A transient failure that `ProbeConfig::retry_on` can select for
+/// another attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "kebab-case"))]
+pub enum RetryOn {
+    /// No answer within the probe timeout
+    Timeout,
+    /// RDAP 5xx
+    ServerError,
+    /// Connection refused, reset or dropped
+    Connection,
+    /// RDAP 429
+    RateLimited,
+}
+
+impl RetryOn {
+    pub const ALL: [RetryOn; 4] = [RetryOn::Timeout, RetryOn::ServerError, RetryOn::Connection, RetryOn::RateLimited];
+}
+
+/// Wait before retry number `attempt` (0-based): `base` doubled per
+/// attempt, capped at 32x, with the upper half jittered so probes that
+/// failed together don't retry in lockstep.
+pub(crate) fn backoff(base: Duration, attempt: u32) -> Duration {
+    let delay = base * 2u32.pow(attempt.min(5));
+    let random = RandomState::new().build_hasher().finish();
+    delay / 2 + delay.mul_f64((random % 1000) as f64 / 2000.0)
+}
@@ -0,0 +1,86 @@
+use crate::psl::public_suffix;
+
+/// How `ProbeError::InvalidName`, for names the TLD's registry won't take,
+/// displays. Those are reported without querying it.
+pub const INVALID_NAME_REASON: &str = "Invalid name for this TLD";
+
+/// Limits a registry puts on the label directly under its TLD, on top of
+/// DNS's own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameRule {
+    pub tld: &'static str,
+    pub min_len: usize,
+    pub max_len: usize,
+    /// All-digit names are refused
+    pub needs_letter: bool,
+}
+
+const fn min(tld: &'static str, min_len: usize) -> NameRule {
+    NameRule { tld, min_len, max_len: 63, needs_letter: false }
+}
+
+/// Bundled rules for registries stricter than DNS. Not exhaustive;
+/// registries change policies, and unlisted TLDs get the DNS checks only.
+const NAME_RULES: &[NameRule] = &[
+    // Not .com or .net: x.com, q.com and z.com are registered, legacy
+    // single letters Verisign no longer hands out
+    min("us", 2),
+    min("au", 2),
+    min("be", 2),
+    min("ca", 2),
+    min("eu", 2),
+    min("nl", 2),
+    min("no", 2),
+    min("es", 3),
+    NameRule { tld: "br", min_len: 2, max_len: 26, needs_letter: true },
+];
+
+/// The bundled rule for a TLD, if it has one.
+pub fn name_rule_for(tld: &str) -> Option<&'static NameRule> {
+    let tld = tld.trim_start_matches('.');
+    NAME_RULES.iter().find(|r| r.tld.eq_ignore_ascii_case(tld))
+}
+
+/// Why `domain` (in ASCII form) can't be registered, if it can't: DNS
+/// label syntax first, then the rule of its TLD.
+pub fn check_name(domain: &str) -> Result<(), String> {
+    if domain.len() > 253 {
+        return Err("longer than 253 characters".to_string());
+    }
+    for label in domain.split('.') {
+        if label.is_empty() {
+            return Err("empty label".to_string());
+        }
+        if label.len() > 63 {
+            return Err(format!("'{}' is longer than 63 characters", label));
+        }
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("'{}' has characters other than letters, digits and hyphens", label));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(format!("'{}' starts or ends with a hyphen", label));
+        }
+        if label.get(2..4) == Some("--") && !label.starts_with("xn--") {
+            return Err(format!("'{}' has hyphens in the third and fourth positions", label));
+        }
+    }
+
+    let Some(suffix) = public_suffix(domain) else {
+        return Ok(());
+    };
+    let tld = suffix.rsplit('.').next().unwrap_or(suffix);
+    let (Some(rule), Some(rest)) = (name_rule_for(tld), domain.strip_suffix(suffix)) else {
+        return Ok(());
+    };
+    let name = rest.trim_end_matches('.').rsplit('.').next().unwrap_or_default();
+    if name.len() < rule.min_len {
+        return Err(format!(".{} names need at least {} characters", tld, rule.min_len));
+    }
+    if name.len() > rule.max_len {
+        return Err(format!(".{} names are at most {} characters", tld, rule.max_len));
+    }
+    if rule.needs_letter && name.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!(".{} names need at least one letter", tld));
+    }
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use reqwest::Client;
+use std::{cmp::Ordering, collections::HashMap};
 use thiserror::Error;
 
 const IANA_TLD_LIST_URL: &str = "https://data.iana.org/TLD/tlds-alpha-by-domain.txt";
@@ -25,3 +26,75 @@ pub async fn fetch_iana_tlds(client: &Client) -> Result<Vec<String>, TldError> {
 pub fn expand_tlds<'a>(name: &'a str, tlds: &'a [String]) -> impl Iterator<Item = String> + 'a {
     tlds.iter().map(move |tld| format!("{}.{}", name, tld))
 }
+
+/// TLDs most people care about, in descending order of interest.
+pub const DEFAULT_PRIORITY_TLDS: &[&str] = &[
+    "com", "net", "org", "io", "ai", "dev", "app", "co", "me", "tech",
+    "xyz", "online", "site", "store", "shop", "blog", "cloud", "digital",
+    "eu", "us", "info", "email", "pro", "live", "zone", "team", "solutions"
+];
+
+/// Orders TLDs by configurable weights. Weighted TLDs come first (highest
+/// weight first); unweighted TLDs compare as equal so callers can apply
+/// their own tie-breaker.
+#[derive(Debug, Clone)]
+pub struct TldRanker {
+    weights: HashMap<String, i32>,
+}
+
+impl TldRanker {
+    /// A ranker with no weights; every TLD compares as equal.
+    pub fn empty() -> Self {
+        Self { weights: HashMap::new() }
+    }
+
+    /// Builds a ranker from an ordered list, first entry weighted highest.
+    pub fn from_ordered<S: AsRef<str>>(tlds: &[S]) -> Self {
+        let mut ranker = Self::empty();
+        let n = tlds.len() as i32;
+        for (i, tld) in tlds.iter().enumerate() {
+            ranker.set_weight(tld.as_ref(), n - i as i32);
+        }
+        ranker
+    }
+
+    pub fn with_weight(mut self, tld: &str, weight: i32) -> Self {
+        self.set_weight(tld, weight);
+        self
+    }
+
+    pub fn set_weight(&mut self, tld: &str, weight: i32) {
+        self.weights.insert(tld.to_lowercase(), weight);
+    }
+
+    pub fn weight(&self, tld: &str) -> Option<i32> {
+        self.weights.get(&tld.to_lowercase()).copied()
+    }
+
+    /// Compares two TLDs by weight. Returns `Equal` when neither is weighted.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match (self.weight(a), self.weight(b)) {
+            (Some(wa), Some(wb)) => wb.cmp(&wa).then_with(|| a.cmp(b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// Sorts TLDs with weighted ones first, then the rest alphabetically.
+    pub fn sort(&self, mut tlds: Vec<String>) -> Vec<String> {
+        tlds.sort_by(|a, b| self.compare(a, b).then_with(|| a.cmp(b)));
+        tlds
+    }
+
+    /// All weighted TLDs, highest weight first.
+    pub fn ranked_tlds(&self) -> Vec<String> {
+        self.sort(self.weights.keys().cloned().collect())
+    }
+}
+
+impl Default for TldRanker {
+    fn default() -> Self {
+        Self::from_ordered(DEFAULT_PRIORITY_TLDS)
+    }
+}
@@ -1,10 +1,85 @@
-use std::time::Duration;
+use crate::{
+    checker::CheckStep,
+    http::DEFAULT_USER_AGENT,
+    launch::LaunchSchedule,
+    maintenance::{builtin_windows, MaintenanceWindow, MAINTENANCE_REASON},
+    proxy::Proxy,
+    resolver::DnsResolver,
+    syntax::INVALID_NAME_REASON,
+    retry::RetryOn,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+use thiserror::Error;
 
+/// Response headers worth keeping when reporting endpoint misbehavior.
+pub const DIAGNOSTIC_HEADERS: &[&str] = &["server", "retry-after", "ratelimit-*", "x-ratelimit-*"];
+
+/// With the `serde` feature, tagged by `status`: `{"status": "unknown",
+/// "reason": {"kind": "timeout"}}`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(tag = "status", rename_all = "snake_case"))]
 pub enum Availability {
     Available,
     Taken,
-    Unknown { reason: String },
+    /// Unregistered, but held back by the registry and not normally for sale
+    Reserved,
+    /// Unregistered, but sold at a registry premium price
+    Premium,
+    Unknown { reason: ProbeError },
+}
+
+/// Why a probe ended without a verdict. Displays as the short reason shown
+/// to users; the result's explanation has the details.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(tag = "kind", content = "detail", rename_all = "snake_case")
+)]
+pub enum ProbeError {
+    /// No answer within `ProbeConfig::timeout`
+    #[error("Timeout")]
+    Timeout,
+    /// The server answered 429
+    #[error("Rate limited")]
+    RateLimited,
+    /// Nowhere to ask: no RDAP endpoint or WHOIS server for the TLD, or
+    /// the ones there are being skipped
+    #[error("{0}")]
+    NoEndpoint(String),
+    /// The request failed: connection, TLS, or an unexpected HTTP status
+    #[error("{0}")]
+    TransportError(String),
+    /// An answer that decides nothing: a non-RDAP page, an unmatched WHOIS
+    /// reply, an endpoint that calls its canary available
+    #[error("{0}")]
+    AmbiguousResponse(String),
+    /// The IANA RDAP bootstrap couldn't be loaded
+    #[error("{0}")]
+    Bootstrap(String),
+    /// The registry was in a maintenance window
+    #[error("{}", MAINTENANCE_REASON)]
+    Maintenance,
+    /// Not a name the TLD's registry takes, or not a domain at all
+    #[error("{}", INVALID_NAME_REASON)]
+    InvalidName,
+}
+
+impl ProbeError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProbeError::Timeout => "timeout",
+            ProbeError::RateLimited => "rate_limited",
+            ProbeError::NoEndpoint(_) => "no_endpoint",
+            ProbeError::TransportError(_) => "transport_error",
+            ProbeError::AmbiguousResponse(_) => "ambiguous_response",
+            ProbeError::Bootstrap(_) => "bootstrap",
+            ProbeError::Maintenance => "maintenance",
+            ProbeError::InvalidName => "invalid_name",
+        }
+    }
 }
 
 impl Availability {
@@ -16,24 +91,279 @@ impl Availability {
         matches!(self, Availability::Taken)
     }
 
+    pub fn is_reserved(&self) -> bool {
+        matches!(self, Availability::Reserved)
+    }
+
+    pub fn is_premium(&self) -> bool {
+        matches!(self, Availability::Premium)
+    }
+
     pub fn is_unknown(&self) -> bool {
         matches!(self, Availability::Unknown { .. })
     }
+
+    /// Unknown because the registry was in a maintenance window.
+    pub fn is_maintenance(&self) -> bool {
+        matches!(self, Availability::Unknown { reason: ProbeError::Maintenance })
+    }
+
+    /// Unknown because the TLD's registry doesn't take names like it.
+    pub fn is_invalid_name(&self) -> bool {
+        matches!(self, Availability::Unknown { reason: ProbeError::InvalidName })
+    }
+}
+
+/// What decided a result's availability. Unknowns carry the last source
+/// asked, RDAP when none was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum ProbeSource {
+    Rdap,
+    Whois,
+    Dns,
+    /// Replayed from an earlier probe
+    Cache,
+    /// A `Checker` of the probe chain
+    Custom,
 }
 
+impl ProbeSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProbeSource::Rdap => "rdap",
+            ProbeSource::Whois => "whois",
+            ProbeSource::Dns => "dns",
+            ProbeSource::Cache => "cache",
+            ProbeSource::Custom => "custom",
+        }
+    }
+
+    /// How far a verdict from this source goes: the registry's RDAP answer
+    /// or an NS delegation is solid, a WHOIS text match is a heuristic.
+    pub(crate) fn confidence(self, availability: &Availability) -> Confidence {
+        match (self, availability) {
+            (_, Availability::Unknown { .. }) => Confidence::Low,
+            (ProbeSource::Rdap | ProbeSource::Dns, _) => Confidence::High,
+            (ProbeSource::Whois | ProbeSource::Cache | ProbeSource::Custom, _) => Confidence::Medium,
+        }
+    }
+}
+
+/// How far a result can be trusted; ordered, so `>= Confidence::High` works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn name(self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(Confidence::Low),
+            "medium" => Some(Confidence::Medium),
+            "high" => Some(Confidence::High),
+            _ => None,
+        }
+    }
+}
+
+/// Progress of the IANA RDAP bootstrap that maps TLDs to endpoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum BootstrapState {
+    NotStarted,
+    InProgress,
+    Ready(usize),
+    /// IANA was unreachable; using the snapshot compiled into the library
+    Bundled(usize),
+    Failed(String),
+}
+
+impl BootstrapState {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, BootstrapState::Ready(_) | BootstrapState::Bundled(_))
+    }
+}
+
+impl std::fmt::Display for BootstrapState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BootstrapState::NotStarted => write!(f, "not started"),
+            BootstrapState::InProgress => write!(f, "bootstrapping"),
+            BootstrapState::Ready(n) => write!(f, "{} endpoints", n),
+            BootstrapState::Bundled(n) => write!(f, "{} bundled endpoints (offline)", n),
+            BootstrapState::Failed(reason) => write!(f, "bootstrap failed: {}", reason),
+        }
+    }
+}
+
+/// The domain as registries expect it: Unicode labels converted to
+/// punycode (`bücher.de` → `xn--bcher-kva.de`), ASCII ones lowercased.
+pub fn to_ascii_domain(domain: &str) -> Result<String, String> {
+    let domain = domain.trim_end_matches('.');
+    if domain.is_ascii() {
+        return Ok(domain.to_ascii_lowercase());
+    }
+    idna::domain_to_ascii(domain).map_err(|_| format!("Invalid internationalized domain: {}", domain))
+}
+
+/// With the `serde` feature, `availability` is flattened into the result
+/// and `duration` is `duration_ms`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProbeResult {
+    /// As given to the prober, Unicode form included
     pub domain: String,
+    #[cfg_attr(feature = "serde", serde(flatten))]
     pub availability: Availability,
+    #[cfg_attr(feature = "serde", serde(rename = "duration_ms", with = "duration_ms"))]
     pub duration: Duration,
+    /// RDAP response headers selected by `ProbeConfig::capture_headers`.
+    pub headers: Vec<(String, String)>,
+    /// RDAP base URL that was queried, if any.
+    pub endpoint: Option<String>,
+    /// How the verdict was reached, e.g. "RDAP 404 from rdap.verisign.com".
+    pub explanation: String,
+    pub source: ProbeSource,
+    pub confidence: Confidence,
+    /// The answer that decided the verdict, RDAP JSON or WHOIS text, with
+    /// `ProbeConfig::capture_raw`.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub raw: Option<String>,
 }
 
+#[cfg(feature = "serde")]
+mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+
 #[derive(Debug, Clone)]
 pub struct ProbeConfig {
     pub timeout: Duration,
     pub whois_fallback: bool,
     pub max_rate_per_endpoint: u32,
+    /// RDAP requests in flight to one base URL at a time. Streams run ten
+    /// times this many probes at once across all endpoints.
     pub max_concurrent_per_endpoint: u32,
+    /// WHOIS queries per second allowed against a single WHOIS server.
+    pub whois_rate_per_server: u32,
+    /// Simultaneous TCP connections allowed to a single WHOIS server.
+    pub whois_max_connections_per_server: u32,
+    /// WHOIS server by TLD or public suffix (`"no"`, `"co.uk"`), ahead of
+    /// the built-in table and IANA's referral.
+    pub whois_servers: HashMap<String, String>,
+    /// RDAP response headers to keep on each result (case-insensitive, a
+    /// trailing `*` matches by prefix). Empty disables capture.
+    pub capture_headers: Vec<String>,
+    /// Keep the registry's answer on each result as `ProbeResult::raw`.
+    pub capture_raw: bool,
+    /// `User-Agent` of RDAP and bootstrap requests; some registries turn
+    /// away generic ones.
+    pub user_agent: String,
+    /// Headers added to every RDAP and bootstrap request, e.g. an API key
+    /// a registry issued.
+    pub extra_headers: Vec<(String, String)>,
+    /// HTTP or SOCKS5 proxy that RDAP, bootstrap and WHOIS connections go
+    /// through. DNS lookups (`dns_first`, `check_dnssec`, confirmation)
+    /// still go to `resolver` directly.
+    pub proxy: Option<Proxy>,
+    /// Looks up RDAP and WHOIS server names, and answers the DNS checks.
+    /// The system's by default; DNS-over-HTTPS where that is filtered.
+    pub resolver: DnsResolver,
+    /// Fetch each endpoint's RDAP `/help` before first use, keep its notices
+    /// and slow down to any query limit it documents.
+    pub discover_limits: bool,
+    /// Before trusting an endpoint's first "available" answer, look up a
+    /// registered canary (`nic.<tld>`) there; if that also comes back
+    /// available, the endpoint's available answers become unknown.
+    pub canary_check: bool,
+    /// Re-verify every "available" verdict via WHOIS or DNS before
+    /// reporting it.
+    pub confirm_available: bool,
+    /// Look the domain up in DNS (NS records) before RDAP; a delegated
+    /// domain is reported taken without querying the registry.
+    pub dns_first: bool,
+    /// Steps each probe goes through, in order, until one decides. Empty
+    /// means `CheckStep::default_chain(dns_first)`: DNS with `dns_first`,
+    /// RDAP, then WHOIS.
+    pub chain: Vec<CheckStep>,
+    /// Extra RDAP and WHOIS attempts after a failure listed in `retry_on`.
+    pub retries: u32,
+    /// Wait before the first retry; doubles with each further attempt, jittered.
+    pub backoff_base: Duration,
+    /// Failures that are retried; anything else is reported unknown at once.
+    pub retry_on: Vec<RetryOn>,
+    /// Times a domain answered with 429 goes to the back of the queue,
+    /// asked again once the endpoint's `Retry-After` (or the backoff) has
+    /// passed. Not counted against `retries`.
+    pub rate_limit_requeues: u32,
+    /// Longest `Retry-After` waited out; a longer one leaves the domain
+    /// unknown.
+    pub max_retry_after: Duration,
+    /// Timeouts, connection errors or 5xx in a row after which an RDAP
+    /// endpoint is skipped (straight to WHOIS) for `breaker_cooldown`.
+    /// 0 never skips.
+    pub breaker_threshold: u32,
+    /// How long a failing endpoint is skipped before one trial request.
+    pub breaker_cooldown: Duration,
+    /// In detailed probes, follow the registry's link to the registrar's
+    /// RDAP server and fill in what the registry left out.
+    pub follow_referrals: bool,
+    /// In detailed probes of taken domains, look up DS records when the
+    /// registry's answer doesn't say whether the domain is DNSSEC-signed.
+    pub check_dnssec: bool,
+    /// File the IANA bootstrap is kept in between runs. A fresh copy is
+    /// used as is; a stale one is used while a new copy downloads in the
+    /// background. `None` fetches it once per `Prober`.
+    pub bootstrap_cache: Option<PathBuf>,
+    /// Age at which `bootstrap_cache` is refreshed.
+    pub bootstrap_ttl: Duration,
+    /// Registry maintenance calendar; inconclusive answers from a TLD in
+    /// one of its windows are reported as `ProbeError::Maintenance`.
+    pub maintenance: Vec<MaintenanceWindow>,
+    /// Don't query registries during their maintenance windows at all;
+    /// such domains come back unknown for a later pass to check.
+    pub defer_during_maintenance: bool,
+    /// When every probe of a suffix in a stream fails the same way, try
+    /// them once more via the suffix's other RDAP base URLs, then WHOIS.
+    /// Their failures are held back until the stream ends to tell.
+    pub group_retry: bool,
+    /// Launch dates of new TLDs, none by default as they go stale within
+    /// months; available verdicts on a TLD not yet in general availability
+    /// say so in their explanation.
+    pub launches: Vec<LaunchSchedule>,
+    /// Reuse a domain's available/taken verdict for this long instead of
+    /// probing it again. `None` always probes.
+    pub cache_ttl: Option<Duration>,
+    /// SQLite file every result is recorded in, across runs. Available and
+    /// taken verdicts found there are replayed instead of probing.
+    #[cfg(feature = "sqlite-cache")]
+    pub result_db: Option<PathBuf>,
+    /// Age past which `result_db` verdicts are probed again, and rows are
+    /// dropped when the file is opened; `None` replays them however old.
+    #[cfg(feature = "sqlite-cache")]
+    pub result_db_max_age: Option<Duration>,
 }
 
 impl Default for ProbeConfig {
@@ -43,6 +373,40 @@ impl Default for ProbeConfig {
             whois_fallback: true,
             max_rate_per_endpoint: 20,
             max_concurrent_per_endpoint: 10,
+            whois_rate_per_server: 2,
+            whois_max_connections_per_server: 2,
+            whois_servers: HashMap::new(),
+            capture_headers: Vec::new(),
+            capture_raw: false,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            extra_headers: Vec::new(),
+            proxy: None,
+            resolver: DnsResolver::System,
+            discover_limits: false,
+            canary_check: true,
+            confirm_available: false,
+            dns_first: false,
+            chain: Vec::new(),
+            retries: 0,
+            backoff_base: Duration::from_millis(250),
+            retry_on: RetryOn::ALL.to_vec(),
+            rate_limit_requeues: 5,
+            max_retry_after: Duration::from_secs(120),
+            breaker_threshold: 5,
+            breaker_cooldown: Duration::from_secs(30),
+            follow_referrals: false,
+            check_dnssec: false,
+            bootstrap_cache: None,
+            bootstrap_ttl: Duration::from_secs(24 * 3600),
+            maintenance: builtin_windows(),
+            defer_during_maintenance: false,
+            group_retry: true,
+            launches: Vec::new(),
+            cache_ttl: None,
+            #[cfg(feature = "sqlite-cache")]
+            result_db: None,
+            #[cfg(feature = "sqlite-cache")]
+            result_db_max_age: None,
         }
     }
 }
@@ -1,53 +1,804 @@
-use crate::types::Availability;
-use std::time::Duration;
+use crate::{
+    proxy::Proxy,
+    psl::public_suffix,
+    ratelimit::EndpointRateLimiters,
+    record::{DomainRecord, RdapEntity},
+    resolver::Resolver,
+    retry::RetryOn,
+    types::{Availability, ProbeError},
+};
+use dashmap::DashMap;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
+use tokio::sync::{OnceCell, Semaphore};
 
 const WHOIS_PORT: u16 = 43;
+/// Longest reply read from a WHOIS server; real ones are a few kilobytes.
+const MAX_RESPONSE: u64 = 256 * 1024;
+/// Answers TLD queries with a `whois:` line naming the registry's server.
+const IANA_WHOIS: &str = "whois.iana.org";
+/// `tld server` lines for hundreds of TLDs, so most lookups skip the IANA
+/// referral. Regenerated from IANA now and then; see the file's header.
+const BUNDLED_SERVERS: &str = include_str!("whois_servers.txt");
 
-pub async fn check_whois(domain: &str, timeout: Duration) -> Availability {
-    let tld = match domain.rsplit('.').next() {
-        Some(t) => t.to_lowercase(),
-        None => return Availability::Unknown { reason: "Invalid domain".to_string() },
+/// A WHOIS verdict with how it was reached.
+pub struct WhoisAnswer {
+    pub availability: Availability,
+    pub explanation: String,
+    /// Registration details, only from `check_detailed` on a taken domain
+    pub record: Option<DomainRecord>,
+    /// What went wrong, when it may go right on another attempt
+    pub failure: Option<RetryOn>,
+    /// The registry's response text, when one came
+    pub raw: Option<String>,
+}
+
+/// Why a WHOIS query got no answer.
+struct QueryError {
+    message: String,
+    reason: ProbeError,
+    failure: Option<RetryOn>,
+}
+
+impl QueryError {
+    fn io(whois_server: &str, e: &std::io::Error) -> Self {
+        let message = format!("WHOIS error at {}: {}", whois_server, e);
+        Self { reason: ProbeError::TransportError(message.clone()), message, failure: io_failure(e) }
+    }
+
+    fn timeout(whois_server: &str) -> Self {
+        let message = format!("WHOIS timeout at {}", whois_server);
+        Self { message, reason: ProbeError::Timeout, failure: Some(RetryOn::Timeout) }
+    }
+}
+
+impl From<ProbeError> for QueryError {
+    fn from(reason: ProbeError) -> Self {
+        Self { message: reason.to_string(), reason, failure: None }
+    }
+}
+
+/// Resets and drops mid-answer are worth another try; other I/O errors
+/// (an unresolvable server name, say) are not.
+fn io_failure(e: &std::io::Error) -> Option<RetryOn> {
+    use std::io::ErrorKind::*;
+    match e.kind() {
+        ConnectionRefused | ConnectionReset | ConnectionAborted | BrokenPipe | UnexpectedEof => {
+            Some(RetryOn::Connection)
+        }
+        TimedOut => Some(RetryOn::Timeout),
+        _ => None,
+    }
+}
+
+/// Consecutive connect failures, with no successful connection to it ever
+/// made, after which a server is skipped for the rest of the run.
+const SERVER_UNREACHABLE_THRESHOLD: u32 = 3;
+/// Servers that have to be unreachable, with no connection to any server
+/// ever made, before outbound port 43 is assumed to be blocked.
+const PORT_BLOCKED_SERVERS: usize = 2;
+
+/// Connection outcomes of one WHOIS server.
+#[derive(Default)]
+struct ServerHealth {
+    /// Connect failures in a row
+    failures: u32,
+    connected: bool,
+}
+
+impl ServerHealth {
+    fn unreachable(&self) -> bool {
+        !self.connected && self.failures >= SERVER_UNREACHABLE_THRESHOLD
+    }
+}
+
+/// Throttled WHOIS client. WHOIS servers close the connection after each
+/// answer, so instead of pooling sockets we cap concurrent connections and
+/// the query rate per server to stay under registries' abuse thresholds.
+pub struct WhoisClient {
+    rate_limiters: EndpointRateLimiters,
+    connections: DashMap<String, Arc<Semaphore>>,
+    max_connections_per_server: usize,
+    /// Public suffix to WHOIS server, set by the user
+    overrides: HashMap<String, String>,
+    /// TLD to WHOIS server as discovered from IANA; `None` when IANA lists none
+    servers: DashMap<String, Arc<OnceCell<Option<String>>>>,
+    health: DashMap<String, ServerHealth>,
+    connected_once: AtomicBool,
+    disabled: AtomicBool,
+    /// `ProbeConfig::proxy`, which connections go through
+    proxy: Option<Proxy>,
+    resolver: Resolver,
+}
+
+impl WhoisClient {
+    pub fn new(
+        rate_per_server: u32,
+        max_connections_per_server: u32,
+        overrides: &HashMap<String, String>,
+        proxy: Option<Proxy>,
+        resolver: Resolver,
+    ) -> Self {
+        Self {
+            rate_limiters: EndpointRateLimiters::new(rate_per_server.max(1)),
+            connections: DashMap::new(),
+            max_connections_per_server: max_connections_per_server.max(1) as usize,
+            overrides: overrides
+                .iter()
+                .map(|(suffix, server)| (suffix.trim_start_matches('.').to_lowercase(), server.trim().to_string()))
+                .collect(),
+            servers: DashMap::new(),
+            health: DashMap::new(),
+            connected_once: AtomicBool::new(false),
+            disabled: AtomicBool::new(false),
+            proxy,
+            resolver,
+        }
+    }
+
+    /// Whether WHOIS was turned off after port 43 turned out to be
+    /// unreachable, on more than one server.
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.load(Ordering::Relaxed)
+    }
+
+    pub async fn check(&self, domain: &str, timeout: Duration) -> WhoisAnswer {
+        self.answer(domain, timeout, false).await
+    }
+
+    /// Like `check`, but parses a taken domain's response into a record.
+    /// Thin registries (.com, .net) only name the registrar, so their
+    /// "Registrar WHOIS Server:" is asked too and fills in the rest.
+    pub async fn check_detailed(&self, domain: &str, timeout: Duration) -> WhoisAnswer {
+        self.answer(domain, timeout, true).await
+    }
+
+    async fn answer(&self, domain: &str, timeout: Duration, detailed: bool) -> WhoisAnswer {
+        let (server, response) = match self.lookup(domain, timeout).await {
+            Ok(found) => found,
+            Err(e) => {
+                return WhoisAnswer {
+                    explanation: e.message,
+                    availability: Availability::Unknown { reason: e.reason },
+                    record: None,
+                    failure: e.failure,
+                    raw: None,
+                }
+            }
+        };
+
+        let (availability, matched) = parse_availability(&response);
+        let mut explanation = match matched {
+            Some(pattern) => format!("WHOIS matched '{}' at {}", pattern, server),
+            None => format!("WHOIS response from {} matched no known pattern", server),
+        };
+
+        let mut record = None;
+        if detailed && availability.is_taken() {
+            let mut parsed = parse_whois(&response);
+            if let Some(referral) = registrar_server(&response).filter(|r| *r != server) {
+                if let Ok(details) = self.query(&referral, domain, timeout).await {
+                    parsed.merge_referral(parse_whois(&details));
+                    explanation = format!("{}; details from {}", explanation, referral);
+                }
+            }
+            record = Some(parsed);
+        }
+
+        WhoisAnswer { availability, explanation, record, failure: None, raw: Some(response) }
+    }
+
+    /// Queries the TLD's WHOIS server, returning it with the raw response.
+    async fn lookup(&self, domain: &str, timeout: Duration) -> Result<(String, String), QueryError> {
+        if self.is_disabled() {
+            return Err(ProbeError::NoEndpoint("WHOIS disabled: outbound port 43 unreachable".to_string()).into());
+        }
+
+        let suffix = match public_suffix(domain) {
+            Some(s) => s.to_lowercase(),
+            None => return Err(ProbeError::InvalidName.into()),
+        };
+
+        let whois_server = match self.server_for(&suffix, timeout).await {
+            Some(server) => server,
+            None => return Err(ProbeError::NoEndpoint(format!("No WHOIS server for .{}", suffix)).into()),
+        };
+
+        let response = self.query(&whois_server, domain, timeout).await?;
+        Ok((whois_server, response))
+    }
+
+    /// The WHOIS server for a public suffix: the user's overrides first,
+    /// then the bundled table, then a referral query to IANA, asked once
+    /// per TLD. Second-level namespaces like `co.uk` are served by their
+    /// TLD's registry.
+    pub async fn server_for(&self, suffix: &str, timeout: Duration) -> Option<String> {
+        let tld = suffix.rsplit('.').next().unwrap_or(suffix);
+        if let Some(server) = self.overrides.get(suffix).or_else(|| self.overrides.get(tld)) {
+            return Some(server.clone());
+        }
+        if let Some(server) = whois_server_for(suffix).or_else(|| whois_server_for(tld)) {
+            return Some(server.to_string());
+        }
+        let cell = self
+            .servers
+            .entry(tld.to_string())
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .clone();
+
+        // A failed IANA query isn't cached, so the next lookup asks again
+        cell.get_or_try_init(|| async {
+            let response = self.query(IANA_WHOIS, tld, timeout).await?;
+            Ok::<_, QueryError>(parse_iana_referral(&response))
+        })
+        .await
+        .ok()
+        .cloned()
+        .flatten()
+    }
+
+    /// Sends one query to `whois_server` under its connection and rate caps.
+    #[tracing::instrument(
+        name = "whois",
+        skip(self, whois_server, timeout),
+        fields(server = whois_server, latency_ms = tracing::field::Empty)
+    )]
+    async fn query(&self, whois_server: &str, query: &str, timeout: Duration) -> Result<String, QueryError> {
+        if self.health.get(whois_server).is_some_and(|health| health.unreachable()) {
+            let message = format!("WHOIS server {} unreachable, skipped", whois_server);
+            return Err(ProbeError::NoEndpoint(message).into());
+        }
+        let semaphore = self.connection_slots(whois_server);
+        let _permit = match semaphore.acquire().await {
+            Ok(permit) => permit,
+            Err(_) => return Err(ProbeError::TransportError("WHOIS client closed".to_string()).into()),
+        };
+        self.rate_limiters.acquire(whois_server).await;
+
+        let start = Instant::now();
+        let result = self.exchange(whois_server, query, timeout).await;
+        tracing::Span::current().record("latency_ms", start.elapsed().as_millis() as u64);
+        match &result {
+            Ok(response) => tracing::debug!(bytes = response.len(), "WHOIS answer"),
+            Err(e) => tracing::debug!(failure = ?e.failure, "{}", e.message),
+        }
+        result
+    }
+
+    /// Connects and trades one query for the answer.
+    async fn exchange(&self, whois_server: &str, query: &str, timeout: Duration) -> Result<String, QueryError> {
+        let connect = async {
+            match &self.proxy {
+                Some(proxy) => proxy.connect(whois_server, WHOIS_PORT, &self.resolver).await,
+                None => self.resolver.connect(whois_server, WHOIS_PORT).await,
+            }
+        };
+        let stream = match tokio::time::timeout(timeout, connect).await {
+            Ok(Ok(stream)) => {
+                self.record_connect_success(whois_server);
+                stream
+            }
+            Ok(Err(e)) => {
+                self.record_connect_failure(whois_server);
+                return Err(QueryError::io(whois_server, &e));
+            }
+            Err(_) => {
+                self.record_connect_failure(whois_server);
+                return Err(QueryError::timeout(whois_server));
+            }
+        };
+
+        match tokio::time::timeout(timeout, query_whois(stream, query)).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) => Err(QueryError::io(whois_server, &e)),
+            Err(_) => Err(QueryError::timeout(whois_server)),
+        }
+    }
+
+    fn record_connect_success(&self, server: &str) {
+        self.connected_once.store(true, Ordering::Relaxed);
+        let mut health = self.health.entry(server.to_string()).or_default();
+        health.connected = true;
+        health.failures = 0;
+    }
+
+    /// Skips `server` once it keeps failing; WHOIS as a whole only goes once
+    /// several servers fail like that and none ever answered, since one
+    /// registry's server being down says nothing about port 43.
+    fn record_connect_failure(&self, server: &str) {
+        let unreachable = {
+            let mut health = self.health.entry(server.to_string()).or_default();
+            health.failures += 1;
+            health.failures == SERVER_UNREACHABLE_THRESHOLD && !health.connected
+        };
+        if !unreachable {
+            return;
+        }
+        tracing::warn!(server, "WHOIS server unreachable, skipped from now on");
+        let servers = self.health.iter().filter(|health| health.unreachable()).count();
+        let blocked = servers >= PORT_BLOCKED_SERVERS && !self.connected_once.load(Ordering::Relaxed);
+        if blocked && !self.disabled.swap(true, Ordering::Relaxed) {
+            tracing::warn!(servers, "WHOIS disabled: outbound port 43 appears blocked");
+        }
+    }
+
+    fn connection_slots(&self, server: &str) -> Arc<Semaphore> {
+        self.connections
+            .entry(server.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_connections_per_server)))
+            .clone()
+    }
+}
+
+/// The TLD's server from the bundled table.
+fn whois_server_for(tld: &str) -> Option<&'static str> {
+    static SERVERS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    SERVERS
+        .get_or_init(|| {
+            BUNDLED_SERVERS
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| line.split_once(' '))
+                .collect()
+        })
+        .get(tld)
+        .copied()
+}
+
+/// Phrases some registries write in place of a `key: value` line.
+const PHRASES: &[(&str, &str)] = &[
+    ("record created on", "created"),
+    ("record expires on", "expires"),
+    ("record last updated on", "updated"),
+];
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// Lowercased, without the dots some registries pad keys with.
+fn normalize_key(key: &str) -> String {
+    key.trim().trim_end_matches('.').split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// `Key: value`, `Key.....: value` or JPRS's `[Key]  value` (also as
+/// `a. [Key]  value`). A line whose "key" has dots in it, like
+/// `ns1.example.uk 2001:db8::1`, is a value.
+fn key_value(line: &str) -> Option<(String, &str)> {
+    let bracketed = if line.get(1..3) == Some(". ") { &line[3..] } else { line };
+    if let Some((key, value)) = bracketed.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        return Some((normalize_key(key), value.trim()));
+    }
+    let (key, value) = line.split_once(':')?;
+    let key = normalize_key(key);
+    let valid = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || " -_/()'".contains(c));
+    valid.then(|| (key, value.trim()))
+}
+
+/// The response as `(key, value)` pairs in order. Besides ICANN's
+/// `Key: value` lines this reads blocks, where a header is followed by
+/// indented lines (`Name servers:` then one per line, as Nominet, EURid
+/// and SIDN write them): each plain line there is a value of the header,
+/// each `Sub: value` line counts as both `sub` and `header sub`.
+fn pairs(response: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    // The block being read: its header and how far that was indented
+    let mut block: Option<(String, usize)> = None;
+    for line in response.lines() {
+        let text = line.trim();
+        if text.is_empty() {
+            block = None;
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let header = block.as_ref().filter(|(_, at)| indent > *at).map(|(header, _)| header.clone());
+        match (key_value(text), header) {
+            (Some((key, value)), Some(header)) => {
+                if !value.is_empty() {
+                    pairs.push((format!("{} {}", header, key), value.to_string()));
+                    pairs.push((key, value.to_string()));
+                }
+            }
+            (None, Some(header)) => pairs.push((header, text.to_string())),
+            (Some((key, value)), None) => {
+                if !value.is_empty() {
+                    pairs.push((key.clone(), value.to_string()));
+                }
+                block = Some((key, indent));
+            }
+            // A bare line may head a block too (`Nameservers` in .it)
+            (None, None) => {
+                for (phrase, key) in PHRASES {
+                    if text.get(..phrase.len()).is_some_and(|p| p.eq_ignore_ascii_case(phrase)) {
+                        pairs.push((key.to_string(), text[phrase.len()..].trim().to_string()));
+                    }
+                }
+                block = Some((normalize_key(text), indent));
+            }
+        }
+    }
+    pairs
+}
+
+/// Value of the first pair whose key is one of `keys`.
+fn field<'a>(pairs: &'a [(String, String)], keys: &[&str]) -> Option<&'a str> {
+    fields(pairs, keys).into_iter().next()
+}
+
+fn fields<'a>(pairs: &'a [(String, String)], keys: &[&str]) -> Vec<&'a str> {
+    pairs
+        .iter()
+        .filter(|(key, _)| keys.contains(&key.as_str()))
+        .map(|(_, value)| value.as_str())
+        .collect()
+}
+
+/// The registrar's own WHOIS server, as named by a thin registry.
+fn registrar_server(response: &str) -> Option<String> {
+    let server = field(&pairs(response), &["registrar whois server", "whois server"])?.to_string();
+    let server = server.trim_start_matches("whois://").trim_end_matches('/').to_lowercase();
+    (!server.is_empty()).then_some(server)
+}
+
+/// Year, month and day of a date in one of the layouts registries use
+/// besides ISO 8601: `01-Jan-2030`, `2030/01/31`, `2030.01.31`,
+/// `31.01.2030`, `20300131` or `2030. 01. 31.`.
+fn parse_date(date: &str) -> Option<(u32, u32, u32)> {
+    let date = date.replace(". ", ".");
+    let token = date.split_whitespace().next()?.trim_end_matches('.');
+    let month = |m: &str| {
+        m.parse().ok().or_else(|| {
+            let name = m.get(..3)?;
+            MONTHS.iter().position(|n| n.eq_ignore_ascii_case(name)).map(|i| i as u32 + 1)
+        })
     };
-    
-    let whois_server = match tld.as_str() {
-        "com" | "net" => "whois.verisign-grs.com",
-        "org" => "whois.pir.org",
-        "io" => "whois.nic.io",
-        "dev" | "app" => "whois.nic.google",
-        "ai" => "whois.nic.ai",
-        "co" => "whois.nic.co",
-        "me" => "whois.nic.me",
-        _ => return Availability::Unknown { reason: format!("No WHOIS server for .{}", tld) },
+    let (year, month, day) = if token.len() == 8 && token.chars().all(|c| c.is_ascii_digit()) {
+        (token[..4].parse().ok()?, token[4..6].parse().ok()?, token[6..].parse().ok()?)
+    } else {
+        let parts: Vec<&str> = token.split(['-', '/', '.']).collect();
+        let [a, b, c] = parts[..] else {
+            return None;
+        };
+        if a.len() == 4 {
+            (a.parse().ok()?, month(b)?, c.parse().ok()?)
+        } else if c.len() == 4 {
+            let (day, month) = (a.parse::<u32>().ok()?, month(b)?);
+            // Month first, US style, when the second number can't be a month
+            let (day, month) = if month > 12 && day <= 12 { (month, day) } else { (day, month) };
+            (c.parse().ok()?, month, day)
+        } else {
+            return None;
+        }
     };
+    ((1..=12).contains(&month) && (1..=31).contains(&day)).then_some((year, month, day))
+}
+
+/// `date` as `YYYY-MM-DD` when `parse_date` reads it; ISO 8601 dates and
+/// anything unrecognized are kept as they are.
+fn normalize_date(date: &str) -> String {
+    if date.get(4..5) == Some("-") && date.get(7..8) == Some("-") {
+        return date.to_string();
+    }
+    match parse_date(date) {
+        Some((year, month, day)) => format!("{:04}-{:02}-{:02}", year, month, day),
+        None => date.to_string(),
+    }
+}
 
-    let result = tokio::time::timeout(timeout, async {
-        let mut stream = TcpStream::connect((whois_server, WHOIS_PORT)).await?;
-        stream.write_all(format!("{}\r\n", domain).as_bytes()).await?;
-        
-        let mut response = String::new();
-        stream.read_to_string(&mut response).await?;
-        
-        Ok::<_, std::io::Error>(response)
-    }).await;
-
-    match result {
-        Ok(Ok(response)) => {
-            let lower = response.to_lowercase();
-            if lower.contains("no match") 
-                || lower.contains("not found") 
-                || lower.contains("no data found")
-                || lower.contains("no entries found")
-            {
-                Availability::Available
-            } else if lower.contains("domain name:") || lower.contains("registrar:") {
-                Availability::Taken
+/// Picks registration details out of a WHOIS response: ICANN's format,
+/// plus the keys and layouts of registries that write their own
+/// (DENIC, Nominet, EURid, SIDN, Norid, JPRS, Registro.br, ...).
+pub fn parse_whois(response: &str) -> DomainRecord {
+    let pairs = pairs(response);
+    let value = |keys: &[&str]| field(&pairs, keys).map(str::to_string);
+    let date = |keys: &[&str]| field(&pairs, keys).map(normalize_date);
+    let mut nameservers = Vec::new();
+    let found = fields(&pairs, &[
+        "name server",
+        "name servers",
+        "nameserver",
+        "nameservers",
+        "nserver",
+        "domain nameservers",
+        "domain servers in listed order",
+        "host name",
+    ]);
+    for server in found.into_iter().filter_map(|s| s.split_whitespace().next()) {
+        let server = server.trim_end_matches('.').to_lowercase();
+        if !nameservers.contains(&server) {
+            nameservers.push(server);
+        }
+    }
+    DomainRecord {
+        ldh_name: value(&["domain name", "domain"]).map(|n| n.to_lowercase()),
+        // Nominet appends the registrar's tag: "Foo Ltd [Tag = FOO]"
+        registrar: value(&[
+            "registrar",
+            "sponsoring registrar",
+            "registrar name",
+            "registrar organization",
+            "registrar organisation",
+            "authorized agency",
+            "registrar handle",
+        ])
+        .map(|r| r.split(" [").next().unwrap_or_default().to_string()),
+        created: date(&[
+            "creation date",
+            "created",
+            "created on",
+            "registered on",
+            "registration date",
+            "registered date",
+            "registration time",
+        ]),
+        expires: date(&[
+            "registry expiry date",
+            "registrar registration expiration date",
+            "expiry date",
+            "expiration date",
+            "expiration time",
+            "expire date",
+            "expires",
+            "expires on",
+            "expire",
+            "renewal date",
+            "paid-till",
+        ]),
+        updated: date(&["updated date", "last updated", "last update", "last modified", "changed", "modified", "updated"]),
+        // "clientTransferProhibited https://icann.org/epp#..." keeps the code
+        statuses: fields(&pairs, &["domain status", "status", "state"])
+            .into_iter()
+            .filter_map(|s| s.split_whitespace().next())
+            .map(|s| s.trim_end_matches(',').to_string())
+            .collect(),
+        nameservers,
+        entities: value(&["registrant organization", "registrant organisation", "registrant"])
+            .map(|org| RdapEntity {
+                handle: None,
+                roles: vec!["registrant".to_string()],
+                name: Some(org),
+            })
+            .into_iter()
+            .collect(),
+        referral: None,
+        // "signedDelegation" / "unsigned" (ICANN), "Signed" / "Unsigned" (Nominet), "yes" / "no"
+        dnssec: value(&["dnssec"]).and_then(|v| {
+            let v = v.to_lowercase();
+            if v.starts_with("unsigned") || v == "no" || v == "inactive" {
+                Some(false)
+            } else if v.starts_with("signed") || v == "yes" || v == "active" {
+                Some(true)
             } else {
-                Availability::Unknown { reason: "Ambiguous WHOIS response".to_string() }
+                None
             }
+        }),
+    }
+}
+
+/// The `whois:` line of IANA's answer for a TLD.
+fn parse_iana_referral(response: &str) -> Option<String> {
+    response.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("whois:")?.trim();
+        (!value.is_empty()).then(|| value.to_lowercase())
+    })
+}
+
+/// Sends `query` and reads the reply, up to `MAX_RESPONSE` bytes. Not all
+/// registries answer in UTF-8 (.jp, .kr), but the parts we look for are
+/// ASCII, so anything else is replaced rather than refused.
+async fn query_whois(mut stream: TcpStream, query: &str) -> std::io::Result<String> {
+    stream.write_all(format!("{}\r\n", query).as_bytes()).await?;
+
+    let mut response = Vec::new();
+    (&mut stream).take(MAX_RESPONSE).read_to_end(&mut response).await?;
+
+    Ok(String::from_utf8_lossy(&response).into_owned())
+}
+
+const AVAILABLE_PATTERNS: &[&str] = &[
+    "no match",
+    "not found",
+    "no data found",
+    "no entries found",
+    "no information available",
+    "status: free",
+    "status: available",
+    " is free",
+];
+const TAKEN_PATTERNS: &[&str] = &["domain name:", "registrar:", "[domain name]", "nserver:", "status: connect"];
+// Not a bare "reserved": disclaimers end in "all rights reserved"
+const RESERVED_PATTERNS: &[&str] = &[
+    "reserved by the registry",
+    "reserved by registry",
+    "reserved name",
+    "reserved domain",
+    "domain is reserved",
+    "status: reserved",
+];
+const PREMIUM_PATTERNS: &[&str] = &["premium domain", "premium name", "is a premium", "premium price"];
+
+/// Classifies a WHOIS response, returning the pattern that decided it.
+/// Reserved and premium answers often also say "not found", so they're
+/// checked first.
+fn parse_availability(response: &str) -> (Availability, Option<&'static str>) {
+    let lower = response.to_lowercase();
+    if let Some(pattern) = RESERVED_PATTERNS.iter().find(|p| lower.contains(*p)) {
+        (Availability::Reserved, Some(pattern))
+    } else if let Some(pattern) = PREMIUM_PATTERNS.iter().find(|p| lower.contains(*p)) {
+        (Availability::Premium, Some(pattern))
+    } else if let Some(pattern) = AVAILABLE_PATTERNS.iter().find(|p| lower.contains(*p)) {
+        (Availability::Available, Some(pattern))
+    } else if let Some(pattern) = TAKEN_PATTERNS.iter().find(|p| lower.contains(*p)) {
+        (Availability::Taken, Some(pattern))
+    } else {
+        let reason = ProbeError::AmbiguousResponse("Ambiguous WHOIS response".to_string());
+        (Availability::Unknown { reason }, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERISIGN: &str = "   Domain Name: GOOGLE.COM
+   Registry Domain ID: 2138514_DOMAIN_COM-VRSN
+   Registrar WHOIS Server: whois.markmonitor.com
+   Registrar URL: http://www.markmonitor.com
+   Updated Date: 2019-09-09T15:39:04Z
+   Creation Date: 1997-09-15T04:00:00Z
+   Registry Expiry Date: 2028-09-14T04:00:00Z
+   Registrar: MarkMonitor Inc.
+   Registrar IANA ID: 292
+   Registrar Abuse Contact Email: abusecomplaints@markmonitor.com
+   Domain Status: clientDeleteProhibited https://icann.org/epp#clientDeleteProhibited
+   Domain Status: clientTransferProhibited https://icann.org/epp#clientTransferProhibited
+   Name Server: NS1.GOOGLE.COM
+   Name Server: NS2.GOOGLE.COM
+   DNSSEC: unsigned
+   URL of the ICANN Whois Inaccuracy Complaint Form: https://www.icann.org/wicf/
+>>> Last update of whois database: 2024-05-01T12:00:00Z <<<
+
+For more information on Whois status codes, please visit https://icann.org/epp
+";
+
+    const DENIC: &str = "Domain: denic.de
+Nserver: ns1.denic.de
+Nserver: ns2.denic.de
+Nserver: ns4.denic.net
+Dnskey: 257 3 8 AwEAAbDEyNBU5ENPsxcuKr5xMZcQjRy8
+Status: connect
+Changed: 2018-06-28T11:05:36+02:00
+";
+
+    const NOMINET: &str = "
+    Domain name:
+        google.co.uk
+
+    Data validation:
+        Nominet was able to match the registrant's name and address against a 3rd party data source on 10-Dec-2012
+
+    Registrar:
+        Markmonitor Inc. t/a MarkMonitor Inc. [Tag = MARKMONITOR]
+        URL: http://www.markmonitor.com
+
+    Relevant dates:
+        Registered on: 14-Feb-1999
+        Expiry date:  14-Feb-2025
+        Last updated:  13-Jan-2024
+
+    Registration status:
+        Registered until expiry date.
+
+    Name servers:
+        ns1.google.com
+        ns2.google.com
+
+    WHOIS lookup made at 10:00:00 01-May-2024
+";
+
+    const NORID: &str = "Domain Information
+
+NORID Handle...............: NOR3D-NORID
+Domain Name................: norid.no
+Registrar Handle...........: REG1-NORID
+Tech-c Handle..............: NH1R-NORID
+Name Server Handle.........: NSNI7H-NORID
+
+Additional information:
+Created:         1999-11-15
+Last updated:    2023-11-16
+";
+
+    const JPRS: &str = "Domain Information: [ドメイン情報]
+[Domain Name]                   GOOGLE.JP
+
+[Registrant]                    Google LLC
+
+[Name Server]                   ns1.google.com
+[Name Server]                   ns2.google.com
+[Signing Key]                   
+
+[Created on]                    2005/05/30
+[Expires on]                    2026/05/31
+[Status]                        Active
+[Last Updated]                  2025/06/01 01:05:04 (JST)
+";
+
+    #[test]
+    fn parses_icann_format() {
+        let record = parse_whois(VERISIGN);
+        assert_eq!(record.ldh_name.as_deref(), Some("google.com"));
+        assert_eq!(record.registrar.as_deref(), Some("MarkMonitor Inc."));
+        assert_eq!(record.created.as_deref(), Some("1997-09-15T04:00:00Z"));
+        assert_eq!(record.expires.as_deref(), Some("2028-09-14T04:00:00Z"));
+        assert_eq!(record.updated.as_deref(), Some("2019-09-09T15:39:04Z"));
+        assert_eq!(record.statuses, ["clientDeleteProhibited", "clientTransferProhibited"]);
+        assert_eq!(record.nameservers, ["ns1.google.com", "ns2.google.com"]);
+        assert_eq!(record.dnssec, Some(false));
+        assert_eq!(registrar_server(VERISIGN).as_deref(), Some("whois.markmonitor.com"));
+    }
+
+    #[test]
+    fn parses_denic() {
+        let record = parse_whois(DENIC);
+        assert_eq!(record.ldh_name.as_deref(), Some("denic.de"));
+        assert_eq!(record.nameservers, ["ns1.denic.de", "ns2.denic.de", "ns4.denic.net"]);
+        assert_eq!(record.statuses, ["connect"]);
+        assert_eq!(record.updated.as_deref(), Some("2018-06-28T11:05:36+02:00"));
+        assert!(parse_availability(DENIC).0.is_taken());
+        assert!(parse_availability("Domain: free-name.de\nStatus: free\n").0.is_available());
+    }
+
+    #[test]
+    fn parses_nominet_blocks() {
+        let record = parse_whois(NOMINET);
+        assert_eq!(record.ldh_name.as_deref(), Some("google.co.uk"));
+        assert_eq!(record.registrar.as_deref(), Some("Markmonitor Inc. t/a MarkMonitor Inc."));
+        assert_eq!(record.created.as_deref(), Some("1999-02-14"));
+        assert_eq!(record.expires.as_deref(), Some("2025-02-14"));
+        assert_eq!(record.updated.as_deref(), Some("2024-01-13"));
+        assert_eq!(record.nameservers, ["ns1.google.com", "ns2.google.com"]);
+    }
+
+    #[test]
+    fn parses_dotted_keys() {
+        let record = parse_whois(NORID);
+        assert_eq!(record.ldh_name.as_deref(), Some("norid.no"));
+        assert_eq!(record.registrar.as_deref(), Some("REG1-NORID"));
+        assert_eq!(record.created.as_deref(), Some("1999-11-15"));
+        assert_eq!(record.updated.as_deref(), Some("2023-11-16"));
+    }
+
+    #[test]
+    fn parses_jprs_brackets() {
+        let record = parse_whois(JPRS);
+        assert_eq!(record.ldh_name.as_deref(), Some("google.jp"));
+        assert_eq!(record.created.as_deref(), Some("2005-05-30"));
+        assert_eq!(record.expires.as_deref(), Some("2026-05-31"));
+        assert_eq!(record.updated.as_deref(), Some("2025-06-01"));
+        assert_eq!(record.statuses, ["Active"]);
+        assert_eq!(record.nameservers, ["ns1.google.com", "ns2.google.com"]);
+        assert_eq!(record.entities[0].name.as_deref(), Some("Google LLC"));
+    }
+
+    #[test]
+    fn matches_phrases_beside_non_ascii_text() {
+        // `İ` lowercases to more bytes than it takes
+        let pairs = pairs("Record created on 2001-02-03 İİİİ\nİİ Record expires on 2030-01-01\n");
+        assert_eq!(pairs, [("created".to_string(), "2001-02-03 İİİİ".to_string())]);
+    }
+
+    #[test]
+    fn normalizes_date_layouts() {
+        for date in ["31-Jan-2030", "2030/01/31", "2030.01.31", "31.01.2030", "20300131", "2030. 01. 31.", "01/31/2030"] {
+            assert_eq!(normalize_date(date), "2030-01-31", "{}", date);
         }
-        Ok(Err(e)) => Availability::Unknown { reason: format!("WHOIS error: {}", e) },
-        Err(_) => Availability::Unknown { reason: "WHOIS timeout".to_string() },
+        assert_eq!(normalize_date("before 2001"), "before 2001");
     }
 }
@@ -0,0 +1,26 @@
+#!/bin/sh
+# Replaces librdap-storm's bundled RDAP bootstrap with IANA's current
+# dns.json, the snapshot the prober falls back to when IANA is unreachable.
+# Run before tagging a release (the release workflow does).
+set -eu
+
+URL="https://data.iana.org/rdap/dns.json"
+DEST="$(dirname "$0")/../librdap-storm/src/bootstrap.json"
+TMP="$(mktemp)"
+trap 'rm -f "$TMP"' EXIT
+
+curl --fail --silent --show-error --location "$URL" -o "$TMP"
+
+# Refuse anything that isn't a plausible bootstrap, so a captive portal or
+# an error page never ends up compiled into the library
+python3 - "$TMP" <<'PY'
+import json, sys
+doc = json.load(open(sys.argv[1]))
+tlds = sum(len(service[0]) for service in doc["services"])
+if tlds < 500:
+    sys.exit(f"only {tlds} TLDs in the bootstrap, not replacing the snapshot")
+print(f"{tlds} TLDs in {len(doc['services'])} services, published {doc['publication']}")
+PY
+
+mv "$TMP" "$DEST"
+trap - EXIT
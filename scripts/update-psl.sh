@@ -0,0 +1,32 @@
+#!/bin/sh
+# Replaces librdap-storm's bundled Public Suffix List with the ICANN
+# section of the current list. Private suffixes (github.io and the like)
+# aren't registries, so they are left out.
+set -eu
+
+URL="https://publicsuffix.org/list/public_suffix_list.dat"
+DEST="$(dirname "$0")/../librdap-storm/src/public_suffix_list.dat"
+TMP="$(mktemp)"
+trap 'rm -f "$TMP"' EXIT
+
+if [ $# -gt 0 ]; then
+    # A local copy, e.g. /usr/share/publicsuffix/public_suffix_list.dat
+    cp "$1" "$TMP"
+else
+    curl --fail --silent --show-error --location "$URL" -o "$TMP"
+fi
+
+{
+    # The list's license header
+    sed -n '1,/^$/p' "$TMP"
+    sed -n '/===BEGIN ICANN DOMAINS===/,/===END ICANN DOMAINS===/p' "$TMP"
+} > "$DEST.new"
+
+rules=$(grep -cv '^\(//.*\)\?$' "$DEST.new" || true)
+if [ "$rules" -lt 5000 ]; then
+    rm -f "$DEST.new"
+    echo "only $rules rules in the ICANN section, not replacing the list" >&2
+    exit 1
+fi
+mv "$DEST.new" "$DEST"
+echo "$rules rules"